@@ -0,0 +1,72 @@
+use arboard::Clipboard;
+use chrono::Local;
+use std::process::Command;
+
+use crate::error::{PromptBankError, Result};
+
+/// Variables filled in automatically when a prompt is rendered
+pub const NAMES: [&str; 6] = [
+    "__date",
+    "__time",
+    "__cwd",
+    "__git_branch",
+    "__user",
+    "__clipboard",
+];
+
+/// `--var name=@clipboard` fills a variable with the clipboard's text
+pub const CLIPBOARD_VALUE: &str = "@clipboard";
+
+/// Whether a variable is filled in automatically rather than asked for
+pub fn is_builtin(name: &str) -> bool {
+    NAMES.contains(&name)
+}
+
+/// Current value of a built-in variable
+///
+/// `None` when the value can't be found out, e.g. the branch outside a git
+/// repository, so the variable falls back to its default like any other.
+pub fn value(name: &str) -> Option<String> {
+    match name {
+        "__date" => Some(Local::now().format("%Y-%m-%d").to_string()),
+        "__time" => Some(Local::now().format("%H:%M").to_string()),
+        "__cwd" => std::env::current_dir()
+            .ok()
+            .map(|dir| dir.display().to_string()),
+        "__git_branch" => git_branch(),
+        "__user" => std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok()
+            .filter(|user| !user.is_empty()),
+        "__clipboard" => clipboard_text().ok(),
+        _ => None,
+    }
+}
+
+/// Text currently on the clipboard
+pub fn clipboard_text() -> Result<String> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| PromptBankError::Clipboard(format!("Cannot read the clipboard: {}", e)))
+}
+
+/// Branch checked out in the working directory, or the commit when detached
+fn git_branch() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if branch != "HEAD" {
+        return Some(branch);
+    }
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
@@ -1,841 +1,6389 @@
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use colored::*;
-use dialoguer::{Editor, Input, Select};
-use std::path::PathBuf;
-
-use crate::claude::{ClaudeIntegration, InstallType};
+use dialoguer::{Confirm, Editor, FuzzySelect, Input, MultiSelect, Select};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::builtins;
+use crate::claude::{ArtifactState, ClaudeIntegration, InstallType};
 use crate::community::Community;
+use crate::completions;
+use crate::config::{self, Config, CopyOnAdd};
+use crate::embeddings::Embedder;
 use crate::error::{PromptBankError, Result};
-use crate::prompt::{Prompt, PromptBank, PromptCategory};
-use crate::storage::Storage;
+use crate::highlight::{self, Highlighter, Segment};
+use crate::history::{self, ApplyEvent, UseEvent};
+use crate::include;
+use crate::ingest::{self, FrontMatter, ImportMap, MapRule};
+use crate::init;
+use crate::lint::{self, Finding, Level, Rule, Span};
+use crate::markdown;
+use crate::net;
+use crate::pager::{self, Pager};
+use crate::pipeline::{self, Pipeline};
+use crate::profile::{self, Phase};
+use crate::prompt::{self, closest_names, list_items, slugify, ClipboardMode, ContentSize, Includes, Prompt, PromptBank, PromptCategory, TemplateEngine, VariableDef, VariableType, BANK_VERSION, BUILTIN_CATEGORIES};
+use crate::recommend::{Recommendations, RECOMMENDED_FILE};
+use crate::storage::{JournalRecovery, Storage, StorageFormat, DEFAULT_BANK};
+use crate::template;
+use crate::tui::Tui;
+use crate::tutorial;
+use tracing::info;
 
 #[derive(Parser)]
 #[command(name = "promptbank")]
 #[command(author, version, about = "Manage and apply prompts for Claude AI")]
 pub struct Cli {
+    /// Prompt bank to operate on
+    #[arg(long, global = true, default_value = DEFAULT_BANK)]
+    pub bank: String,
+
+    /// Output format for list, get, search, grep, info, stats, vars, status, config list, apply,
+    /// lint, and history export
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Disable colored output (also honored via NO_COLOR)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Print where the command spent its time (to stderr)
+    #[arg(long, global = true)]
+    pub profile: bool,
+
+    /// Never pipe long output through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Fail instead of prompting for input (implied when stdin isn't a terminal)
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+
+    /// Log what promptbank does to stderr (-v for file writes and network
+    /// requests, -vv for paths and other detail)
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Only log errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Also append a debug log to promptbank.log in the data directory
+    #[arg(long, global = true)]
+    pub log_file: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
-#[derive(Subcommand)]
-pub enum Commands {
-    /// Add a new prompt
-    Add {
-        /// Name of the prompt
-        #[arg(short, long)]
-        name: Option<String>,
+/// Arguments for `add`
+#[derive(Args)]
+pub struct AddArgs {
+    /// Name of the prompt
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Category (system, skill, agent, role, task, template, or a custom category;
+    /// default: the `add.default_category` setting)
+    #[arg(short, long)]
+    pub category: Option<String>,
+
+    /// Description of the prompt
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Tags (comma-separated)
+    #[arg(short, long)]
+    pub tags: Option<String>,
+
+    /// Aliases (comma-separated), e.g. short slash-command names
+    #[arg(long)]
+    pub aliases: Option<String>,
+
+    /// Content of the prompt (read from stdin when piped, otherwise opens the editor)
+    #[arg(long)]
+    pub content: Option<String>,
+
+    /// Read content from a file ('-' for stdin)
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+
+    /// Download content from a URL, taking metadata from its front matter
+    #[arg(long, conflicts_with_all = ["content", "file"])]
+    pub url: Option<String>,
+
+    /// Add every .md, .markdown, and .txt file below a directory, named after
+    /// the file unless its front matter says otherwise
+    #[arg(long, conflicts_with_all = ["name", "aliases", "content", "file", "url"])]
+    pub dir: Option<PathBuf>,
+
+    /// What to do if a prompt with the same name exists
+    #[arg(long, value_enum, default_value_t = OnConflict::Abort)]
+    pub on_conflict: OnConflict,
+
+    /// Copy the new prompt's ID to the clipboard
+    #[arg(long, conflicts_with = "dir")]
+    pub copy_id: bool,
+
+    /// Copy the new prompt's name to the clipboard
+    #[arg(long, conflicts_with_all = ["dir", "copy_id"])]
+    pub copy_name: bool,
+}
 
-        /// Category (system, skill, agent, role, task, template)
-        #[arg(short, long)]
-        category: Option<String>,
+/// Arguments for `list`
+#[derive(Args)]
+pub struct ListArgs {
+    /// Filter by category
+    #[arg(short, long)]
+    pub category: Option<String>,
 
-        /// Description of the prompt
-        #[arg(short, long)]
-        description: Option<String>,
+    /// Show full content
+    #[arg(long, conflicts_with = "no_full")]
+    pub full: bool,
 
-        /// Tags (comma-separated)
-        #[arg(short, long)]
-        tags: Option<String>,
+    /// Only show summaries, even if the config says to show full content
+    #[arg(long)]
+    pub no_full: bool,
 
-        /// Content of the prompt (opens editor if not provided)
-        #[arg(long)]
-        content: Option<String>,
+    /// Show full content as raw text instead of rendered Markdown
+    #[arg(long, conflicts_with = "no_full")]
+    pub plain: bool,
 
-        /// Read content from a file
-        #[arg(short, long)]
-        file: Option<PathBuf>,
-    },
+    /// Include archived prompts
+    #[arg(long)]
+    pub archived: bool,
 
-    /// List all prompts
-    List {
-        /// Filter by category
-        #[arg(short, long)]
-        category: Option<String>,
+    /// Show each prompt's length in characters, words, and estimated tokens
+    #[arg(long)]
+    pub size: bool,
 
-        /// Show full content
-        #[arg(long)]
-        full: bool,
-    },
+    /// Sort order (default: order added)
+    #[arg(long, value_enum)]
+    pub sort: Option<ListSort>,
+
+    /// Reverse the sort order
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Only prompts with this tag (repeatable; all must match)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Match prompts with any of the given tags instead of all
+    #[arg(long, requires = "tags")]
+    pub any_tag: bool,
+
+    /// Only prompts created on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    pub created_after: Option<String>,
+
+    /// Only prompts updated on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    pub updated_since: Option<String>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+/// Pagination for `list` and `search`
+#[derive(Args)]
+pub struct PageArgs {
+    /// Show at most this many prompts
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Skip this many prompts
+    #[arg(long, default_value_t = 0)]
+    pub offset: usize,
+}
+
+impl PageArgs {
+    /// Cut the requested page out of a full result list
+    fn apply<T>(&self, items: Vec<T>) -> Vec<T> {
+        let page = items.into_iter().skip(self.offset);
+        match self.limit {
+            Some(limit) => page.take(limit).collect(),
+            None => page.collect(),
+        }
+    }
+
+    /// Point at the next page if the results were cut short
+    fn print_footer(&self, shown: usize, total: usize) {
+        if shown == 0 {
+            println!(
+                "{} Offset {} is past the last of {} prompt(s)",
+                "→".yellow(),
+                self.offset,
+                total
+            );
+            return;
+        }
+
+        let end = self.offset + shown;
+        if self.offset > 0 || end < total {
+            println!(
+                "{} Showing {}-{} of {}",
+                "→".blue(),
+                self.offset + 1,
+                end,
+                total
+            );
+        }
+        if end < total {
+            println!("  Next page: {}", format!("--offset {}", end).cyan());
+        }
+    }
+}
+
+/// Orderings for `list --sort`
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ListSort {
+    /// Alphabetically by name
+    Name,
+    /// Newest first
+    Created,
+    /// Most recently edited first
+    Updated,
+    /// By category, then name
+    Category,
+    /// Most used first
+    Usage,
+}
+
+/// What `add` and `import` do with a prompt whose name is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    /// Stop with an error
+    Abort,
+    /// Add a numeric suffix to the new prompt's name ("name-2")
+    Suffix,
+    /// Replace the existing prompt
+    Overwrite,
+}
+
+/// Arguments for `apply`
+#[derive(Args)]
+pub struct ApplyArgs {
+    /// Variable substitutions (format: key=value)
+    // No `-v`: it is the global --verbose
+    #[arg(long)]
+    pub var: Vec<String>,
+
+    /// Read variable values from a JSON or YAML file of name: value pairs
+    /// (--var overrides them)
+    #[arg(long, value_name = "FILE")]
+    pub vars_file: Option<PathBuf>,
+
+    /// Another prompt to render after this one with the same values, e.g. a
+    /// task prompt after a role prompt (repeatable)
+    #[arg(short, long = "with", value_name = "ID")]
+    pub with: Vec<String>,
+
+    /// Text between the prompts combined with --with, where \n is a newline
+    /// (default: apply.separator, or a blank line)
+    #[arg(long, requires = "with")]
+    pub separator: Option<String>,
+
+    /// Copy to clipboard
+    #[arg(short, long, conflicts_with = "no_copy")]
+    pub copy: bool,
+
+    /// Don't copy to clipboard, even if the prompt or config says so
+    #[arg(long)]
+    pub no_copy: bool,
+
+    /// Interactive mode for variables
+    #[arg(short, long)]
+    pub interactive: bool,
+
+    /// Fill in text variables in the editor instead of on a single line
+    /// (implies --interactive)
+    #[arg(long)]
+    pub editor_vars: bool,
+
+    /// Reuse the variable values from the last time this prompt was applied
+    /// (--var overrides them)
+    #[arg(long)]
+    pub last: bool,
+
+    /// Fill variables that are still unset from environment variables of the
+    /// same name (or the one given by the variable's `env`)
+    #[arg(long)]
+    pub env: bool,
+
+    /// Print a chat messages JSON array, with the prompt as the system message
+    #[arg(long)]
+    pub messages: bool,
+
+    /// User message to pair with the prompt (with --messages)
+    #[arg(long, requires = "messages")]
+    pub user: Option<String>,
+
+    /// Run the commands in `{{cmd:...}}` placeholders and inline their output
+    #[arg(long)]
+    pub allow_exec: bool,
+
+    /// Read the files in `{{file:...}}` placeholders and inline them
+    #[arg(long)]
+    pub allow_files: bool,
+
+    /// Fail instead of warning when the result is over the prompt's
+    /// max_tokens budget
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Wrap the result in an XML tag, e.g. --wrap-xml instructions
+    #[arg(long, value_name = "TAG")]
+    pub wrap_xml: Option<String>,
+
+    /// Write the result to a file instead of printing it
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Add to the end of the --output file instead of replacing it
+    #[arg(long, requires = "output")]
+    pub append: bool,
+
+    /// Run a command with the result on its stdin instead of printing it,
+    /// e.g. --exec "claude -p"
+    #[arg(long, value_name = "COMMAND", conflicts_with = "output")]
+    pub exec: Option<String>,
+
+    /// Only report which variables have a value and which are missing,
+    /// without rendering (exits with an error if any are missing)
+    #[arg(long, conflicts_with_all = ["interactive", "editor_vars", "copy", "user", "output", "exec"])]
+    pub check: bool,
+}
+
+/// Candidate sets printed by the hidden `__complete` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionKind {
+    /// Prompt IDs and names
+    Prompts,
+    /// Tags used across the bank
+    Tags,
+}
+
+/// Output formats supported by the global --format flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Yaml,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Add a new prompt
+    Add(AddArgs),
+
+    /// List all prompts
+    List(ListArgs),
 
     /// Get a specific prompt by ID or name
+    #[command(visible_alias = "show")]
     Get {
-        /// ID or name of the prompt
-        id: String,
+        /// ID or name of the prompt (opens a picker if omitted)
+        id: Option<String>,
+
+        /// Pick the prompt interactively
+        #[arg(long)]
+        pick: bool,
 
         /// Copy to clipboard
         #[arg(short, long)]
         copy: bool,
 
         /// Only output the content (for piping)
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "no_raw")]
         raw: bool,
+
+        /// Only output these fields (comma-separated, e.g. name,description,variables)
+        #[arg(long, value_delimiter = ',', conflicts_with_all = ["raw", "copy", "plain"])]
+        fields: Vec<String>,
+
+        /// Shorthand for --format json
+        #[arg(long)]
+        json: bool,
+
+        /// Show the whole prompt, even if the config says to only output content
+        #[arg(long)]
+        no_raw: bool,
+
+        /// Show content as raw text instead of rendered Markdown
+        #[arg(long)]
+        plain: bool,
+
+        /// Show the prompt as it was at a date (YYYY-MM-DD) or RFC 3339 time
+        #[arg(long, value_name = "DATE")]
+        as_of: Option<String>,
+
+        /// Write the content to a file instead of printing it
+        #[arg(short, long, value_name = "PATH", conflicts_with = "fields")]
+        output: Option<PathBuf>,
+
+        /// Add to the end of the --output file instead of replacing it
+        #[arg(long, requires = "output")]
+        append: bool,
     },
 
     /// Apply a prompt (render with variables)
     Apply {
-        /// ID or name of the prompt
-        id: String,
-
-        /// Variable substitutions (format: key=value)
-        #[arg(short, long)]
-        var: Vec<String>,
+        /// ID or name of the prompt (opens a picker if omitted)
+        id: Option<String>,
 
-        /// Copy to clipboard
-        #[arg(short, long)]
-        copy: bool,
+        /// Pick the prompt interactively
+        #[arg(long)]
+        pick: bool,
 
-        /// Interactive mode for variables
-        #[arg(short, long)]
-        interactive: bool,
+        #[command(flatten)]
+        args: ApplyArgs,
     },
 
     /// Edit an existing prompt
     Edit {
+        /// ID or name of the prompt (opens a picker if omitted)
+        id: Option<String>,
+
+        /// Pick the prompt interactively
+        #[arg(long)]
+        pick: bool,
+
+        /// Show the rendered result after each save before keeping it
+        #[arg(long)]
+        preview: bool,
+
+        /// Save without showing the changes and asking first
+        #[arg(long, conflicts_with_all = ["meta", "all"])]
+        no_confirm: bool,
+
+        /// Edit every field, not just the content, as YAML
+        #[arg(long, conflicts_with = "preview")]
+        meta: bool,
+
+        /// Edit every field of all active prompts (or those in --category) in one YAML file
+        #[arg(long, conflicts_with_all = ["id", "pick", "preview", "meta", "name", "description", "tags"])]
+        all: bool,
+
+        /// New name (changes metadata without opening the editor)
+        #[arg(long, conflicts_with_all = ["preview", "meta"])]
+        name: Option<String>,
+
+        /// New description
+        #[arg(short, long, conflicts_with_all = ["preview", "meta"])]
+        description: Option<String>,
+
+        /// New tags (comma-separated, replacing the current ones)
+        #[arg(short, long, conflicts_with_all = ["preview", "meta"])]
+        tags: Option<String>,
+
+        /// New category; with --all, only edit prompts in this category
+        #[arg(short, long, conflicts_with_all = ["preview", "meta"])]
+        category: Option<String>,
+    },
+
+    /// Set whether applying a prompt copies it to the clipboard
+    Clipboard {
         /// ID or name of the prompt
         id: String,
+
+        /// always, never, or default (follow the apply.copy setting)
+        mode: ClipboardMode,
     },
 
-    /// Delete a prompt
-    Delete {
+    /// Rename a prompt
+    Rename {
         /// ID or name of the prompt
         id: String,
 
-        /// Skip confirmation
+        /// New name
+        new_name: String,
+
+        /// Also replace the ID with a slug of the new name
+        #[arg(long)]
+        slug_id: bool,
+
+        /// Rename even if another prompt already uses the name
         #[arg(short, long)]
         force: bool,
     },
 
-    /// Search prompts
-    Search {
-        /// Search query
-        query: String,
+    /// Duplicate a prompt under a new ID
+    Duplicate {
+        /// ID or name of the prompt
+        id: String,
+
+        /// Name of the copy (defaults to "<name>-copy")
+        #[arg(short, long)]
+        name: Option<String>,
     },
 
-    /// Export prompts to a file
-    Export {
-        /// Output file path
-        output: PathBuf,
+    /// Transfer a prompt to another author
+    Chown {
+        /// ID or name of the prompt
+        id: String,
+
+        /// New author
+        author: String,
     },
 
-    /// Import prompts from a file
-    Import {
-        /// Input file path
-        input: PathBuf,
+    /// Delete a prompt
+    Delete {
+        /// ID or name of the prompt (opens a picker if omitted)
+        id: Option<String>,
 
-        /// Merge with existing prompts
+        /// Pick the prompt interactively
+        #[arg(long)]
+        pick: bool,
+
+        /// Pick several prompts from a checklist
+        #[arg(short, long, conflicts_with_all = ["id", "pick"])]
+        interactive: bool,
+
+        /// Skip confirmation
         #[arg(short, long)]
-        merge: bool,
+        force: bool,
+
+        /// Show what would be deleted without saving
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Show storage info
-    Info,
+    /// Copy a prompt into another bank
+    Copy {
+        /// ID or name of the prompt
+        id: String,
 
-    /// Update promptbank to the latest version
-    Update,
+        /// Destination bank
+        #[arg(long)]
+        to: String,
 
-    /// Community prompts - browse, install, and share
-    #[command(subcommand)]
-    Community(CommunityCommands),
+        /// Overwrite a prompt with the same name in the destination bank
+        #[arg(short, long)]
+        force: bool,
+    },
 
-    /// Claude integration - install prompts as skills/commands
-    #[command(subcommand)]
-    Claude(ClaudeCommands),
-}
+    /// Recategorize/retag a prompt or move it into another bank
+    Move {
+        /// ID or name of the prompt
+        id: String,
 
-#[derive(Subcommand)]
-pub enum CommunityCommands {
-    /// Browse available community prompts
-    Browse {
-        /// Filter by category
+        /// New category
         #[arg(short, long)]
         category: Option<String>,
-    },
 
-    /// Install a prompt from the community
-    Install {
-        /// Name of the prompt to install
-        name: String,
+        /// Replace tags (comma-separated, empty to clear)
+        #[arg(short, long)]
+        tags: Option<String>,
 
-        /// Apply (output) the prompt after installing
+        /// Destination bank
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Overwrite a prompt with the same name in the destination bank
         #[arg(short, long)]
-        apply: bool,
+        force: bool,
+    },
 
-        /// Copy to clipboard after installing
+    /// Show the most recently used prompts
+    Recent {
+        /// Number of prompts to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+
+    /// Review stale prompts and archive or delete them in bulk
+    Prune {
+        /// Pick prompts from a checklist and choose an action
         #[arg(short, long)]
-        copy: bool,
+        interactive: bool,
     },
 
-    /// Search community prompts
+    /// Search prompts
     Search {
-        /// Search query
+        /// Search query; quote phrases and narrow with tag:, category:, name:,
+        /// description:, content:, or var: filters
         query: String,
-    },
 
-    /// Show info about contributing
-    Contribute,
-}
+        /// Rank prompts by meaning using an embeddings API instead of keywords
+        #[arg(long)]
+        semantic: bool,
 
-#[derive(Subcommand)]
-pub enum ClaudeCommands {
-    /// Install a prompt as a Claude skill or command
-    Install {
-        /// ID or name of the prompt to install
-        id: String,
+        /// Include archived prompts
+        #[arg(long)]
+        archived: bool,
 
-        /// Install as skill (default) or command
-        #[arg(long, value_parser = ["skill", "command"], default_value = "skill")]
-        as_type: String,
+        #[command(flatten)]
+        page: PageArgs,
     },
 
-    /// List prompts installed in Claude
-    List,
-
-    /// Remove a prompt from Claude
-    Remove {
-        /// Name of the skill/command to remove
-        name: String,
-    },
-}
+    /// Show the lines of prompt content that match a regular expression
+    Grep {
+        /// Pattern to look for (a regular expression unless --fixed-strings)
+        pattern: String,
 
-pub struct App {
-    storage: Storage,
-    bank: PromptBank,
-}
+        /// Lines of context to show around each match
+        #[arg(short = 'C', long, default_value_t = 2)]
+        context: usize,
 
-impl App {
-    pub fn new() -> Result<Self> {
-        let storage = Storage::new()?;
-        let bank = storage.load()?;
-        Ok(Self { storage, bank })
-    }
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
 
-    pub fn run(&mut self, cli: Cli) -> Result<()> {
-        match cli.command {
-            Commands::Add {
-                name,
-                category,
-                description,
-                tags,
-                content,
-                file,
-            } => self.add_prompt(name, category, description, tags, content, file),
+        /// Treat the pattern as plain text
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
 
-            Commands::List { category, full } => self.list_prompts(category, full),
+        /// Include archived prompts
+        #[arg(long)]
+        archived: bool,
+    },
 
-            Commands::Get { id, copy, raw } => self.get_prompt(&id, copy, raw),
+    /// Find and replace text in the content of many prompts at once
+    Replace {
+        /// Text to look for
+        #[arg(long)]
+        pattern: String,
 
-            Commands::Apply {
-                id,
-                var,
-                copy,
-                interactive,
-            } => self.apply_prompt(&id, var, copy, interactive),
+        /// Replacement text (with --regex, $1 or ${name} insert capture groups)
+        #[arg(long = "with", value_name = "TEXT")]
+        replacement: String,
 
-            Commands::Edit { id } => self.edit_prompt(&id),
+        /// Treat the pattern as a regular expression
+        #[arg(long)]
+        regex: bool,
 
-            Commands::Delete { id, force } => self.delete_prompt(&id, force),
+        /// Only prompts with this tag (repeatable; all must match)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
 
-            Commands::Search { query } => self.search_prompts(&query),
+        /// Show the changes without saving them
+        #[arg(long)]
+        dry_run: bool,
+    },
 
-            Commands::Export { output } => self.export_prompts(&output),
+    /// Archive a prompt, hiding it from list and search
+    Archive {
+        /// ID or name of the prompt
+        id: String,
+    },
 
-            Commands::Import { input, merge } => self.import_prompts(&input, merge),
+    /// Restore an archived prompt
+    Unarchive {
+        /// ID or name of the prompt
+        id: String,
+    },
 
-            Commands::Info => self.show_info(),
+    /// Export prompts to a file
+    Export {
+        /// Output file path
+        output: PathBuf,
 
-            Commands::Update => self.update_self(),
+        /// Include usage counters and history (for moving to a new machine)
+        #[arg(long)]
+        with_state: bool,
+
+        /// Pick the prompts to export from a checklist
+        #[arg(short, long)]
+        interactive: bool,
+    },
+
+    /// Import prompts from an export file or a directory of prompt files
+    Import {
+        /// Input file or directory path
+        input: PathBuf,
+
+        /// Merge with existing prompts
+        #[arg(short, long)]
+        merge: bool,
+
+        /// Mapping file assigning names, categories, and tags by path (directories only)
+        #[arg(long)]
+        map: Option<PathBuf>,
+
+        /// Also import usage counters and history from the file
+        #[arg(long, conflicts_with = "map")]
+        with_state: bool,
+
+        /// What to do with prompts whose name is already taken
+        #[arg(long, value_enum, default_value_t = OnConflict::Abort)]
+        on_conflict: OnConflict,
+
+        /// Show which prompts would be added, overwritten, or removed without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check prompts for problems (exits non-zero if any errors are found)
+    Lint {
+        /// Print the findings as a SARIF 2.1.0 log, e.g. for code scanning
+        #[arg(long)]
+        sarif: bool,
+    },
+
+    /// Check prompt templates for malformed or inconsistent variables and
+    /// overly long lines
+    Validate {
+        /// ID or name of the prompt (default: every prompt)
+        id: Option<String>,
+
+        /// Print the findings as a SARIF 2.1.0 log, e.g. for code scanning
+        #[arg(long)]
+        sarif: bool,
+    },
+
+    /// List every variable used across prompts, with the prompts using it
+    Vars {
+        /// Include archived prompts
+        #[arg(long)]
+        archived: bool,
+    },
+
+    /// Generate a categorized index of a markdown bank
+    Index {
+        /// Write the index to this file in the bank directory and keep it updated on save
+        #[arg(long)]
+        write: Option<PathBuf>,
+    },
+
+    /// Install prompts recommended for the current repository
+    Recommend {
+        /// Write a recommendations file listing these prompts (defaults to
+        /// the prompts installed into this project)
+        #[arg(long)]
+        write: bool,
+
+        /// Prompt names to recommend (with --write)
+        names: Vec<String>,
+
+        /// Install missing prompts without asking
+        #[arg(short, long)]
+        yes: bool,
+
+        /// What to do with prompts whose name is already taken
+        #[arg(long, value_enum, default_value_t = OnConflict::Abort)]
+        on_conflict: OnConflict,
+    },
+
+    /// Browse prompts in an interactive terminal UI
+    Tui,
+
+    /// Set up the data directory, editor, and default category, and add example prompts
+    Init,
+
+    /// Learn the basics in a temporary bank
+    Tutorial,
+
+    /// Check the bank for problems and quota overruns
+    Doctor,
+
+    /// Check that the bank exists, is current, has the repository's
+    /// recommended prompts, and matches what is installed in Claude
+    Status {
+        /// Exit non-zero if anything needs attention
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Show storage info
+    Info,
+
+    /// Show counts per category, tag and month, content length, variable
+    /// usage and the most used prompts
+    Stats {
+        /// Include archived prompts
+        #[arg(long)]
+        archived: bool,
+    },
+
+    /// Open the data directory in the file manager, or a prompt's file in the editor
+    Open {
+        /// Prompt whose file to edit (markdown storage only)
+        id: Option<String>,
+
+        /// Print the path instead of opening it
+        #[arg(long)]
+        path: bool,
+    },
+
+    /// Keep a prompt's content in sync with a file, updating it whenever the file is saved
+    Watch {
+        /// ID or name of the prompt
+        id: String,
+
+        /// File to read the content from (front matter is skipped)
+        #[arg(short, long)]
+        file: PathBuf,
+    },
+
+    /// Update promptbank to the latest version
+    Update,
+
+    /// Generate shell completions
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+
+    /// List completion candidates (used by shell completion scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Kind of candidates to list
+        #[arg(value_enum)]
+        kind: CompletionKind,
+    },
+
+    /// Manage custom categories
+    #[command(subcommand)]
+    Category(CategoryCommands),
+
+    /// Show and change settings in config.toml
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// Community prompts - browse, install, and share
+    #[command(subcommand)]
+    Community(CommunityCommands),
+
+    /// Claude integration - install prompts as skills/commands
+    #[command(subcommand)]
+    Claude(ClaudeCommands),
+
+    /// Pipelines - apply prompts in turn, each fed the output of the last
+    #[command(subcommand)]
+    Pipeline(PipelineCommands),
+
+    /// Show recent applications of prompts and the values used, or export them
+    History(HistoryArgs),
+}
+
+/// Arguments for `history`
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    pub command: Option<HistoryCommands>,
+
+    /// Only applications of this prompt
+    pub id: Option<String>,
+
+    /// Number of applications to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommands {
+    /// Export apply events (prompt, time, and number of variables, never values)
+    Export {
+        /// Only events newer than this age (e.g. 30d, 12h, 2w) or date
+        #[arg(long, value_name = "AGE")]
+        since: Option<String>,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Write comma-separated values, e.g. for a spreadsheet
+        #[arg(long)]
+        csv: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CategoryCommands {
+    /// Register a custom category
+    Add {
+        /// Category name
+        name: String,
+    },
+
+    /// List built-in and custom categories
+    List,
+
+    /// Unregister a custom category
+    Remove {
+        /// Category name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the value of a setting
+    Get {
+        /// Setting, e.g. apply.copy or theme.categories.task
+        key: String,
+    },
+
+    /// Change a setting
+    Set {
+        /// Setting, e.g. apply.copy or theme.categories.task
+        key: String,
+
+        /// New value
+        value: String,
+    },
+
+    /// List all settings with their current values
+    List,
+
+    /// Open config.toml in your editor
+    Edit,
+}
+
+#[derive(Subcommand)]
+pub enum CommunityCommands {
+    /// Browse available community prompts
+    Browse {
+        /// Filter by category
+        #[arg(short, long)]
+        category: Option<String>,
+    },
+
+    /// Install a prompt from the community
+    Install {
+        /// Name of the prompt to install
+        name: String,
+
+        /// Apply (output) the prompt after installing
+        #[arg(short, long)]
+        apply: bool,
+
+        /// Copy to clipboard after installing
+        #[arg(short, long)]
+        copy: bool,
+
+        /// What to do if a prompt with the same name exists
+        #[arg(long, value_enum, default_value_t = OnConflict::Abort)]
+        on_conflict: OnConflict,
+    },
+
+    /// Search community prompts
+    Search {
+        /// Search query
+        query: String,
+    },
+
+    /// Show info about contributing
+    Contribute,
+}
+
+#[derive(Subcommand)]
+pub enum ClaudeCommands {
+    /// Install a prompt as a Claude skill or command
+    Install {
+        /// ID or name of the prompt to install
+        id: String,
+
+        /// Install as skill or command (default: per-category config, else skill)
+        #[arg(long, value_parser = ["skill", "command", "agent"])]
+        as_type: Option<String>,
+
+        /// Install into a project's .claude directory instead of ~/.claude
+        #[arg(long, conflicts_with = "all_projects")]
+        project: Option<PathBuf>,
+
+        /// Reinstall into every project that previously received the prompt
+        #[arg(long)]
+        all_projects: bool,
+
+        /// Also install a command for each of the prompt's aliases
+        #[arg(long)]
+        with_aliases: bool,
+    },
+
+    /// Print the SKILL.md a prompt would install as, without installing it
+    RenderSkill {
+        /// ID or name of the prompt
+        id: String,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// List prompts installed in Claude
+    List,
+
+    /// Compare installed skills/commands with the bank
+    Status {
+        /// Check a project's .claude directory instead of ~/.claude
+        #[arg(long)]
+        project: Option<PathBuf>,
+    },
+
+    /// Remove a prompt from Claude
+    Remove {
+        /// Name of the skill/command to remove
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PipelineCommands {
+    /// Create a pipeline from prompts to apply in order
+    Add {
+        /// Pipeline name
+        name: String,
+
+        /// Prompt to apply, in order; PROMPT:VARIABLE names the variable that
+        /// takes the previous step's output (default: the prompt's only one)
+        #[arg(
+            short,
+            long = "step",
+            value_name = "PROMPT[:VARIABLE]",
+            required = true
+        )]
+        steps: Vec<String>,
+
+        /// Command each step's prompt is piped into, e.g. "claude -p"
+        #[arg(long, value_name = "COMMAND")]
+        exec: Option<String>,
+
+        /// Description
+        #[arg(short, long, default_value = "")]
+        description: String,
+
+        /// Replace a pipeline of the same name
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// List pipelines
+    List,
+
+    /// Show a pipeline's steps
+    Show {
+        /// Pipeline name
+        name: String,
+    },
+
+    /// Apply a pipeline's prompts in turn and print the last one's output
+    Run {
+        /// Pipeline name
+        name: String,
+
+        /// Variable substitutions for any step (format: key=value)
+        #[arg(long)]
+        var: Vec<String>,
+
+        /// Pipe each step into this command instead of the pipeline's own
+        #[arg(long, value_name = "COMMAND")]
+        exec: Option<String>,
+
+        /// Run the pipeline's own command, and the commands in `{{cmd:...}}`
+        /// placeholders
+        #[arg(long)]
+        allow_exec: bool,
+
+        /// Read the files in `{{file:...}}` placeholders
+        #[arg(long)]
+        allow_files: bool,
+    },
+
+    /// Delete a pipeline
+    Remove {
+        /// Pipeline name
+        name: String,
+    },
+}
+
+impl Commands {
+    /// Whether the command reads or writes the prompt bank
+    fn needs_bank(&self) -> bool {
+        match self {
+            Commands::Update
+            | Commands::Completions { .. }
+            | Commands::Init
+            | Commands::Tutorial => false,
+            Commands::Category(cmd) => matches!(cmd, CategoryCommands::List),
+            Commands::Config(_) => false,
+            Commands::Open { id, .. } => id.is_some(),
+            Commands::Community(cmd) => matches!(cmd, CommunityCommands::Install { .. }),
+            Commands::Claude(cmd) => matches!(
+                cmd,
+                ClaudeCommands::Install { .. }
+                    | ClaudeCommands::RenderSkill { .. }
+                    | ClaudeCommands::Status { .. }
+            ),
+            _ => true,
+        }
+    }
+}
+
+/// A single chat message emitted by `apply --messages`
+#[derive(Serialize)]
+struct ChatMessage {
+    role: &'static str,
+    content: String,
+}
+
+/// Rendered prompt emitted by `apply` in structured formats
+#[derive(Serialize)]
+struct RenderedPrompt<'a> {
+    id: &'a str,
+    name: &'a str,
+    content: &'a str,
+}
+
+/// Where `apply` would take a variable's value from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum VariableSource {
+    /// A --var flag
+    Var,
+    /// The --vars-file
+    File,
+    /// The values recorded by the last apply (--last)
+    Last,
+    /// An environment variable (--env)
+    Env,
+    /// The `{{name:default}}` in the content
+    Default,
+    Missing,
+}
+
+impl VariableSource {
+    fn label(self) -> &'static str {
+        match self {
+            VariableSource::Var => "--var",
+            VariableSource::File => "--vars-file",
+            VariableSource::Last => "--last",
+            VariableSource::Env => "--env",
+            VariableSource::Default => "default",
+            VariableSource::Missing => "missing",
+        }
+    }
+}
+
+/// One variable reported by `apply --check`
+#[derive(Serialize)]
+struct VariableCheck {
+    name: String,
+    source: VariableSource,
+    /// Left out for values from the environment, which may be secret
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// Environment variable the value comes from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env: Option<String>,
+}
+
+/// Matching content line emitted by `grep` in structured formats
+#[derive(Serialize)]
+struct GrepMatch<'a> {
+    prompt_id: &'a str,
+    prompt_name: &'a str,
+    /// 1-based line number within the content
+    line: usize,
+    text: &'a str,
+}
+
+/// The fields of a prompt that `edit --meta` lets the user change
+///
+/// The ID, timestamps, and usage data are left out since they are managed
+/// by promptbank.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+struct EditableFields {
+    name: String,
+    description: String,
+    category: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    clipboard: ClipboardMode,
+    #[serde(default)]
+    engine: TemplateEngine,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    /// Lists every variable in the content so its guidance can be filled in
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    variable_defs: BTreeMap<String, VariableDef>,
+    content: String,
+}
+
+/// A prompt in the file opened by `edit --all`
+#[derive(Serialize, Deserialize)]
+struct BulkEntry {
+    id: String,
+    #[serde(flatten)]
+    fields: EditableFields,
+}
+
+impl From<&Prompt> for EditableFields {
+    fn from(prompt: &Prompt) -> Self {
+        Self {
+            name: prompt.name.clone(),
+            description: prompt.description.clone(),
+            category: prompt.category.to_string(),
+            tags: prompt.tags.clone(),
+            aliases: prompt.aliases.clone(),
+            clipboard: prompt.clipboard,
+            engine: prompt.engine,
+            max_tokens: prompt.max_tokens,
+            archived: prompt.archived,
+            author: prompt.author.clone(),
+            variable_defs: prompt
+                .variables
+                .iter()
+                .map(|v| (v.clone(), prompt.variable_def(v)))
+                .chain(prompt.variable_defs.clone())
+                .collect(),
+            content: prompt.content.clone(),
+        }
+    }
+}
+
+/// Storage summary emitted by `info` in structured formats
+#[derive(Serialize)]
+struct InfoReport<'a> {
+    bank: &'a str,
+    data_file: &'a PathBuf,
+    total_prompts: usize,
+    categories: BTreeMap<String, usize>,
+    total_uses: u64,
+    most_used: Vec<PromptUsage<'a>>,
+    size_bytes: u64,
+    warnings: Vec<String>,
+}
+
+/// Bank statistics emitted by `stats` in structured formats
+#[derive(Serialize)]
+struct StatsReport<'a> {
+    total_prompts: usize,
+    categories: BTreeMap<String, usize>,
+    tags: BTreeMap<&'a str, usize>,
+    /// Prompts created per month, keyed `YYYY-MM`
+    created: BTreeMap<String, usize>,
+    /// Prompts last updated per month, keyed `YYYY-MM`
+    updated: BTreeMap<String, usize>,
+    /// Mean content length in characters
+    average_length: usize,
+    /// Number of prompts using each variable
+    variables: BTreeMap<&'a str, usize>,
+    most_used: Vec<PromptUsage<'a>>,
+}
+
+/// A lint finding with its location on disk
+#[derive(Serialize)]
+struct LocatedFinding<'a> {
+    #[serde(flatten)]
+    finding: &'a Finding,
+    file: String,
+    line: Option<usize>,
+}
+
+/// Bank health emitted by `status` in structured formats
+#[derive(Serialize)]
+struct StatusReport<'a> {
+    bank: &'a str,
+    data_file: &'a PathBuf,
+    problems: &'a [String],
+}
+
+/// Use count of a single prompt, as reported by `info` and `stats`
+#[derive(Serialize)]
+struct PromptUsage<'a> {
+    id: &'a str,
+    name: &'a str,
+    use_count: u64,
+}
+
+/// A variable and the prompts using it, as reported by `vars`
+#[derive(Serialize)]
+struct VariableUsage<'a> {
+    name: &'a str,
+    /// Names of the prompts using the variable
+    prompts: Vec<&'a str>,
+    /// Other variables that look like the same thing under another name
+    similar: Vec<&'a str>,
+}
+
+/// Suggestion printed alongside quota warnings
+const QUOTA_HINT: &str = "Archive unused prompts with 'promptbank prune' or split the bank with 'promptbank move --to <bank>'";
+
+/// How many of the most used prompts `info` and `stats` report
+const MOST_USED_LIMIT: usize = 5;
+
+/// How long `watch` waits for an editor to finish saving before reading the file
+const WATCH_SETTLE_MS: u64 = 100;
+
+/// How many prompts `search --semantic` shows without `--limit`
+const SEMANTIC_RESULTS: usize = 10;
+
+/// Make room for a prompt whose name another prompt already uses
+///
+/// Depending on `on_conflict`, fails, renames the new prompt, or removes the
+/// prompts it collides with.
+fn settle_name_conflict(
+    bank: &mut PromptBank,
+    prompt: &mut Prompt,
+    on_conflict: OnConflict,
+) -> Result<()> {
+    // `get` resolves IDs before names, so an ID collision hides the prompt too
+    let collides = |p: &Prompt| p.id != prompt.id && (p.name == prompt.name || p.id == prompt.name);
+    if !bank.prompts.iter().any(collides) {
+        return Ok(());
+    }
+
+    match on_conflict {
+        OnConflict::Abort => Err(name_taken(&prompt.name)),
+        OnConflict::Suffix => {
+            let name = bank.unique_name(&prompt.name);
+            println!(
+                "{} '{}' is taken, using '{}'",
+                "→".yellow(),
+                prompt.name,
+                name
+            );
+            prompt.name = name;
+            Ok(())
+        }
+        OnConflict::Overwrite => {
+            println!("{} Replacing existing '{}'", "→".yellow(), prompt.name);
+            bank.prompts.retain(|p| !collides(p));
+            Ok(())
+        }
+    }
+}
+
+fn name_taken(name: &str) -> PromptBankError {
+    PromptBankError::InvalidInput(format!(
+        "A prompt named '{}' already exists (use --on-conflict suffix or overwrite)",
+        name
+    ))
+}
+
+/// Report what replacing `before` with `after` would add, overwrite, and remove
+///
+/// A prompt counts as overwritten when its ID or name survives with different
+/// fields or content.
+fn print_bank_changes(before: &PromptBank, after: &PromptBank) {
+    let same = |a: &Prompt, b: &Prompt| a.id == b.id || a.name == b.name;
+    let mut added = 0;
+    let mut overwritten = 0;
+    for prompt in &after.prompts {
+        match before.prompts.iter().find(|old| same(old, prompt)) {
+            None => {
+                println!("  {} {} ({})", "+".green(), prompt.name, prompt.id.cyan());
+                added += 1;
+            }
+            Some(old) if EditableFields::from(old) != EditableFields::from(prompt) => {
+                println!("  {} {} ({})", "~".yellow(), prompt.name, prompt.id.cyan());
+                overwritten += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    let mut removed = 0;
+    for prompt in &before.prompts {
+        if !after.prompts.iter().any(|new| same(prompt, new)) {
+            println!("  {} {} ({})", "-".red(), prompt.name, prompt.id.cyan());
+            removed += 1;
+        }
+    }
+    println!(
+        "\n{} {} added, {} overwritten, {} removed (dry run, nothing saved)",
+        "→".yellow(),
+        added,
+        overwritten,
+        removed
+    );
+}
+
+/// Show a directory in the platform's file manager
+fn reveal(path: &Path) -> Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    let status = std::process::Command::new(program)
+        .arg(path)
+        .status()
+        .map_err(|e| PromptBankError::InvalidInput(format!("Failed to run {}: {}", program, e)))?;
+    // explorer reports failure even when the window opens
+    if !status.success() && !cfg!(windows) {
+        return Err(PromptBankError::InvalidInput(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+    Ok(())
+}
+
+/// A JSON value as plain text: strings unquoted, null as nothing
+fn field_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// A setting as typed on the command line: strings unquoted, the rest as TOML
+fn setting_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Whether stdin is a pipe or a file that content can be read from
+///
+/// Anything else, like a terminal or a socket a CI runner leaves open, is
+/// not read, so `add` can't wait on it forever.
+#[cfg(unix)]
+fn stdin_is_piped() -> bool {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(fd) = std::io::stdin().as_fd().try_clone_to_owned() else {
+        return false;
+    };
+    std::fs::File::from(fd)
+        .metadata()
+        .is_ok_and(|m| m.file_type().is_fifo() || m.is_file())
+}
+
+#[cfg(not(unix))]
+fn stdin_is_piped() -> bool {
+    !std::io::stdin().is_terminal()
+}
+
+/// Read prompt content piped to stdin
+fn read_stdin() -> Result<String> {
+    let mut content = String::new();
+    std::io::stdin().read_to_string(&mut content)?;
+    if content.trim().is_empty() {
+        return Err(PromptBankError::InvalidInput(
+            "No content provided on stdin".to_string(),
+        ));
+    }
+    Ok(content)
+}
+
+fn name_in_use(name: &str, other: &Prompt) -> PromptBankError {
+    PromptBankError::InvalidInput(format!(
+        "'{}' is already used by prompt {} ({})",
+        name, other.id, other.name
+    ))
+}
+
+/// Write edited fields to a prompt, recording the previous version
+fn apply_fields(prompt: &mut Prompt, fields: EditableFields, category: PromptCategory) {
+    // Variables are found in the content with the new engine's syntax
+    prompt.engine = fields.engine;
+    // Records the previous name, description, tags, and content
+    prompt.update_content(fields.content);
+    prompt.name = fields.name;
+    prompt.description = fields.description;
+    prompt.category = category;
+    prompt.tags = fields.tags;
+    prompt.aliases = fields.aliases;
+    prompt.clipboard = fields.clipboard;
+    prompt.max_tokens = fields.max_tokens;
+    prompt.archived = fields.archived;
+    prompt.author = fields.author;
+    // Placeholders left untouched, and variables no longer in the content, go
+    prompt.variable_defs = fields
+        .variable_defs
+        .into_iter()
+        .filter(|(name, def)| *def != VariableDef::default() && prompt.variables.contains(name))
+        .collect();
+}
+
+/// Names of the fields that differ between two versions, for change summaries
+fn changed_fields(before: &EditableFields, after: &EditableFields) -> Vec<&'static str> {
+    [
+        ("name", before.name != after.name),
+        ("description", before.description != after.description),
+        ("category", before.category != after.category),
+        ("tags", before.tags != after.tags),
+        ("aliases", before.aliases != after.aliases),
+        ("clipboard", before.clipboard != after.clipboard),
+        ("engine", before.engine != after.engine),
+        ("max_tokens", before.max_tokens != after.max_tokens),
+        ("archived", before.archived != after.archived),
+        ("author", before.author != after.author),
+        ("variable_defs", before.variable_defs != after.variable_defs),
+        ("content", before.content != after.content),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(field, _)| field)
+    .collect()
+}
+
+/// The most used prompts, busiest first
+fn most_used<'a>(prompts: impl Iterator<Item = &'a Prompt>) -> Vec<PromptUsage<'a>> {
+    let mut usages: Vec<PromptUsage> = prompts
+        .filter(|p| p.use_count > 0)
+        .map(|p| PromptUsage {
+            id: &p.id,
+            name: &p.name,
+            use_count: p.use_count,
+        })
+        .collect();
+    usages.sort_by_key(|u| std::cmp::Reverse(u.use_count));
+    usages.truncate(MOST_USED_LIMIT);
+    usages
+}
+
+/// Map entries sorted by descending count, ties in key order
+fn by_count<K: Ord>(counts: &BTreeMap<K, usize>) -> Vec<(&K, &usize)> {
+    let mut entries: Vec<_> = counts.iter().collect();
+    entries.sort_by_key(|(_, count)| std::cmp::Reverse(**count));
+    entries
+}
+
+/// Print a titled `stats` section of counts, skipping it when empty
+fn print_counts<K: std::fmt::Display>(title: &str, entries: Vec<(K, &usize)>) {
+    if entries.is_empty() {
+        return;
+    }
+    println!("\n  {}:", title.dimmed());
+    for (key, count) in entries {
+        println!("    {}: {}", key, count);
+    }
+}
+
+/// Whether two variable names probably mean the same thing
+///
+/// Catches case and separator differences (`user_name`, `userName`),
+/// abbreviations (`lang`, `language`), and single typos.
+fn similar_variables(a: &str, b: &str) -> bool {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    let (short, long) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    a == b
+        || (short.len() >= 3 && long.starts_with(short.as_str()))
+        || (short.len() >= 4 && strsim::levenshtein(&a, &b) <= 1)
+}
+
+/// Parse a date or RFC 3339 time from the command line
+///
+/// A bare date means the start of that day (UTC), or its end when
+/// `end_of_day` is set.
+fn parse_time(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| {
+            if end_of_day {
+                date.and_hms_opt(23, 59, 59)
+            } else {
+                date.and_hms_opt(0, 0, 0)
+            }
+        })
+        .map(|time| time.and_utc())
+        .ok_or_else(|| {
+            PromptBankError::InvalidInput(format!(
+                "Invalid date '{}' (use YYYY-MM-DD or an RFC 3339 time)",
+                value
+            ))
+        })
+}
+
+/// Parse an age like "30d", "12h" or "2w" (or a date) into the cutoff time
+fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    for unit in ['h', 'd', 'w'] {
+        if let Some(count) = value.strip_suffix(unit).and_then(|n| n.parse::<i64>().ok()) {
+            let span = match unit {
+                'h' => Duration::try_hours(count),
+                'd' => Duration::try_days(count),
+                _ => Duration::try_weeks(count),
+            };
+            return span
+                .and_then(|span| Utc::now().checked_sub_signed(span))
+                .ok_or_else(|| {
+                    PromptBankError::InvalidInput("--since is too far back".to_string())
+                });
+        }
+    }
+    parse_time(value, false).map_err(|_| {
+        PromptBankError::InvalidInput(format!(
+            "Invalid age '{}' (use e.g. 30d, 12h, 2w, or a YYYY-MM-DD date)",
+            value
+        ))
+    })
+}
+
+/// Build a SARIF 2.1.0 log from lint findings
+fn sarif_log(findings: &[LocatedFinding]) -> serde_json::Value {
+    let rules: Vec<serde_json::Value> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            serde_json::json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+                "defaultConfiguration": { "level": rule.level() },
+            })
+        })
+        .collect();
+
+    let results: Vec<serde_json::Value> = findings
+        .iter()
+        .map(|located| {
+            // Relative paths resolve against the repository root
+            let uri = if std::path::Path::new(&located.file).is_absolute() {
+                format!("file://{}", located.file)
+            } else {
+                located.file.clone()
+            };
+            let mut physical = serde_json::json!({
+                "artifactLocation": { "uri": uri },
+            });
+            if let Some(line) = located.line {
+                physical["region"] = serde_json::json!({ "startLine": line });
+            }
+            serde_json::json!({
+                "ruleId": located.finding.rule.id(),
+                "level": located.finding.level,
+                "message": { "text": located.finding.message },
+                "locations": [{ "physicalLocation": physical }],
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "promptbank",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/ff-vivek/promptbank",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Color every non-empty match of a pattern within a line
+fn highlight_matches(regex: &Regex, line: &str) -> String {
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for found in regex.find_iter(line).filter(|m| !m.is_empty()) {
+        highlighted.push_str(&line[last..found.start()]);
+        highlighted.push_str(&found.as_str().red().bold().to_string());
+        last = found.end();
+    }
+    highlighted.push_str(&line[last..]);
+    highlighted
+}
+
+/// Print the changed lines between two versions of content, with a little context
+fn print_diff(old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    for (index, group) in diff.grouped_ops(1).iter().enumerate() {
+        if index > 0 {
+            println!("{}", "  ...".dimmed());
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let line = change.value().trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => println!("  {}", format!("- {}", line).red()),
+                    ChangeTag::Insert => println!("  {}", format!("+ {}", line).green()),
+                    ChangeTag::Equal => println!("  {}", format!("  {}", line).dimmed()),
+                }
+            }
+        }
+    }
+}
+
+/// Print an applied prompt between rules, highlighting code blocks on a color terminal
+fn print_rendered(rendered: &str) {
+    println!("\n{}", "═".repeat(60).dimmed());
+    if colored::control::SHOULD_COLORIZE.should_colorize() {
+        println!("{}", highlight::highlight_code_blocks(rendered));
+    } else {
+        println!("{}", rendered);
+    }
+    println!("{}", "═".repeat(60).dimmed());
+}
+
+/// Ask what each undescribed variable is for, so others can fill it in
+fn describe_variables(prompt: &mut Prompt) -> Result<()> {
+    let undescribed: Vec<String> = prompt
+        .variables
+        .iter()
+        .filter(|v| prompt.variable_def(v).description.is_none())
+        .cloned()
+        .collect();
+    if undescribed.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "\n{} Describe the variables for whoever applies this prompt (optional):",
+        "→".blue()
+    );
+    for var in undescribed {
+        let description: String = Input::new()
+            .with_prompt(format!("  {}", var))
+            .allow_empty(true)
+            .interact_text()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        let description = description.trim();
+        if !description.is_empty() {
+            prompt.variable_defs.entry(var).or_default().description =
+                Some(description.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Ask for one variable's value, showing its description and type
+///
+/// Variables with choices are picked from a list; an optional one can also
+/// be left empty. Given an editor, other variables are written there.
+fn ask_variable(
+    var: &str,
+    def: &VariableDef,
+    default: Option<&String>,
+    editor: Option<Editor>,
+) -> Result<String> {
+    let mut hints: Vec<String> = def.description.iter().cloned().collect();
+    if def.kind != VariableType::Text && def.choices.is_empty() {
+        hints.push(def.kind.to_string());
+    }
+    let label = if hints.is_empty() {
+        format!("  {}", var)
+    } else {
+        format!("  {} ({})", var, hints.join(", "))
+    };
+
+    if !def.choices.is_empty() && def.kind == VariableType::List {
+        let chosen = list_items(default.map_or("", |d| d.as_str()));
+        let defaults: Vec<bool> = def
+            .choices
+            .iter()
+            .map(|c| chosen.contains(&c.as_str()))
+            .collect();
+        let selection = MultiSelect::new()
+            .with_prompt(label)
+            .items(&def.choices)
+            .defaults(&defaults)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        if selection.is_empty() && def.required {
+            return Err(PromptBankError::InvalidInput(format!(
+                "No value given for '{}'",
+                var
+            )));
+        }
+        let picked: Vec<&str> = selection.iter().map(|&i| def.choices[i].as_str()).collect();
+        return Ok(picked.join("\n"));
+    }
+
+    if !def.choices.is_empty() {
+        let mut items: Vec<&str> = def.choices.iter().map(|c| c.as_str()).collect();
+        if !def.required {
+            items.insert(0, "(none)");
+        }
+        let selection = Select::new()
+            .with_prompt(label)
+            .items(&items)
+            .default(
+                default
+                    .and_then(|d| items.iter().position(|item| item == d))
+                    .unwrap_or(0),
+            )
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        if !def.required && selection == 0 {
+            return Ok(String::new());
+        }
+        return Ok(items[selection].to_string());
+    }
+
+    if let Some(editor) = editor {
+        println!("{} (opening editor)", label);
+        let value = editor
+            .edit(default.map_or("", |d| d.as_str()))
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
+            .map(|text| text.strip_suffix('\n').unwrap_or(&text).to_string())
+            .or_else(|| default.cloned())
+            .unwrap_or_default();
+        if value.is_empty() && def.required {
+            return Err(PromptBankError::InvalidInput(format!(
+                "No value given for '{}'",
+                var
+            )));
+        }
+        def.check(var, &value)?;
+        return Ok(value);
+    }
+
+    let mut input = Input::new()
+        .with_prompt(label)
+        .allow_empty(!def.required)
+        .validate_with(|value: &String| def.check(var, value).map_err(|e| e.to_string()));
+    if let Some(default) = default {
+        input = input.default(default.clone());
+    }
+    input
+        .interact_text()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))
+}
+
+/// Read `apply --vars-file`, a JSON or YAML mapping of variable names to values
+///
+/// Numbers and booleans are taken as written, and lists become one item per
+/// line for list variables. Nested mappings are rejected since they have no
+/// text form.
+fn load_vars_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let invalid =
+        |detail: String| PromptBankError::InvalidInput(format!("{}: {}", path.display(), detail));
+    let text = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    // YAML is a superset of JSON, so one parser reads both
+    let values: BTreeMap<String, serde_yaml::Value> =
+        serde_yaml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+    let scalar = |value: serde_yaml::Value| match value {
+        serde_yaml::Value::String(s) => Some(s),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        serde_yaml::Value::Null => Some(String::new()),
+        _ => None,
+    };
+    values
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_yaml::Value::Sequence(items) => items
+                    .into_iter()
+                    .map(scalar)
+                    .collect::<Option<Vec<String>>>()
+                    .map(|items| items.join("\n")),
+                value => scalar(value),
+            };
+            let value =
+                value.ok_or_else(|| invalid(format!("'{}' must be a value or a list", name)))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
+/// Values `apply --env` finds for a prompt's variables, with the environment
+/// variable each came from
+fn env_values(prompt: &Prompt) -> BTreeMap<String, (String, String)> {
+    prompt
+        .variables
+        .iter()
+        .filter_map(|var| {
+            let key = prompt.variable_def(var).env.unwrap_or_else(|| var.clone());
+            let value = std::env::var(&key).ok()?;
+            Some((var.clone(), (key, value)))
+        })
+        .collect()
+}
+
+/// Warn on stderr when a rendered prompt is over its `max_tokens`, or fail
+/// with `strict`
+fn check_budget(prompt: &Prompt, rendered: &str, strict: bool) -> Result<()> {
+    let Some(max_tokens) = prompt.max_tokens else {
+        return Ok(());
+    };
+    let tokens = ContentSize::of(rendered).tokens;
+    if tokens <= max_tokens {
+        return Ok(());
+    }
+    let message = format!(
+        "'{}' came out at ~{} tokens, over its budget of {}",
+        prompt.name, tokens, max_tokens
+    );
+    if strict {
+        return Err(PromptBankError::InvalidInput(message));
+    }
+    eprintln!("{} {}", "⚠".yellow(), message);
+    Ok(())
+}
+
+/// Write `--output`, creating its directory first
+///
+/// The file ends with a newline, as the output would on stdout, so appended
+/// results don't run together.
+fn write_output(path: &Path, text: &str, append: bool) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    info!(path = %path.display(), append, "writing output");
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?;
+    file.write_all(text.as_bytes())?;
+    if !text.ends_with('\n') {
+        file.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Run `command` in the shell with `text` on its stdin, letting its output
+/// through as it comes
+fn pipe_to(command: &str, text: &str) -> Result<()> {
+    run_piped(command, text, false).map(drop)
+}
+
+/// Run `command` in the shell with `text` on its stdin and return what it
+/// prints, without a final newline
+fn pipe_through(command: &str, text: &str) -> Result<String> {
+    let stdout = run_piped(command, text, true)?;
+    let output = String::from_utf8(stdout).map_err(|_| {
+        PromptBankError::InvalidInput(format!("`{}` printed something other than text", command))
+    })?;
+    Ok(output.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn run_piped(command: &str, text: &str, capture: bool) -> Result<Vec<u8>> {
+    use std::process::Stdio;
+
+    info!(command, capture, "piping prompt to command");
+    let stdout = if capture {
+        Stdio::piped()
+    } else {
+        Stdio::inherit()
+    };
+    let mut child = std::process::Command::new(include::SHELL[0])
+        .args([include::SHELL[1], command])
+        .stdin(Stdio::piped())
+        .stdout(stdout)
+        .spawn()
+        .map_err(|e| {
+            PromptBankError::InvalidInput(format!("Failed to run `{}`: {}", command, e))
+        })?;
+    // Written from another thread so a command that prints while it reads
+    // can't fill its stdout pipe and stall
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let newline = if text.ends_with('\n') { "" } else { "\n" };
+    let input = format!("{}{}", text, newline);
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output()?;
+    match writer.join().expect("stdin writer doesn't panic") {
+        // A command may exit without reading everything, which is its call
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+        _ => {}
+    }
+    if !output.status.success() {
+        return Err(PromptBankError::InvalidInput(format!(
+            "`{}` exited with {}",
+            command, output.status
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Parse `--var key=value` flags, skipping any without an `=`
+fn parse_vars(vars: &[String]) -> Result<Vec<(String, String)>> {
+    vars.iter()
+        .filter_map(|var| var.split_once('='))
+        .map(|(key, value)| Ok((key.to_string(), resolve_value(value)?)))
+        .collect()
+}
+
+/// `--var` values that read from elsewhere, as given rather than as read
+///
+/// These are what history and `--last` keep for such variables, so the
+/// clipboard or a file's contents are never stored.
+fn var_references(vars: &[String]) -> BTreeMap<String, String> {
+    vars.iter()
+        .filter_map(|var| var.split_once('='))
+        .filter(|(_, value)| is_reference(value))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Whether a value is read from elsewhere, like `@clipboard` or `@./spec.md`
+fn is_reference(value: &str) -> bool {
+    value == builtins::CLIPBOARD_VALUE || include::value_path(value).is_some()
+}
+
+/// A value as given: `@clipboard` is replaced by the text on the clipboard,
+/// and a path like `@./spec.md` by the file's contents
+fn resolve_value(value: &str) -> Result<String> {
+    Ok(match value {
+        builtins::CLIPBOARD_VALUE => builtins::clipboard_text()?,
+        _ => match include::value_path(value) {
+            Some(path) => include::read(&path)?,
+            None => value.to_string(),
+        },
+    })
+}
+
+/// Last values of every prompt applied together that has variables
+///
+/// Values kept as `@clipboard` or a path are read again.
+fn last_values_of(parts: &[&Prompt]) -> Result<BTreeMap<String, String>> {
+    let mut values = BTreeMap::new();
+    for part in parts.iter().filter(|p| !p.variables.is_empty()) {
+        if part.last_values.is_empty() {
+            return Err(PromptBankError::InvalidInput(format!(
+                "No earlier values recorded for '{}'",
+                part.name
+            )));
+        }
+        for (name, value) in &part.last_values {
+            if !values.contains_key(name) {
+                values.insert(name.clone(), resolve_value(value)?);
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// The `@clipboard` or path a variable was last read from, if any
+fn references_of(parts: &[&Prompt], name: &str) -> Option<String> {
+    parts
+        .iter()
+        .find_map(|part| part.last_values.get(name))
+        .filter(|value| is_reference(value))
+        .cloned()
+}
+
+/// Render edited content with the values last used for each variable
+///
+/// Variables that have never been filled in show their default, or `<name>`
+/// if they have none.
+fn print_edit_preview(prompt: &Prompt, content: &str) {
+    let mut draft = prompt.clone();
+    draft.update_content(content.to_string());
+    let defaults = draft.variable_defaults();
+    let substitutions: Vec<(String, String)> = draft
+        .variables
+        .iter()
+        .filter(|var| prompt.last_values.contains_key(*var) || !defaults.contains_key(*var))
+        .map(|var| {
+            let value = prompt
+                .last_values
+                .get(var)
+                .cloned()
+                .unwrap_or_else(|| format!("<{}>", var));
+            (var.clone(), value)
+        })
+        .collect();
+
+    println!("\n{} Preview of '{}'", "→".blue(), prompt.name);
+    match draft.render(&substitutions, Includes::default()) {
+        Ok(rendered) => print_rendered(&rendered),
+        Err(e) => println!("{} {}", "⚠".yellow(), e),
+    }
+}
+
+/// Print prompt content, rendered as Markdown when writing to a color terminal
+///
+/// Fenced code blocks in a known language are syntax highlighted.
+fn print_content(content: &str, plain: bool) {
+    if plain || !colored::control::SHOULD_COLORIZE.should_colorize() {
+        println!("{}", content);
+        return;
+    }
+    let skin = termimad::MadSkin::default();
+    let mut highlighter = Highlighter::default();
+    for segment in highlight::segments(content) {
+        match segment {
+            Segment::Text(text) => print!("{}", skin.term_text(text)),
+            Segment::Code {
+                open,
+                language,
+                code,
+                close,
+            } => match highlighter.highlight(code, language) {
+                Some(highlighted) => {
+                    print!("{}", highlighted);
+                    if !code.ends_with('\n') {
+                        println!();
+                    }
+                }
+                None => {
+                    let block = format!("{}{}{}", open, code, close.unwrap_or(""));
+                    print!("{}", skin.term_text(&block));
+                }
+            },
+        }
+    }
+}
+
+/// Format a byte count for humans, e.g. "1.5 MB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Describe how long ago a timestamp was, e.g. "3 days ago"
+fn format_age(time: chrono::DateTime<chrono::Utc>) -> String {
+    let elapsed = chrono::Utc::now() - time;
+    let (count, unit) = if elapsed.num_days() >= 365 {
+        (elapsed.num_days() / 365, "year")
+    } else if elapsed.num_days() >= 30 {
+        (elapsed.num_days() / 30, "month")
+    } else if elapsed.num_days() >= 1 {
+        (elapsed.num_days(), "day")
+    } else if elapsed.num_hours() >= 1 {
+        (elapsed.num_hours(), "hour")
+    } else {
+        return "just now".to_string();
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+pub struct App {
+    storage: Storage,
+    bank: PromptBank,
+    config: Config,
+    format: OutputFormat,
+    pager: bool,
+    /// Whether the user can be prompted for input
+    interactive: bool,
+}
+
+impl App {
+    pub fn new(bank_name: &str, config: Config) -> Result<Self> {
+        let storage = Storage::open(bank_name, config.storage.format)?;
+        Ok(Self {
+            storage,
+            bank: PromptBank::new(),
+            config,
+            format: OutputFormat::Text,
+            pager: true,
+            interactive: true,
+        })
+    }
+
+    pub fn run(&mut self, cli: Cli) -> Result<()> {
+        self.format = cli.format;
+        self.pager = !cli.no_pager;
+        self.interactive = !cli.non_interactive && std::io::stdin().is_terminal();
+
+        // The bank is only parsed for commands that use it, keeping trivial
+        // invocations fast on large banks
+        if cli.command.needs_bank() {
+            self.recover_journal(&self.storage)?;
+            self.bank = self.storage.load()?;
+        }
+
+        match cli.command {
+            Commands::Add(args) => self.add_prompt(args),
+
+            Commands::List(args) => self.list_prompts(args),
+
+            Commands::Get {
+                id,
+                pick,
+                copy,
+                raw,
+                fields,
+                json,
+                no_raw,
+                plain,
+                as_of,
+                output,
+                append,
+            } => {
+                if json {
+                    self.format = OutputFormat::Json;
+                }
+                // Picking fields replaces the content-only default
+                let raw = raw || (self.config.get.raw && !no_raw && fields.is_empty());
+                let id = self.resolve_fuzzy(self.resolve_id(id, pick)?)?;
+                let as_of = as_of.as_deref().map(|d| parse_time(d, true)).transpose()?;
+                match output {
+                    Some(path) => self.write_prompt(&id, as_of, &path, append, copy)?,
+                    None => self.get_prompt(&id, copy, raw, plain, &fields, as_of)?,
+                }
+                self.record_get(&id)
+            }
+
+            Commands::Apply { id, pick, args } => {
+                let id = self.resolve_id(id, pick)?;
+                if args.check {
+                    return self.check_variables(&id, &args);
+                }
+                self.apply_prompt(&id, args)?;
+                self.record_use(&id)
+            }
+
+            Commands::Edit {
+                id,
+                pick,
+                preview,
+                no_confirm,
+                meta,
+                all,
+                name,
+                description,
+                tags,
+                category,
+            } => {
+                let with_fields =
+                    name.is_some() || description.is_some() || tags.is_some() || category.is_some();
+                if !self.interactive && (all || meta || !with_fields) {
+                    return Err(PromptBankError::InvalidInput(
+                        "Cannot open an editor; pass --name, --description, --tags, or --category instead"
+                            .to_string(),
+                    ));
+                }
+                if all {
+                    return self.edit_all(category);
+                }
+                let id = self.resolve_id(id, pick)?;
+                if meta {
+                    self.edit_meta(&id)
+                } else if with_fields {
+                    let mut fields = EditableFields::from(self.bank.find(&id)?);
+                    if let Some(name) = name {
+                        fields.name = name;
+                    }
+                    if let Some(description) = description {
+                        fields.description = description;
+                    }
+                    if let Some(tags) = tags {
+                        fields.tags = tags.split(',').map(|s| s.to_string()).collect();
+                    }
+                    if let Some(category) = category {
+                        fields.category = category;
+                    }
+                    self.save_fields(&id, fields)
+                } else {
+                    self.edit_prompt(&id, preview, !no_confirm)
+                }
+            }
+
+            Commands::Delete {
+                id,
+                pick,
+                interactive,
+                force,
+                dry_run,
+            } => {
+                if interactive {
+                    return self.delete_selected(force, dry_run);
+                }
+                let id = self.resolve_id(id, pick)?;
+                self.delete_prompt(&id, force, dry_run)
+            }
+
+            Commands::Clipboard { id, mode } => self.set_clipboard_mode(&id, mode),
+
+            Commands::Rename {
+                id,
+                new_name,
+                slug_id,
+                force,
+            } => self.rename_prompt(&id, &new_name, slug_id, force),
+
+            Commands::Duplicate { id, name } => self.duplicate_prompt(&id, name),
+
+            Commands::Chown { id, author } => self.chown_prompt(&id, &author),
+
+            Commands::Copy { id, to, force } => self.transfer_prompt(&id, &to, force, false),
+
+            Commands::Move {
+                id,
+                category,
+                tags,
+                to,
+                force,
+            } => self.move_prompt(&id, category, tags, to, force),
+
+            Commands::Recent { limit } => self.recent_prompts(limit),
+
+            Commands::Prune { interactive } => self.prune_prompts(interactive),
+
+            Commands::Search {
+                query,
+                semantic,
+                archived,
+                page,
+            } => self.search_prompts(&query, semantic, archived, page),
+
+            Commands::Grep {
+                pattern,
+                context,
+                ignore_case,
+                fixed_strings,
+                archived,
+            } => self.grep_prompts(&pattern, context, ignore_case, fixed_strings, archived),
+
+            Commands::Replace {
+                pattern,
+                replacement,
+                regex,
+                tags,
+                dry_run,
+            } => self.replace_content(&pattern, &replacement, regex, &tags, dry_run),
+
+            Commands::Archive { id } => self.set_archived(&id, true),
+
+            Commands::Unarchive { id } => self.set_archived(&id, false),
+
+            Commands::Export {
+                output,
+                with_state,
+                interactive,
+            } => self.export_prompts(&output, with_state, interactive),
+
+            Commands::Import {
+                input,
+                merge,
+                map,
+                with_state,
+                on_conflict,
+                dry_run,
+            } => self.import_prompts(&input, merge, map, with_state, on_conflict, dry_run),
+
+            Commands::Lint { sarif } => self.lint_prompts(sarif),
+
+            Commands::Validate { id, sarif } => self.validate_prompts(id.as_deref(), sarif),
+
+            Commands::Vars { archived } => self.show_variables(archived),
+
+            Commands::Index { write } => self.write_index(write),
+
+            Commands::Recommend {
+                write,
+                names,
+                yes,
+                on_conflict,
+            } => {
+                if write {
+                    self.write_recommendations(names)
+                } else {
+                    self.install_recommendations(yes, on_conflict)
+                }
+            }
+
+            Commands::Tui if !self.interactive => Err(PromptBankError::InvalidInput(
+                "The TUI needs a terminal; use list, get, and apply in scripts".to_string(),
+            )),
+            Commands::Tui => Tui::new(&mut self.bank, &self.storage, &self.config).run(),
+
+            Commands::Info => self.show_info(),
+            Commands::Stats { archived } => self.show_stats(archived),
+
+            Commands::Open { id, path } => self.open_path(id.as_deref(), path),
+
+            Commands::Watch { id, file } => self.watch_prompt(&id, &file),
+
+            Commands::Doctor => self.run_doctor(),
+
+            Commands::Status { strict } => self.show_status(strict),
+
+            Commands::Init => init::run(self.storage.bank_name(), &mut self.config),
+
+            Commands::Tutorial => tutorial::run(),
+
+            Commands::Update => self.update_self(),
+
+            Commands::Completions { shell } => completions::generate(shell),
+
+            Commands::Complete { kind } => self.print_completions(kind),
+
+            Commands::Category(cmd) => self.run_category(cmd),
+
+            Commands::Config(cmd) => self.run_config(cmd),
 
             Commands::Community(cmd) => self.run_community(cmd),
 
-            Commands::Claude(cmd) => self.run_claude(cmd),
+            Commands::Claude(cmd) => self.run_claude(cmd),
+
+            Commands::Pipeline(cmd) => self.run_pipeline(cmd),
+
+            Commands::History(args) => self.run_history(args),
+        }
+    }
+
+    fn run_category(&mut self, cmd: CategoryCommands) -> Result<()> {
+        match cmd {
+            CategoryCommands::Add { name } => self.category_add(&name),
+            CategoryCommands::List => self.category_list(),
+            CategoryCommands::Remove { name } => self.category_remove(&name),
+        }
+    }
+
+    fn category_add(&mut self, name: &str) -> Result<()> {
+        let name = name.trim().to_lowercase();
+        let name = name.strip_prefix("custom:").unwrap_or(&name).to_string();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(PromptBankError::InvalidCategory(name));
+        }
+        if BUILTIN_CATEGORIES.contains(&name.as_str()) || self.config.categories.contains(&name) {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Category '{}' already exists",
+                name
+            )));
+        }
+
+        self.config.categories.push(name.clone());
+        self.config.save()?;
+        println!("{} Category '{}' added", "✓".green(), name.cyan());
+        Ok(())
+    }
+
+    fn category_list(&self) -> Result<()> {
+        let count = |category: &PromptCategory| {
+            self.bank
+                .prompts
+                .iter()
+                .filter(|p| &p.category == category)
+                .count()
+        };
+
+        println!("\n{}", "Categories".bold().underline());
+        for name in BUILTIN_CATEGORIES {
+            let category: PromptCategory = name.parse()?;
+            println!("  {:<20} {}", name, count(&category));
+        }
+
+        if !self.config.categories.is_empty() {
+            println!("\n  {}:", "Custom".dimmed());
+            for name in &self.config.categories {
+                let category = PromptCategory::Custom(name.clone());
+                println!("  {:<20} {}", name, count(&category));
+            }
+        }
+
+        println!();
+        Ok(())
+    }
+
+    fn category_remove(&mut self, name: &str) -> Result<()> {
+        let name = name.trim().to_lowercase();
+        let name = name.strip_prefix("custom:").unwrap_or(&name).to_string();
+        let before = self.config.categories.len();
+        self.config.categories.retain(|c| c != &name);
+
+        if self.config.categories.len() == before {
+            println!("{} Category '{}' is not registered", "→".yellow(), name);
+            return Ok(());
+        }
+
+        self.config.save()?;
+        println!("{} Category '{}' removed", "✓".green(), name);
+        println!(
+            "  Existing prompts keep it and can still be found with --category custom:{}",
+            name
+        );
+        Ok(())
+    }
+
+    fn run_config(&mut self, cmd: ConfigCommands) -> Result<()> {
+        match cmd {
+            ConfigCommands::Get { key } => self.config_get(&key),
+            ConfigCommands::Set { key, value } => self.config_set(&key, &value),
+            ConfigCommands::List => self.config_list(),
+            ConfigCommands::Edit => self.config_edit(),
+        }
+    }
+
+    fn config_get(&self, key: &str) -> Result<()> {
+        // Unset settings print nothing, so `$(promptbank config get ...)` is empty
+        if let Some(value) = self.config.get(key)? {
+            println!("{}", setting_text(&value));
+        }
+        Ok(())
+    }
+
+    fn config_set(&mut self, key: &str, value: &str) -> Result<()> {
+        let old_data_dir = Storage::get_data_dir()?;
+        self.config.set(key, value)?;
+        // A file that didn't load is replaced, so keep a copy to fix by hand
+        if Config::load().is_err() {
+            let path = Config::path()?;
+            let backup = path.with_extension("toml.bak");
+            std::fs::copy(&path, &backup)?;
+            println!(
+                "{} Replaced the broken config; the old file is in {}",
+                "→".yellow(),
+                backup.display()
+            );
+        }
+        self.config.save()?;
+
+        let value = self.config.get(key)?.map(|v| setting_text(&v));
+        println!(
+            "{} {} = {}",
+            "✓".green(),
+            key.cyan(),
+            value.unwrap_or_default()
+        );
+        if key == "storage.data_dir" {
+            println!(
+                "  Existing data is not moved; copy it over from {}",
+                old_data_dir.display()
+            );
+        }
+        Ok(())
+    }
+
+    fn config_list(&self) -> Result<()> {
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&self.config);
+        }
+
+        println!(
+            "\n{} {}\n",
+            "Config".bold().underline(),
+            format!("({})", Config::path()?.display()).dimmed()
+        );
+        for (key, description) in config::KEYS {
+            println!("  {}", format!("# {}", description).dimmed());
+            match self.config.get(key)? {
+                Some(value) => println!("  {} = {}", key.cyan(), setting_text(&value)),
+                None => println!("  {} = {}", key.cyan(), "(not set)".dimmed()),
+            }
+        }
+        for (key, value) in self.config.map_entries()? {
+            println!("  {} = {}", key.cyan(), setting_text(&value));
+        }
+
+        println!();
+        Ok(())
+    }
+
+    fn config_edit(&mut self) -> Result<()> {
+        if !self.interactive {
+            return Err(PromptBankError::InvalidInput(
+                "Cannot open an editor; use `config set` instead".to_string(),
+            ));
+        }
+
+        let path = Config::path()?;
+        let current = if path.exists() {
+            std::fs::read_to_string(&path)?
+        } else {
+            toml::to_string_pretty(&self.config)
+                .map_err(|e| PromptBankError::Config(e.to_string()))?
+        };
+        let edited = self
+            .config
+            .editor()
+            .extension(".toml")
+            .trim_newlines(false)
+            .edit(&current)
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+        let Some(edited) = edited.filter(|e| e != &current) else {
+            println!("{}", "No changes made.".yellow());
+            return Ok(());
+        };
+
+        // Validate before writing, keeping the user's comments and layout
+        self.config = toml::from_str(&edited)
+            .map_err(|e| PromptBankError::Config(format!("Not saved: {}", e)))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, edited)?;
+        println!("{} Config saved", "✓".green());
+        Ok(())
+    }
+
+    /// Parse a category, accepting registered custom categories without the
+    /// `custom:` prefix
+    fn parse_category(&self, name: &str) -> Result<PromptCategory> {
+        let lower = name.to_lowercase();
+        if self.config.categories.contains(&lower) {
+            return Ok(PromptCategory::Custom(lower));
+        }
+        name.parse()
+    }
+
+    fn run_claude(&mut self, cmd: ClaudeCommands) -> Result<()> {
+        match cmd {
+            ClaudeCommands::Install {
+                id,
+                as_type,
+                project,
+                all_projects,
+                with_aliases,
+            } => self.claude_install(&id, as_type.as_deref(), project, all_projects, with_aliases),
+            ClaudeCommands::RenderSkill { id, out } => self.claude_render_skill(&id, out),
+            ClaudeCommands::List => self.claude_list(),
+            ClaudeCommands::Status { project } => self.claude_status(project),
+            ClaudeCommands::Remove { name } => self.claude_remove(&name),
+        }
+    }
+
+    fn run_pipeline(&mut self, cmd: PipelineCommands) -> Result<()> {
+        match cmd {
+            PipelineCommands::Add {
+                name,
+                steps,
+                exec,
+                description,
+                force,
+            } => self.pipeline_add(name, &steps, exec, description, force),
+            PipelineCommands::List => self.pipeline_list(),
+            PipelineCommands::Show { name } => self.pipeline_show(&name),
+            PipelineCommands::Run {
+                name,
+                var,
+                exec,
+                allow_exec,
+                allow_files,
+            } => {
+                let includes = Includes {
+                    files: allow_files,
+                    commands: allow_exec,
+                };
+                self.pipeline_run(&name, &var, exec, includes)
+            }
+            PipelineCommands::Remove { name } => self.pipeline_remove(&name),
+        }
+    }
+
+    fn pipeline_add(
+        &mut self,
+        name: String,
+        specs: &[String],
+        exec: Option<String>,
+        description: String,
+        force: bool,
+    ) -> Result<()> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(PromptBankError::InvalidInput(
+                "Pipeline name cannot be empty".to_string(),
+            ));
+        }
+        let exists = self.bank.pipelines.iter().any(|p| p.name == name);
+        if exists && !force {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Pipeline '{}' already exists (pass --force to replace it)",
+                name
+            )));
+        }
+        let steps = specs
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| pipeline::Step::parse(&self.bank, spec, i == 0))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.bank.pipelines.retain(|p| p.name != name);
+        self.bank.pipelines.push(Pipeline {
+            name: name.clone(),
+            description,
+            steps,
+            exec,
+            created_at: Utc::now(),
+        });
+        self.storage.save(&self.bank)?;
+        let verb = if exists { "replaced" } else { "added" };
+        println!("{} Pipeline '{}' {}", "✓".green(), name.cyan(), verb);
+        Ok(())
+    }
+
+    fn pipeline_list(&self) -> Result<()> {
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&self.bank.pipelines);
+        }
+        if self.bank.pipelines.is_empty() {
+            println!(
+                "{}",
+                "No pipelines yet. Create one with 'promptbank pipeline add'.".yellow()
+            );
+            return Ok(());
+        }
+
+        println!("\n{}", "Pipelines".bold().underline());
+        for pipeline in &self.bank.pipelines {
+            let steps: Vec<&str> = pipeline
+                .steps
+                .iter()
+                .map(|step| self.step_name(step))
+                .collect();
+            println!("  {} {}", pipeline.name.bold(), steps.join(" → ").dimmed());
+            if !pipeline.description.is_empty() {
+                println!("    {}", pipeline.description);
+            }
+        }
+        println!();
+        Ok(())
+    }
+
+    fn pipeline_show(&self, name: &str) -> Result<()> {
+        let pipeline = self.bank.pipeline(name)?;
+        if self.format != OutputFormat::Text {
+            return self.print_structured(pipeline);
+        }
+
+        println!("\n{}", pipeline.name.bold().underline());
+        if !pipeline.description.is_empty() {
+            println!("{}", pipeline.description);
+        }
+        match &pipeline.exec {
+            Some(command) => println!("Command: {}", command.cyan()),
+            None => println!(
+                "Command: {}",
+                "none, steps pass on the rendered prompt".dimmed()
+            ),
+        }
+        println!();
+        for (i, step) in pipeline.steps.iter().enumerate() {
+            let input = match &step.input {
+                Some(input) => format!("previous output → {{{{{}}}}}", input),
+                None => String::new(),
+            };
+            println!(
+                "  {}. {} {}",
+                i + 1,
+                self.step_name(step).bold(),
+                input.dimmed()
+            );
+        }
+        println!();
+        Ok(())
+    }
+
+    /// Name of a step's prompt, or its ID if the prompt has been deleted
+    fn step_name<'a>(&'a self, step: &'a pipeline::Step) -> &'a str {
+        self.bank
+            .get(&step.prompt)
+            .map_or(step.prompt.as_str(), |p| p.name.as_str())
+    }
+
+    /// Apply each step's prompt, passing its output into the next one
+    ///
+    /// Output of the last step goes straight to stdout; the ones before it
+    /// are captured.
+    fn pipeline_run(
+        &mut self,
+        name: &str,
+        vars: &[String],
+        exec: Option<String>,
+        includes: Includes,
+    ) -> Result<()> {
+        let pipeline = self.bank.pipeline(name)?.clone();
+        // A command typed for this run is the user's own; a stored one may
+        // have come from someone else's bank, so it only runs when asked
+        let exec = match (exec, pipeline.exec) {
+            (Some(command), _) => Some(command),
+            (None, Some(command))
+                if includes.commands || self.confirm_pipeline_command(&command)? =>
+            {
+                Some(command)
+            }
+            (None, Some(command)) => {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Pipeline '{}' pipes each step into `{}`; pass --allow-exec to run it",
+                    pipeline.name, command
+                )));
+            }
+            (None, None) => None,
+        };
+        let substitutions = parse_vars(vars)?;
+        let count = pipeline.steps.len();
+
+        // Fail before the first command runs rather than halfway through
+        if !self.interactive {
+            for (i, step) in pipeline.steps.iter().enumerate() {
+                let prompt = self.bank.find(&step.prompt)?;
+                let defaults = prompt.variable_defaults();
+                let missing = prompt.variables.iter().find(|v| {
+                    step.input.as_ref() != Some(*v)
+                        && !substitutions.iter().any(|(k, _)| k == *v)
+                        && !defaults.contains_key(*v)
+                });
+                if let Some(var) = missing {
+                    return Err(PromptBankError::InvalidInput(format!(
+                        "Step {} ('{}') has no value for '{}' (pass --var {}=value)",
+                        i + 1,
+                        prompt.name,
+                        var,
+                        var
+                    )));
+                }
+            }
+        }
+
+        let mut previous: Option<String> = None;
+        for (i, step) in pipeline.steps.iter().enumerate() {
+            let prompt = self.bank.find(&step.prompt)?;
+            let mut values = substitutions.clone();
+            if let (Some(input), Some(output)) = (&step.input, previous.take()) {
+                values.retain(|(k, _)| k != input);
+                values.push((input.clone(), output));
+            }
+
+            let defaults = prompt.variable_defaults();
+            for var in &prompt.variables {
+                if values.iter().any(|(k, _)| k == var) || defaults.contains_key(var) {
+                    continue;
+                }
+                let value = ask_variable(var, &prompt.variable_def(var), None, None)?;
+                values.push((var.clone(), value));
+            }
+            for (name, value) in &values {
+                if prompt.variables.contains(name) {
+                    prompt.variable_def(name).check(name, value)?;
+                }
+            }
+
+            let rendered = prompt.render(&values, includes)?;
+            check_budget(prompt, &rendered, false)?;
+            info!(pipeline = %pipeline.name, step = i + 1, prompt = %prompt.name, "running step");
+            let last = i + 1 == count;
+            match &exec {
+                Some(command) if last => pipe_to(command, &rendered)?,
+                Some(command) => {
+                    let message = format!("Step {}/{}: {}", i + 1, count, prompt.name);
+                    previous = Some(net::spin(&message, || pipe_through(command, &rendered))?);
+                }
+                None if last => println!("{}", rendered.trim_end_matches('\n')),
+                None => previous = Some(rendered),
+            }
+
+            let recorded: Vec<(String, String)> = values
+                .into_iter()
+                .filter(|(k, _)| prompt.variables.contains(k) && step.input.as_ref() != Some(k))
+                .collect();
+            self.storage.append_history(&ApplyEvent {
+                prompt_id: prompt.id.clone(),
+                prompt_name: prompt.name.clone(),
+                timestamp: Utc::now(),
+                variables: recorded.len(),
+                values: recorded.into_iter().collect(),
+            })?;
+        }
+
+        for step in &pipeline.steps {
+            if let Some(prompt) = self.bank.get_mut(&step.prompt) {
+                prompt.mark_used();
+            }
+        }
+        self.storage.save(&self.bank)
+    }
+
+    /// Ask before running a pipeline's stored command, when there is a
+    /// terminal to ask on
+    fn confirm_pipeline_command(&self, command: &str) -> Result<bool> {
+        if !self.interactive {
+            return Ok(false);
+        }
+        Confirm::new()
+            .with_prompt(format!("Pipe each step into `{}`?", command))
+            .default(false)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))
+    }
+
+    fn pipeline_remove(&mut self, name: &str) -> Result<()> {
+        let name = self.bank.pipeline(name)?.name.clone();
+        self.bank.pipelines.retain(|p| p.name != name);
+        self.storage.save(&self.bank)?;
+        println!("{} Pipeline '{}' removed", "✓".green(), name);
+        Ok(())
+    }
+
+    fn run_history(&self, args: HistoryArgs) -> Result<()> {
+        match args.command {
+            Some(HistoryCommands::Export { since, output, csv }) => {
+                self.export_history(since.as_deref(), output.as_deref(), csv)
+            }
+            None => self.show_history(args.id.as_deref(), args.limit),
+        }
+    }
+
+    fn show_history(&self, id: Option<&str>, limit: usize) -> Result<()> {
+        let mut events = self.storage.load_history()?;
+        if let Some(id) = id {
+            let prompt = self.bank.find(id)?;
+            events.retain(|e| e.prompt_id == prompt.id);
+        }
+        events.reverse();
+        events.truncate(limit);
+
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&events);
+        }
+
+        if events.is_empty() {
+            println!("{}", "No apply events found.".yellow());
+            return Ok(());
+        }
+
+        let theme = &self.config.theme;
+        for event in &events {
+            let time = event.timestamp.format("%Y-%m-%d %H:%M").to_string();
+            println!(
+                "{}  {} ({})",
+                time.dimmed(),
+                event.prompt_name.bold(),
+                event.prompt_id.color(theme.accent())
+            );
+            for (name, value) in &event.values {
+                println!("    {} = {}", name.color(theme.variables()), value);
+            }
+        }
+        Ok(())
+    }
+
+    fn export_history(&self, since: Option<&str>, output: Option<&Path>, csv: bool) -> Result<()> {
+        if csv {
+            self.own_format("--csv")?;
+        }
+        let mut events = self.storage.load_history()?;
+        if let Some(since) = since {
+            let cutoff = parse_since(since)?;
+            events.retain(|e| e.timestamp >= cutoff);
+        }
+        history::anonymize(&mut events);
+
+        let content = match self.format {
+            _ if csv => history::to_csv(&events),
+            OutputFormat::Json => serde_json::to_string_pretty(&events)? + "\n",
+            OutputFormat::Yaml => serde_yaml::to_string(&events)?,
+            OutputFormat::Text => events
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{}  {} ({}), {} variable(s)\n",
+                        e.timestamp.format("%Y-%m-%d %H:%M"),
+                        e.prompt_name,
+                        e.prompt_id,
+                        e.variables
+                    )
+                })
+                .collect(),
+        };
+
+        match output {
+            Some(path) => {
+                info!(path = %path.display(), "writing history export");
+                std::fs::write(path, content)?;
+                println!(
+                    "{} Exported {} event(s) to {:?}",
+                    "✓".green(),
+                    events.len(),
+                    path
+                );
+            }
+            None if events.is_empty() && self.format == OutputFormat::Text && !csv => {
+                println!("{}", "No apply events found.".yellow());
+            }
+            None => print!("{}", content),
+        }
+        Ok(())
+    }
+
+    fn claude_install(
+        &mut self,
+        id: &str,
+        as_type: Option<&str>,
+        project: Option<PathBuf>,
+        all_projects: bool,
+        with_aliases: bool,
+    ) -> Result<()> {
+        let prompt = self.bank.find(id)?.clone();
+
+        let install_type = match as_type {
+            Some("command") => InstallType::Command,
+            Some("agent") => InstallType::Agent,
+            Some(_) => InstallType::Skill,
+            None => self
+                .config
+                .install
+                .install_type(&prompt.category.to_string()),
+        };
+
+        let type_name = match install_type {
+            InstallType::Skill => "skill",
+            InstallType::Command => "command",
+            InstallType::Agent => "agent",
+        };
+
+        if with_aliases && install_type != InstallType::Command {
+            return Err(PromptBankError::InvalidInput(
+                "--with-aliases requires --as-type command".to_string(),
+            ));
+        }
+
+        let bank_name = self.storage.bank_name();
+        let install = |claude: &ClaudeIntegration| -> Result<Vec<PathBuf>> {
+            let mut paths = vec![claude.install(&prompt, install_type, bank_name)?];
+            if with_aliases {
+                paths.extend(claude.install_aliases(&prompt, bank_name)?);
+            }
+            Ok(paths)
+        };
+
+        if all_projects {
+            if prompt.installed_projects.is_empty() {
+                println!(
+                    "{} '{}' has not been installed into any project",
+                    "→".yellow(),
+                    prompt.name
+                );
+                return Ok(());
+            }
+
+            for project in &prompt.installed_projects {
+                match ClaudeIntegration::for_project(project).and_then(|claude| install(&claude)) {
+                    Ok(paths) => {
+                        for path in paths {
+                            println!("{} Refreshed {:?}", "✓".green(), path);
+                        }
+                    }
+                    Err(e) => println!("{} Skipped {:?}: {}", "⚠".yellow(), project, e),
+                }
+            }
+            return Ok(());
+        }
+
+        let paths = match &project {
+            Some(project) => {
+                let project = project.canonicalize()?;
+                let paths = install(&ClaudeIntegration::for_project(&project)?)?;
+
+                let stored = self.bank.find_mut(id)?;
+                if !stored.installed_projects.contains(&project) {
+                    stored.installed_projects.push(project);
+                    self.storage.save(&self.bank)?;
+                }
+                paths
+            }
+            None => install(&ClaudeIntegration::new()?)?,
+        };
+
+        println!(
+            "{} Installed '{}' as Claude {}",
+            "✓".green(),
+            prompt.name.cyan(),
+            type_name
+        );
+        for path in &paths {
+            println!("  Path: {:?}", path);
+        }
+        if install_type == InstallType::Agent {
+            println!("\n  Ask Claude to use the {} agent", prompt.name.cyan());
+        } else {
+            println!("\n  Use with: {}{}", "/".cyan(), prompt.name);
+        }
+        if with_aliases {
+            for alias in &prompt.aliases {
+                println!("        {}{}", "/".cyan(), alias);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn claude_render_skill(&self, id: &str, out: Option<PathBuf>) -> Result<()> {
+        let prompt = self.bank.find(id)?;
+        let content = ClaudeIntegration::generate_skill_content(prompt);
+
+        match out {
+            Some(path) => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                info!(path = %path.display(), "writing skill");
+                std::fs::write(&path, content)?;
+                println!(
+                    "{} Rendered '{}' as a skill to {:?}",
+                    "✓".green(),
+                    prompt.name,
+                    path
+                );
+            }
+            None => print!("{}", content),
+        }
+        Ok(())
+    }
+
+    fn claude_list(&self) -> Result<()> {
+        let claude = ClaudeIntegration::new()?;
+        let (skills, commands, agents) = claude.list_installed()?;
+
+        println!("\n{}", "Claude Integrations".bold().underline());
+        println!("  Directory: {:?}", claude.claude_dir());
+
+        if skills.is_empty() && commands.is_empty() && agents.is_empty() {
+            println!("\n  No prompts installed in Claude.");
+        } else {
+            if !skills.is_empty() {
+                println!("\n  {}:", "Skills".yellow());
+                for skill in skills {
+                    println!("    /{}", skill);
+                }
+            }
+
+            if !commands.is_empty() {
+                println!("\n  {}:", "Commands".yellow());
+                for cmd in commands {
+                    println!("    /{}", cmd);
+                }
+            }
+
+            if !agents.is_empty() {
+                println!("\n  {}:", "Agents".yellow());
+                for agent in agents {
+                    println!("    {}", agent);
+                }
+            }
+        }
+
+        println!();
+        Ok(())
+    }
+
+    fn claude_status(&self, project: Option<PathBuf>) -> Result<()> {
+        let claude = match project {
+            Some(project) => ClaudeIntegration::for_project(&project)?,
+            None => ClaudeIntegration::new()?,
+        };
+        let report = claude.status(&self.bank, self.storage.bank_name())?;
+
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&report);
+        }
+
+        println!("\n{}", "Claude Sync Status".bold().underline());
+        println!("  Directory: {:?}", claude.claude_dir());
+
+        if report.is_empty() {
+            println!("\n  No prompts installed by promptbank.");
+        } else {
+            println!();
+            for artifact in &report {
+                let state = match artifact.state {
+                    ArtifactState::InSync => "in-sync".green(),
+                    ArtifactState::Outdated => "outdated".yellow(),
+                    ArtifactState::Modified => "modified".yellow(),
+                    ArtifactState::Missing => "missing".red(),
+                    ArtifactState::Orphaned => "orphaned".red(),
+                };
+                let type_name = match artifact.install_type {
+                    InstallType::Skill => "skill",
+                    InstallType::Command => "command",
+                    InstallType::Agent => "agent",
+                };
+                println!(
+                    "  {:<10} {} ({})",
+                    state,
+                    artifact.install_type.invocation(&artifact.name),
+                    type_name
+                );
+            }
+        }
+
+        println!();
+        Ok(())
+    }
+
+    fn claude_remove(&self, name: &str) -> Result<()> {
+        let claude = ClaudeIntegration::new()?;
+        let removed = claude.remove(name)?;
+
+        if removed {
+            println!("{} Removed '{}' from Claude", "✓".green(), name);
+        } else {
+            println!("{} '{}' not found in Claude", "→".yellow(), name);
+        }
+
+        Ok(())
+    }
+
+    fn run_community(&mut self, cmd: CommunityCommands) -> Result<()> {
+        match cmd {
+            CommunityCommands::Browse { category } => self.community_browse(category),
+            CommunityCommands::Install {
+                name,
+                apply,
+                copy,
+                on_conflict,
+            } => self.community_install(&name, apply, copy, on_conflict),
+            CommunityCommands::Search { query } => self.community_search(&query),
+            CommunityCommands::Contribute => self.community_contribute(),
+        }
+    }
+
+    fn community_browse(&self, category: Option<String>) -> Result<()> {
+        let index = Community::new(&self.config.community).fetch_index()?;
+
+        let prompts: Vec<_> = if let Some(cat) = category {
+            index
+                .prompts
+                .iter()
+                .filter(|p| p.category.to_lowercase() == cat.to_lowercase())
+                .collect()
+        } else {
+            index.prompts.iter().collect()
+        };
+
+        if prompts.is_empty() {
+            println!("{}", "No community prompts found.".yellow());
+            return Ok(());
+        }
+
+        println!(
+            "\n{} {} community prompt(s) available:\n",
+            "→".blue(),
+            prompts.len().to_string().cyan()
+        );
+
+        for prompt in prompts {
+            println!(
+                "  {} [{}] by {}",
+                prompt.name.bold(),
+                prompt.category.yellow(),
+                prompt.author.dimmed()
+            );
+            println!("    {}", prompt.description);
+            if !prompt.tags.is_empty() {
+                println!("    Tags: {}", prompt.tags.join(", ").blue());
+            }
+            println!();
+        }
+
+        println!(
+            "Install with: {} <name>",
+            "promptbank community install".cyan()
+        );
+
+        Ok(())
+    }
+
+    fn community_install(
+        &mut self,
+        name: &str,
+        apply: bool,
+        copy: bool,
+        on_conflict: OnConflict,
+    ) -> Result<()> {
+        let community = Community::new(&self.config.community);
+        let index = community.fetch_index()?;
+
+        let entry = index
+            .prompts
+            .iter()
+            .find(|p| p.name.to_lowercase() == name.to_lowercase())
+            .ok_or_else(|| {
+                let names = index.prompts.iter().map(|p| p.name.as_str());
+                PromptBankError::PromptNotFound(name.to_string(), closest_names(name, names))
+            })?;
+
+        println!("Installing '{}'...", entry.name);
+
+        let community_prompt = community.fetch_prompt(&entry.path)?;
+        let mut prompt = Community::to_local_prompt(community_prompt)?;
+        settle_name_conflict(&mut self.bank, &mut prompt, on_conflict)?;
+        let prompt_name = prompt.name.clone();
+        let prompt_id = prompt.id.clone();
+        let prompt_content = prompt.content.clone();
+
+        // Save to local promptbank
+        self.bank.add(prompt.clone());
+        self.storage.save(&self.bank)?;
+
+        println!(
+            "{} Saved to promptbank with ID: {}",
+            "✓".green(),
+            prompt_id.cyan()
+        );
+
+        // Install to Claude as skill
+        match ClaudeIntegration::new() {
+            Ok(claude) => {
+                match claude.install(&prompt, InstallType::Skill, self.storage.bank_name()) {
+                    Ok(_) => {
+                        println!(
+                            "{} Installed as Claude skill: {}",
+                            "✓".green(),
+                            format!("/{}", prompt_name).cyan()
+                        );
+                    }
+                    Err(e) => {
+                        println!(
+                            "{} Could not install to Claude: {}",
+                            "⚠".yellow(),
+                            e
+                        );
+                    }
+                }
+            }
+            Err(_) => {
+                println!(
+                    "{} Claude not found - skipping skill installation",
+                    "⚠".yellow()
+                );
+            }
+        }
+
+        if apply {
+            println!("\n{}", "═".repeat(60).dimmed());
+            println!("{}", prompt_content);
+            println!("{}", "═".repeat(60).dimmed());
+        }
+
+        if copy {
+            self.copy_to_clipboard(&prompt_content)?;
+            println!("\n{} Copied to clipboard!", "✓".green());
+        }
+
+        Ok(())
+    }
+
+    fn community_search(&self, query: &str) -> Result<()> {
+        println!("{}", "Searching community prompts...".dimmed());
+
+        let index = Community::new(&self.config.community).fetch_index()?;
+        let results = Community::search(&index, query);
+
+        if results.is_empty() {
+            println!("{} No community prompts matching '{}'", "→".yellow(), query);
+            return Ok(());
+        }
+
+        println!(
+            "\n{} {} result(s) for '{}':\n",
+            "→".blue(),
+            results.len().to_string().cyan(),
+            query
+        );
+
+        for prompt in results {
+            println!(
+                "  {} [{}] by {}",
+                prompt.name.bold(),
+                prompt.category.yellow(),
+                prompt.author.dimmed()
+            );
+            println!("    {}", prompt.description);
+            println!();
+        }
+
+        Ok(())
+    }
+
+    fn community_contribute(&self) -> Result<()> {
+        println!("\n{}", "Contributing to PromptBank Community".bold().underline());
+        println!();
+        println!("To share your prompts with the community:");
+        println!();
+        println!("  1. Fork the repository:");
+        println!("     {}", Community::repo_url().cyan());
+        println!();
+        println!("  2. Add your prompt file to the appropriate category folder:");
+        println!("     prompts/{{category}}/{{your-prompt-name}}.json");
+        println!();
+        println!("  3. Update index.json with your prompt entry");
+        println!();
+        println!("  4. Submit a Pull Request");
+        println!();
+        println!("See the README for detailed contribution guidelines.");
+        println!();
+
+        Ok(())
+    }
+
+    /// Use the given ID, or let the user fuzzy-pick a prompt when none is
+    /// given or `--pick` is set
+    fn resolve_id(&self, id: Option<String>, pick: bool) -> Result<String> {
+        if let Some(id) = id {
+            if !pick {
+                // Expand a short ID prefix; anything else is reported later
+                return Ok(match self.bank.resolve(&id)? {
+                    Some(prompt) => prompt.id.clone(),
+                    None => id,
+                });
+            }
+        }
+
+        if !self.interactive {
+            return Err(PromptBankError::InvalidInput(
+                "No prompt ID given and prompting is disabled".to_string(),
+            ));
+        }
+
+        if self.bank.prompts.is_empty() {
+            return Err(PromptBankError::InvalidInput(
+                "No prompts to pick from".to_string(),
+            ));
+        }
+
+        let items: Vec<String> = self
+            .bank
+            .prompts
+            .iter()
+            .map(|p| format!("{} [{}] {}", p.name, p.category, p.description.dimmed()))
+            .collect();
+
+        let selection = FuzzySelect::new()
+            .with_prompt("Select prompt")
+            .items(&items)
+            .default(0)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+        Ok(self.bank.prompts[selection].id.clone())
+    }
+
+    /// Pick any number of prompts from a checklist, returning their indices
+    fn pick_many(&self, action: &str) -> Result<Vec<usize>> {
+        if !self.interactive {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Cannot prompt for input; run {} without --interactive",
+                action
+            )));
+        }
+
+        if self.bank.prompts.is_empty() {
+            return Err(PromptBankError::InvalidInput(
+                "No prompts to pick from".to_string(),
+            ));
+        }
+
+        let items: Vec<String> = self
+            .bank
+            .prompts
+            .iter()
+            .map(|p| format!("{} [{}] {}", p.name, p.category, p.description.dimmed()))
+            .collect();
+
+        MultiSelect::new()
+            .with_prompt(format!(
+                "Select prompts to {} (space to toggle, enter to confirm)",
+                action
+            ))
+            .items(&items)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))
+    }
+
+    /// Offer the closest name when no prompt has this ID or name
+    ///
+    /// Only asked on a terminal; otherwise the ID is kept, so the lookup
+    /// fails with suggestions rather than quietly showing another prompt.
+    fn resolve_fuzzy(&self, id: String) -> Result<String> {
+        if !self.interactive || self.bank.get(&id).is_some() {
+            return Ok(id);
+        }
+        let Some(prompt) = self.bank.closest_match(&id) else {
+            return Ok(id);
+        };
+        let show = Confirm::new()
+            .with_prompt(format!("No prompt named '{}'. Show '{}'?", id, prompt.name))
+            .default(true)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        Ok(if show { prompt.id.clone() } else { id })
+    }
+
+    fn add_prompt(&mut self, args: AddArgs) -> Result<()> {
+        let AddArgs {
+            name,
+            category,
+            description,
+            tags,
+            aliases,
+            content,
+            file,
+            url,
+            dir,
+            on_conflict,
+            copy_id,
+            copy_name,
+        } = args;
+        if let Some(dir) = dir {
+            return self.add_dir(&dir, category, description, tags, on_conflict);
+        }
+
+        // Flags win over the downloaded front matter
+        let (name, category, description, tags, content, meta) = match &url {
+            Some(url) => {
+                let (meta, body) = ingest::fetch_url(url)?;
+                let meta_tags = (!meta.tags.is_empty()).then(|| meta.tags.join(","));
+                (
+                    name.or(meta.name.clone()),
+                    category.or(meta.category.clone()),
+                    description.or(meta.description.clone()),
+                    tags.or(meta_tags),
+                    Some(body),
+                    meta,
+                )
+            }
+            None => (
+                name,
+                category,
+                description,
+                tags,
+                content,
+                FrontMatter::default(),
+            ),
+        };
+        // Whether the user is being walked through the prompt's details
+        let guided = self.interactive && (name.is_none() || description.is_none());
+        let category = category.or_else(|| self.config.add.default_category.clone());
+        // `cat prompt.md | promptbank add ...` supplies the content
+        let piped = stdin_is_piped();
+
+        if !self.interactive {
+            let mut missing = Vec::new();
+            if name.is_none() {
+                missing.push("--name");
+            }
+            if category.is_none() {
+                missing.push("--category");
+            }
+            if description.is_none() {
+                missing.push("--description");
+            }
+            if content.is_none() && file.is_none() && !piped {
+                missing.push("--content or --file");
+            }
+            if !missing.is_empty() {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Cannot prompt for input; missing {}",
+                    missing.join(", ")
+                )));
+            }
+        }
+
+        // Get name interactively if not provided
+        let name = match name {
+            Some(n) => n,
+            None => Input::new()
+                .with_prompt("Prompt name")
+                .interact_text()
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?,
+        };
+        // Fail before asking for the rest
+        if on_conflict == OnConflict::Abort && self.bank.get(&name).is_some() {
+            return Err(name_taken(&name));
+        }
+
+        // Get category interactively if not provided
+        let category = match category {
+            Some(c) => self.parse_category(&c)?,
+            None => {
+                let categories: Vec<&str> = BUILTIN_CATEGORIES
+                    .iter()
+                    .copied()
+                    .chain(self.config.categories.iter().map(|c| c.as_str()))
+                    .collect();
+                let selection = Select::new()
+                    .with_prompt("Select category")
+                    .items(&categories)
+                    .default(0)
+                    .interact()
+                    .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+                self.parse_category(categories[selection])?
+            }
+        };
+
+        // Get description
+        let description = match description {
+            Some(d) => d,
+            None => Input::new()
+                .with_prompt("Description")
+                .interact_text()
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?,
+        };
+
+        // Get tags
+        let tags: Vec<String> = match tags {
+            Some(t) => t.split(',').map(|s| s.trim().to_string()).collect(),
+            None if !self.interactive => Vec::new(),
+            None => {
+                let tags_str: String = Input::new()
+                    .with_prompt("Tags (comma-separated, optional)")
+                    .allow_empty(true)
+                    .interact_text()
+                    .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+                if tags_str.is_empty() {
+                    Vec::new()
+                } else {
+                    tags_str.split(',').map(|s| s.trim().to_string()).collect()
+                }
+            }
+        };
+
+        // Get content
+        let content = if let Some(path) = file {
+            if path == Path::new("-") {
+                read_stdin()?
+            } else {
+                std::fs::read_to_string(&path)?
+            }
+        } else if let Some(c) = content {
+            c
+        } else if piped {
+            read_stdin()?
+        } else {
+            self.config
+                .editor()
+                .edit("# Enter your prompt content here\n")
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
+                .ok_or_else(|| PromptBankError::InvalidInput("No content provided".to_string()))?
+        };
+
+        let mut prompt = Prompt::new(name.clone(), category, description, content, tags);
+        prompt.author = meta.author.or_else(|| self.config.author.clone());
+        prompt.imported_from = url;
+        prompt.variable_defs = meta.variable_defs;
+        prompt.set_engine(meta.engine);
+        prompt.max_tokens = meta.max_tokens;
+        if guided {
+            describe_variables(&mut prompt)?;
+        }
+        if let Some(aliases) = aliases {
+            prompt.aliases = aliases
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        settle_name_conflict(&mut self.bank, &mut prompt, on_conflict)?;
+        let name = prompt.name.clone();
+        let id = prompt.id.clone();
+        self.bank.add(prompt);
+        self.storage.save(&self.bank)?;
+
+        println!("{} Prompt '{}' added with ID: {}", "✓".green(), name, id.cyan());
+
+        let copy = match (copy_id, copy_name) {
+            (true, _) => Some(CopyOnAdd::Id),
+            (_, true) => Some(CopyOnAdd::Name),
+            _ => self.config.add.copy,
+        };
+        if let Some(copy) = copy {
+            let (what, text) = match copy {
+                CopyOnAdd::Id => ("ID", &id),
+                CopyOnAdd::Name => ("Name", &name),
+            };
+            // The prompt is saved either way, so a missing clipboard is not fatal
+            match self.copy_to_clipboard(text) {
+                Ok(()) => println!("{} {} copied to clipboard", "✓".green(), what),
+                Err(e) => eprintln!("{} {}", "⚠".yellow(), e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add every prompt file in a directory, using the flags as defaults
+    fn add_dir(
+        &mut self,
+        dir: &Path,
+        category: Option<String>,
+        description: Option<String>,
+        tags: Option<String>,
+        on_conflict: OnConflict,
+    ) -> Result<()> {
+        if !dir.is_dir() {
+            return Err(PromptBankError::InvalidInput(format!(
+                "{:?} is not a directory",
+                dir
+            )));
+        }
+        let category = category
+            .or_else(|| self.config.add.default_category.clone())
+            .map(|c| self.parse_category(&c).map(|c| c.to_string()))
+            .transpose()?;
+        let map = ImportMap {
+            defaults: MapRule {
+                category,
+                description,
+                tags: tags
+                    .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
+                    .unwrap_or_default(),
+                ..MapRule::default()
+            },
+            rules: Vec::new(),
+        };
+
+        let mut added = Vec::new();
+        let mut skipped = Vec::new();
+        let prompts = ingest::ingest_dir(dir, &map, &|c| self.parse_category(c))?;
+        for (path, mut prompt) in prompts {
+            if prompt.author.is_none() {
+                prompt.author = self.config.author.clone();
+            }
+            // Unless told otherwise, a taken name skips the file rather than
+            // stopping halfway through the directory
+            if settle_name_conflict(&mut self.bank, &mut prompt, on_conflict).is_err() {
+                skipped.push((path, prompt.name));
+                continue;
+            }
+            added.push((path, prompt.name.clone(), prompt.id.clone()));
+            self.bank.add(prompt);
+        }
+
+        if !added.is_empty() {
+            self.storage.save(&self.bank)?;
+        }
+
+        println!(
+            "\n{} Added {} prompt(s) from {:?}",
+            "✓".green(),
+            added.len(),
+            dir
+        );
+        for (path, name, id) in &added {
+            println!("  {} → {} ({})", path.display(), name, id.cyan());
+        }
+        if !skipped.is_empty() {
+            println!(
+                "\n{} Skipped {} file(s) whose name is taken (use --on-conflict suffix or overwrite):",
+                "→".yellow(),
+                skipped.len()
+            );
+            for (path, name) in &skipped {
+                println!("  {} ({})", path.display(), name);
+            }
+        }
+        println!();
+        Ok(())
+    }
+
+    fn list_prompts(&self, args: ListArgs) -> Result<()> {
+        let ListArgs {
+            category,
+            full,
+            no_full,
+            plain,
+            archived,
+            size,
+            sort,
+            reverse,
+            tags,
+            any_tag,
+            created_after,
+            updated_since,
+            page,
+        } = args;
+        let full = full || plain || (self.config.list.full && !no_full);
+        let created_after = created_after
+            .as_deref()
+            .map(|d| parse_time(d, false))
+            .transpose()?;
+        let updated_since = updated_since
+            .as_deref()
+            .map(|d| parse_time(d, false))
+            .transpose()?;
+
+        let mut prompts: Vec<&Prompt> = if let Some(cat) = category {
+            let cat = self.parse_category(&cat)?;
+            self.bank.list_by_category(&cat)
+        } else {
+            self.bank.prompts.iter().collect()
+        };
+        if !archived {
+            prompts.retain(|p| !p.archived);
+        }
+        if !tags.is_empty() {
+            let has_tag =
+                |p: &Prompt, tag: &String| p.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+            if any_tag {
+                prompts.retain(|p| tags.iter().any(|tag| has_tag(p, tag)));
+            } else {
+                prompts.retain(|p| tags.iter().all(|tag| has_tag(p, tag)));
+            }
+        }
+        if let Some(after) = created_after {
+            prompts.retain(|p| p.created_at >= after);
+        }
+        if let Some(since) = updated_since {
+            prompts.retain(|p| p.updated_at >= since);
+        }
+        match sort {
+            Some(ListSort::Name) => prompts.sort_by_key(|p| p.name.to_lowercase()),
+            Some(ListSort::Created) => prompts.sort_by_key(|p| std::cmp::Reverse(p.created_at)),
+            Some(ListSort::Updated) => prompts.sort_by_key(|p| std::cmp::Reverse(p.updated_at)),
+            Some(ListSort::Category) => {
+                prompts.sort_by_key(|p| (p.category.to_string(), p.name.to_lowercase()))
+            }
+            Some(ListSort::Usage) => prompts.sort_by_key(|p| std::cmp::Reverse(p.use_count)),
+            None => {}
+        }
+        if reverse {
+            prompts.reverse();
+        }
+
+        let total = prompts.len();
+        let prompts = page.apply(prompts);
+
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&prompts);
+        }
+
+        if total == 0 {
+            println!("{}", "No prompts found.".yellow());
+            return Ok(());
+        }
+
+        println!(
+            "\n{} {} prompt(s) found:\n",
+            "→".blue(),
+            total.to_string().cyan()
+        );
+
+        let _pager = if full {
+            let lines = prompts.iter().map(|p| p.content.lines().count() + 8).sum();
+            self.start_pager(lines)
+        } else {
+            None
+        };
+
+        let shown = prompts.len();
+        for prompt in prompts {
+            self.print_prompt_summary(prompt, full, plain, size);
+        }
+        page.print_footer(shown, total);
+
+        Ok(())
+    }
+
+    fn get_prompt(
+        &self,
+        id: &str,
+        copy: bool,
+        raw: bool,
+        plain: bool,
+        fields: &[String],
+        as_of: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let prompt = self.find_as_of(id, as_of)?;
+        let prompt = prompt.as_ref();
+        if let Some(time) = as_of {
+            if !raw && fields.is_empty() && self.format == OutputFormat::Text {
+                println!(
+                    "{} Version saved {} (as of {})",
+                    "→".blue(),
+                    prompt.updated_at.format("%Y-%m-%d %H:%M"),
+                    time.format("%Y-%m-%d %H:%M")
+                );
+            }
+        }
+
+        if !fields.is_empty() {
+            return self.print_fields(prompt, fields);
+        }
+
+        let structured = self.format != OutputFormat::Text;
+        let _pager = if structured || raw {
+            None
+        } else {
+            self.start_pager(prompt.content.lines().count() + 15)
+        };
+        if structured {
+            self.print_structured(prompt)?;
+        } else if raw {
+            println!("{}", prompt.content);
+        } else {
+            self.print_prompt_full(prompt, plain);
+        }
+
+        if copy {
+            self.copy_to_clipboard(&prompt.content)?;
+            if !raw && !structured {
+                println!("\n{} Copied to clipboard!", "✓".green());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write a prompt's content to a file for `get --output`, or the whole
+    /// prompt in a structured format
+    fn write_prompt(
+        &self,
+        id: &str,
+        as_of: Option<DateTime<Utc>>,
+        path: &Path,
+        append: bool,
+        copy: bool,
+    ) -> Result<()> {
+        let prompt = self.find_as_of(id, as_of)?;
+        let structured = self.format != OutputFormat::Text;
+        let text = if structured {
+            self.structured(prompt.as_ref())?
+        } else {
+            prompt.content.clone()
+        };
+        write_output(path, &text, append)?;
+        if !structured {
+            let verb = if append { "Appended" } else { "Wrote" };
+            println!("{} {} '{}' to {:?}", "✓".green(), verb, prompt.name, path);
+        }
+        if copy {
+            self.copy_to_clipboard(&prompt.content)?;
+        }
+        Ok(())
+    }
+
+    /// A prompt, or the version of it saved as of a point in time
+    fn find_as_of(&self, id: &str, as_of: Option<DateTime<Utc>>) -> Result<Cow<'_, Prompt>> {
+        let prompt = self.bank.find(id)?;
+        let Some(time) = as_of else {
+            return Ok(Cow::Borrowed(prompt));
+        };
+        prompt.as_of(time).map(Cow::Owned).ok_or_else(|| {
+            PromptBankError::InvalidInput(format!(
+                "Prompt '{}' was created after {}",
+                prompt.name,
+                time.format("%Y-%m-%d %H:%M")
+            ))
+        })
+    }
+
+    /// Print only the chosen fields of a prompt: bare values for a single field,
+    /// `field: value` lines for several, or an object in structured formats
+    ///
+    /// Fields are named as in JSON output, so only those the prompt has can be
+    /// selected.
+    fn print_fields(&self, prompt: &Prompt, fields: &[String]) -> Result<()> {
+        let all = serde_json::to_value(prompt)?;
+        let known: Vec<&str> = all
+            .as_object()
+            .map(|object| object.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        for field in fields {
+            if !known.contains(&field.as_str()) {
+                let suggestions = closest_names(field, known.iter().copied());
+                let hint = if suggestions.is_empty() {
+                    format!(" (fields: {})", known.join(", "))
+                } else {
+                    format!(" (did you mean: {}?)", suggestions.join(", "))
+                };
+                return Err(PromptBankError::InvalidInput(format!(
+                    "'{}' has no field '{}'{}",
+                    prompt.name, field, hint
+                )));
+            }
+        }
+
+        let value = |field: &str| all.get(field).cloned().unwrap_or_default();
+        if self.format != OutputFormat::Text {
+            let selected: serde_json::Map<String, serde_json::Value> =
+                fields.iter().map(|f| (f.clone(), value(f))).collect();
+            return self.print_structured(&selected);
+        }
+
+        for field in fields {
+            let text = match value(field) {
+                // Custom categories serialize as objects
+                _ if field == "category" => prompt.category.to_string(),
+                serde_json::Value::Array(items) => {
+                    items.iter().map(field_text).collect::<Vec<_>>().join(",")
+                }
+                other => field_text(&other),
+            };
+            if fields.len() == 1 {
+                println!("{}", text);
+            } else {
+                println!("{}: {}", field, text);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_prompt(&mut self, id: &str, args: ApplyArgs) -> Result<()> {
+        let ApplyArgs {
+            var: vars,
+            vars_file,
+            with,
+            separator,
+            copy,
+            no_copy,
+            interactive,
+            editor_vars,
+            last,
+            env,
+            messages,
+            user,
+            allow_exec,
+            allow_files,
+            strict,
+            wrap_xml,
+            output: out,
+            append,
+            exec,
+            check: _,
+        } = args;
+        let interactive = interactive || editor_vars;
+
+        if messages {
+            self.own_format("--messages")?;
+        }
+        if let Some(tag) = wrap_xml.as_ref().filter(|t| !prompt::is_xml_name(t)) {
+            return Err(PromptBankError::InvalidInput(format!(
+                "--wrap-xml '{}' is not a valid XML tag name",
+                tag
+            )));
+        }
+        let parts = self.find_parts(id, &with)?;
+        let prompt = &parts[0].combined_with(&parts[1..]);
+        let separator = match separator {
+            Some(separator) => separator.replace("\\n", "\n"),
+            None => self.config.apply.separator.clone(),
+        };
+
+        let mut substitutions = parse_vars(&vars)?;
+        let mut references = var_references(&vars);
+        if let Some(path) = &vars_file {
+            for (name, value) in load_vars_file(path)? {
+                if !substitutions.iter().any(|(k, _)| *k == name) {
+                    substitutions.push((name, value));
+                }
+            }
+        }
+        if last {
+            for (name, value) in last_values_of(&parts)? {
+                if !substitutions.iter().any(|(k, _)| *k == name) {
+                    if let Some(reference) = references_of(&parts, &name) {
+                        references.insert(name.clone(), reference);
+                    }
+                    substitutions.push((name, value));
+                }
+            }
+        }
+        // Often secrets, so they are neither shown nor remembered
+        let mut from_env: Vec<(String, String)> = Vec::new();
+        if env {
+            for (name, (key, value)) in env_values(prompt) {
+                if !substitutions.iter().any(|(k, _)| *k == name) {
+                    from_env.push((name.clone(), key));
+                    substitutions.push((name, value));
+                }
+            }
+        }
+
+        for (name, value) in &substitutions {
+            if !prompt.variables.contains(name) {
+                continue;
+            }
+            let checked = prompt.variable_def(name).check(name, value);
+            match from_env.iter().find(|(k, _)| k == name) {
+                Some((_, key)) if checked.is_err() => {
+                    return Err(PromptBankError::InvalidInput(format!(
+                        "Variable '{}' got an invalid value from ${}",
+                        name, key
+                    )));
+                }
+                _ => checked?,
+            }
+        }
+
+        let defaults = prompt.variable_defaults();
+        if !self.interactive {
+            let unresolved: Vec<&str> = prompt
+                .variables
+                .iter()
+                .filter(|v| !substitutions.iter().any(|(k, _)| k == *v))
+                .filter(|v| !defaults.contains_key(*v))
+                .map(|v| v.as_str())
+                .collect();
+            if !unresolved.is_empty() {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Unresolved variable(s): {} (pass --var name=value)",
+                    unresolved.join(", ")
+                )));
+            }
+        }
+
+        // Interactive mode for remaining variables
+        if interactive && !prompt.variables.is_empty() {
+            println!(
+                "\n{} This prompt has {} variable(s):\n",
+                "→".blue(),
+                prompt.variables.len()
+            );
+
+            for var in &prompt.variables {
+                let existing = substitutions.iter().find(|(k, _)| k == var);
+                if existing.is_none() {
+                    let def = prompt.variable_def(var);
+                    let default = defaults.get(var).filter(|d| !d.is_empty());
+                    let editor = (editor_vars || def.multiline).then(|| self.config.editor());
+                    let value = ask_variable(var, &def, default, editor)?;
+                    substitutions.push((var.clone(), value));
+                }
+            }
+        }
+
+        let includes = Includes {
+            files: allow_files,
+            commands: allow_exec,
+        };
+        let texts = profile::measure(Phase::Render, || {
+            parts
+                .iter()
+                .map(|part| part.render(&substitutions, includes))
+                .collect::<Result<Vec<_>>>()
+        })?;
+        for (part, text) in parts.iter().zip(&texts) {
+            check_budget(part, text, strict)?;
+        }
+        let rendered = texts.join(&separator);
+        let rendered = match &wrap_xml {
+            Some(tag) => prompt::wrap_xml(tag, &rendered),
+            None => rendered,
+        };
+        let last_values: Vec<(String, String)> = substitutions
+            .iter()
+            .filter(|(k, _)| prompt.variables.contains(k))
+            .filter(|(k, _)| !from_env.iter().any(|(name, _)| name == k))
+            .map(|(k, v)| (k.clone(), references.get(k).unwrap_or(v).clone()))
+            .collect();
+
+        // Explicit flags win over the prompt's setting, which wins over config
+        let copy = if copy || no_copy {
+            copy
+        } else {
+            match prompt.clipboard {
+                ClipboardMode::Always => true,
+                ClipboardMode::Never => false,
+                ClipboardMode::Default => self.config.apply.copy,
+            }
+        };
+
+        // What is printed or written, and what is copied
+        let (text, output) = match self.format {
+            _ if messages => {
+                let mut messages = vec![ChatMessage {
+                    role: "system",
+                    content: rendered,
+                }];
+                if let Some(user) = user {
+                    messages.push(ChatMessage {
+                        role: "user",
+                        content: user,
+                    });
+                }
+                let json = serde_json::to_string_pretty(&messages)?;
+                (json.clone() + "\n", json)
+            }
+            OutputFormat::Text => (rendered.clone(), rendered),
+            OutputFormat::Json | OutputFormat::Yaml => {
+                let structured = self.structured(&RenderedPrompt {
+                    id: &prompt.id,
+                    name: &prompt.name,
+                    content: &rendered,
+                })?;
+                (structured, rendered)
+            }
+        };
+
+        match (&out, &exec) {
+            (_, Some(command)) => pipe_to(command, &text)?,
+            (Some(path), None) => {
+                write_output(path, &text, append)?;
+                if self.format == OutputFormat::Text && !messages {
+                    let verb = if append { "Appended" } else { "Wrote" };
+                    println!("{} {} '{}' to {:?}", "✓".green(), verb, prompt.name, path);
+                }
+            }
+            (None, None) if self.format == OutputFormat::Text && !messages => {
+                let _pager = self.start_pager(text.lines().count() + 4);
+                print_rendered(&text);
+            }
+            (None, None) => print!("{}", text),
+        }
+
+        if copy {
+            self.copy_to_clipboard(&output)?;
+            if self.format == OutputFormat::Text && !messages {
+                println!("\n{} Copied to clipboard!", "✓".green());
+            }
+        }
+
+        // Each prompt applied with --with gets its own event and values
+        let events: Vec<ApplyEvent> = parts
+            .iter()
+            .map(|part| {
+                let values: BTreeMap<String, String> = last_values
+                    .iter()
+                    .filter(|(k, _)| part.variables.contains(k))
+                    .cloned()
+                    .collect();
+                ApplyEvent {
+                    prompt_id: part.id.clone(),
+                    prompt_name: part.name.clone(),
+                    timestamp: Utc::now(),
+                    variables: values.len(),
+                    values,
+                }
+            })
+            .collect();
+
+        // Saved along with the use count by `record_use`, which counts the
+        // first prompt
+        for (i, event) in events.into_iter().enumerate() {
+            self.storage.append_history(&event)?;
+            if let Some(part) = self.bank.get_mut(&event.prompt_id) {
+                if i > 0 {
+                    part.mark_used();
+                }
+                part.last_values.extend(event.values);
+            }
+        }
+        Ok(())
+    }
+
+    /// The prompt to apply followed by those given with `apply --with`
+    fn find_parts(&self, id: &str, with: &[String]) -> Result<Vec<&Prompt>> {
+        std::iter::once(id)
+            .chain(with.iter().map(String::as_str))
+            .map(|id| self.bank.find(id))
+            .collect()
+    }
+
+    /// Report where `apply` would get each variable's value, without rendering
+    fn check_variables(&self, id: &str, args: &ApplyArgs) -> Result<()> {
+        let parts = self.find_parts(id, &args.with)?;
+        let prompt = &parts[0].combined_with(&parts[1..]);
+        let given = parse_vars(&args.var)?;
+        let from_file = match &args.vars_file {
+            Some(path) => load_vars_file(path)?,
+            None => Vec::new(),
+        };
+        for (name, value) in given.iter().chain(&from_file) {
+            if prompt.variables.contains(name) {
+                prompt.variable_def(name).check(name, value)?;
+            }
+        }
+        let previous = if args.last {
+            last_values_of(&parts)?
+        } else {
+            BTreeMap::new()
+        };
+        let from_env = if args.env {
+            env_values(prompt)
+        } else {
+            BTreeMap::new()
+        };
+        let defaults = prompt.variable_defaults();
+
+        let checks: Vec<VariableCheck> = prompt
+            .variables
+            .iter()
+            .map(|name| {
+                let var = given.iter().find(|(k, _)| k == name);
+                let file = from_file.iter().find(|(k, _)| k == name);
+                let (source, value) = if let Some((_, value)) = var {
+                    (VariableSource::Var, Some(value))
+                } else if let Some((_, value)) = file {
+                    (VariableSource::File, Some(value))
+                } else if let Some(value) = previous.get(name) {
+                    (VariableSource::Last, Some(value))
+                } else if let Some((key, _)) = from_env.get(name) {
+                    return VariableCheck {
+                        name: name.clone(),
+                        source: VariableSource::Env,
+                        value: None,
+                        env: Some(key.clone()),
+                    };
+                } else if let Some(value) = defaults.get(name) {
+                    (VariableSource::Default, Some(value))
+                } else {
+                    (VariableSource::Missing, None)
+                };
+                VariableCheck {
+                    name: name.clone(),
+                    source,
+                    value: value.cloned(),
+                    env: None,
+                }
+            })
+            .collect();
+        let missing: Vec<&str> = checks
+            .iter()
+            .filter(|c| c.source == VariableSource::Missing)
+            .map(|c| c.name.as_str())
+            .collect();
+
+        if self.format != OutputFormat::Text {
+            self.print_structured(&checks)?;
+        } else if checks.is_empty() {
+            println!("{} '{}' has no variables", "→".blue(), prompt.name);
+        } else {
+            let theme = &self.config.theme;
+            println!("{} Variables of '{}':", "→".blue(), prompt.name);
+            for check in &checks {
+                let name = check.name.color(theme.variables());
+                if let Some(key) = &check.env {
+                    let from = format!("(from ${})", key);
+                    println!("  {} {} {}", "✓".green(), name, from.dimmed());
+                    continue;
+                }
+                match &check.value {
+                    Some(value) if value.is_empty() => {
+                        println!("  {} {} {}", "✓".green(), name, "(optional)".dimmed())
+                    }
+                    Some(value) => println!(
+                        "  {} {} = {} {}",
+                        "✓".green(),
+                        name,
+                        value,
+                        format!("({})", check.source.label()).dimmed()
+                    ),
+                    None => println!("  {} {} {}", "✗".red(), name, "(missing)".dimmed()),
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Missing value for {} (pass --var name=value)",
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Page text output that won't fit on screen, unless `--no-pager` was given
+    fn start_pager(&self, lines: usize) -> Option<Pager> {
+        if !self.pager || self.format != OutputFormat::Text {
+            return None;
+        }
+        pager::start(lines)
+    }
+
+    /// Count a read of a prompt, logged so `get` never rewrites the bank
+    fn record_get(&mut self, id: &str) -> Result<()> {
+        if let Some(prompt) = self.bank.get_mut(id) {
+            prompt.mark_used();
+            self.storage.append_use(&UseEvent {
+                prompt_id: prompt.id.clone(),
+                timestamp: Utc::now(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Stamp a prompt as used so `recent` and `prune` can rank it
+    fn record_use(&mut self, id: &str) -> Result<()> {
+        if let Some(prompt) = self.bank.get_mut(id) {
+            prompt.mark_used();
+            self.storage.save(&self.bank)?;
+        }
+        Ok(())
+    }
+
+    fn recent_prompts(&self, limit: usize) -> Result<()> {
+        let mut prompts: Vec<&Prompt> = self
+            .bank
+            .prompts
+            .iter()
+            .filter(|p| p.last_used_at.is_some() && !p.archived)
+            .collect();
+        prompts.sort_by_key(|p| std::cmp::Reverse(p.last_used_at));
+        prompts.truncate(limit);
+
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&prompts);
+        }
+
+        if prompts.is_empty() {
+            println!(
+                "{}",
+                "No prompts used yet. Run 'promptbank get' or 'promptbank apply' first.".yellow()
+            );
+            return Ok(());
+        }
+
+        let theme = &self.config.theme;
+        println!("\n{} Recently used prompts:\n", "→".blue());
+        for prompt in prompts {
+            let Some(used) = prompt.last_used_at else {
+                continue;
+            };
+            let category = prompt.category.to_string();
+            println!(
+                "  {} {} [{}] {}",
+                prompt.id.color(theme.accent()),
+                prompt.name.bold(),
+                category.color(theme.category(&category)),
+                format!("used {}", format_age(used)).dimmed()
+            );
+        }
+        println!();
+        Ok(())
+    }
+
+    fn edit_prompt(&mut self, id: &str, preview: bool, confirm: bool) -> Result<()> {
+        let prompt = self.bank.find(id)?;
+
+        let current_content = prompt.content.clone();
+        let mut new_content = current_content.clone();
+
+        loop {
+            new_content = self
+                .config
+                .editor()
+                .edit(&new_content)
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
+                .ok_or_else(|| PromptBankError::InvalidInput("No content provided".to_string()))?;
+
+            if (!preview && !confirm) || new_content == current_content {
+                break;
+            }
+
+            if preview {
+                print_edit_preview(prompt, &new_content);
+            }
+            if confirm {
+                println!("\n{} Changes to '{}':", "→".blue(), prompt.name.bold());
+                print_diff(&current_content, &new_content);
+                println!();
+            }
+            let action = Select::new()
+                .with_prompt("Keep these changes?")
+                .items(&["Save", "Keep editing", "Discard"])
+                .default(0)
+                .interact()
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+            match action {
+                0 => break,
+                1 => continue,
+                _ => {
+                    println!("{}", "Changes discarded.".yellow());
+                    return Ok(());
+                }
+            }
+        }
+
+        if new_content == current_content {
+            println!("{}", "No changes made.".yellow());
+            return Ok(());
+        }
+
+        let prompt = self.bank.find_mut(id)?;
+        prompt.update_content(new_content);
+        self.storage.save(&self.bank)?;
+
+        println!("{} Prompt '{}' updated.", "✓".green(), id);
+        Ok(())
+    }
+
+    fn edit_meta(&mut self, id: &str) -> Result<()> {
+        let prompt = self.bank.find(id)?;
+        let header = format!(
+            "# Editing {} ({}). The ID, dates, and usage are not editable.\n",
+            prompt.name, prompt.id
+        );
+        let mut text = header + &serde_yaml::to_string(&EditableFields::from(prompt))?;
+
+        let fields = loop {
+            text = self
+                .config
+                .editor()
+                .extension(".yaml")
+                .edit(&text)
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
+                .ok_or_else(|| PromptBankError::InvalidInput("No content provided".to_string()))?;
+
+            match serde_yaml::from_str::<EditableFields>(&text) {
+                Ok(fields) => break fields,
+                Err(e) => {
+                    println!("{} {}", "✗".red(), e);
+                    let again = self.interactive
+                        && Confirm::new()
+                            .with_prompt("Edit again?")
+                            .default(true)
+                            .interact()
+                            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+                    if !again {
+                        return Err(PromptBankError::InvalidInput(
+                            "Invalid YAML; nothing was changed".to_string(),
+                        ));
+                    }
+                }
+            }
+        };
+
+        self.save_fields(id, fields)
+    }
+
+    /// Validate edited fields and write them back, recording a revision
+    fn save_fields(&mut self, id: &str, mut fields: EditableFields) -> Result<()> {
+        let category = self.check_fields(&mut fields)?;
+
+        let prompt = self.bank.find(id)?;
+        let prompt_id = prompt.id.clone();
+        if fields == EditableFields::from(prompt) {
+            println!("{}", "No changes made.".yellow());
+            return Ok(());
+        }
+        if fields.name != prompt.name {
+            if let Some(other) = self.bank.get(&fields.name) {
+                return Err(name_in_use(&fields.name, other));
+            }
+        }
+
+        let prompt = self.bank.find_mut(&prompt_id)?;
+        apply_fields(prompt, fields, category);
+        let name = prompt.name.clone();
+        self.storage.save(&self.bank)?;
+
+        println!("{} Prompt '{}' updated.", "✓".green(), name);
+        Ok(())
+    }
+
+    /// Normalize edited fields, returning the category they name
+    fn check_fields(&self, fields: &mut EditableFields) -> Result<PromptCategory> {
+        fields.name = fields.name.trim().to_string();
+        if fields.name.is_empty() {
+            return Err(PromptBankError::InvalidInput(
+                "Name cannot be empty".to_string(),
+            ));
+        }
+        let clean = |list: &[String]| -> Vec<String> {
+            list.iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        fields.tags = clean(&fields.tags);
+        fields.aliases = clean(&fields.aliases);
+        if fields.max_tokens == Some(0) {
+            return Err(PromptBankError::InvalidInput(
+                "max_tokens must be more than 0".to_string(),
+            ));
+        }
+        if fields.engine == TemplateEngine::Tera {
+            if let Some((line, message)) = template::syntax_error(&fields.content) {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Content is not a valid Tera template (line {}): {}",
+                    line, message
+                )));
+            }
+        }
+        for (name, def) in &fields.variable_defs {
+            def.compiled_pattern(name)?;
+            if let Some(tag) = def.wrap.as_ref().filter(|t| !prompt::is_xml_name(t)) {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Variable '{}' has an invalid wrap tag '{}'; use letters, digits, '_', '-', or '.'",
+                    name, tag
+                )));
+            }
+            if let Some(default) = &def.default {
+                def.check(name, default)?;
+            }
+        }
+        let category = self.parse_category(&fields.category)?;
+        fields.category = category.to_string();
+        Ok(category)
+    }
+
+    fn edit_all(&mut self, category: Option<String>) -> Result<()> {
+        let category = category.map(|c| self.parse_category(&c)).transpose()?;
+        let entries: Vec<BulkEntry> = self
+            .bank
+            .prompts
+            .iter()
+            .filter(|p| !p.archived)
+            .filter(|p| category.as_ref().is_none_or(|c| &p.category == c))
+            .map(|p| BulkEntry {
+                id: p.id.clone(),
+                fields: EditableFields::from(p),
+            })
+            .collect();
+        if entries.is_empty() {
+            println!("{}", "No prompts found.".yellow());
+            return Ok(());
+        }
+
+        let header = "# Edit any field. IDs identify the prompts and must not change;\n\
+                      # prompts removed from this file are left as they are.\n";
+        let mut text = header.to_string() + &serde_yaml::to_string(&entries)?;
+
+        let edits = loop {
+            text = self
+                .config
+                .editor()
+                .extension(".yaml")
+                .edit(&text)
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
+                .ok_or_else(|| PromptBankError::InvalidInput("No content provided".to_string()))?;
+
+            match self.check_bulk_edit(&entries, &text) {
+                Ok(edits) => break edits,
+                Err(e) => {
+                    println!("{} {}", "✗".red(), e);
+                    let again = self.interactive
+                        && Confirm::new()
+                            .with_prompt("Edit again?")
+                            .default(true)
+                            .interact()
+                            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+                    if !again {
+                        return Err(PromptBankError::InvalidInput(
+                            "Invalid edit; nothing was changed".to_string(),
+                        ));
+                    }
+                }
+            }
+        };
+
+        if edits.is_empty() {
+            println!("{}", "No changes made.".yellow());
+            return Ok(());
+        }
+
+        println!("\n{} {} prompt(s) changed:", "→".blue(), edits.len());
+        for (entry, _) in &edits {
+            let before = self.bank.find(&entry.id)?;
+            let name = if before.name == entry.fields.name {
+                before.name.clone()
+            } else {
+                format!("{} → {}", before.name, entry.fields.name)
+            };
+            println!(
+                "  {} {}: {}",
+                entry.id.cyan(),
+                name,
+                changed_fields(&EditableFields::from(before), &entry.fields).join(", ")
+            );
+        }
+        println!();
+
+        if self.interactive {
+            let confirm = Confirm::new()
+                .with_prompt("Apply these changes?")
+                .default(true)
+                .interact()
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+            if !confirm {
+                println!("{}", "Changes discarded.".yellow());
+                return Ok(());
+            }
+        }
+
+        let count = edits.len();
+        for (entry, category) in edits {
+            let prompt = self.bank.find_mut(&entry.id)?;
+            apply_fields(prompt, entry.fields, category);
+        }
+        self.storage.save(&self.bank)?;
+
+        println!("{} {} prompt(s) updated", "✓".green(), count);
+        Ok(())
+    }
+
+    /// Parse and validate a bulk edit, returning only the entries that changed
+    fn check_bulk_edit(
+        &self,
+        original: &[BulkEntry],
+        text: &str,
+    ) -> Result<Vec<(BulkEntry, PromptCategory)>> {
+        let edited: Vec<BulkEntry> =
+            serde_yaml::from_str::<Option<Vec<BulkEntry>>>(text)?.unwrap_or_default();
+
+        let mut seen = Vec::new();
+        let mut edits = Vec::new();
+        for mut entry in edited {
+            let Some(before) = original.iter().find(|o| o.id == entry.id) else {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Unknown ID '{}' (IDs cannot be changed here; use rename --slug-id)",
+                    entry.id
+                )));
+            };
+            if seen.contains(&entry.id) {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Prompt {} appears more than once",
+                    entry.id
+                )));
+            }
+            seen.push(entry.id.clone());
+
+            let category = self
+                .check_fields(&mut entry.fields)
+                .map_err(|e| PromptBankError::InvalidInput(format!("{}: {}", entry.id, e)))?;
+            // Compare against the original after the same cleanup, so stray
+            // empty tags aren't reported as edits
+            let mut unchanged = before.fields.clone();
+            self.check_fields(&mut unchanged)?;
+            if entry.fields != unchanged {
+                edits.push((entry, category));
+            }
+        }
+
+        // Names must stay unique across the whole bank once everything is applied,
+        // which still allows two prompts to swap names
+        let final_name = |prompt: &Prompt| -> String {
+            edits
+                .iter()
+                .find(|(entry, _)| entry.id == prompt.id)
+                .map_or(prompt.name.clone(), |(entry, _)| entry.fields.name.clone())
+        };
+        for (entry, _) in &edits {
+            let clash = self.bank.prompts.iter().find(|p| {
+                p.id != entry.id
+                    && (p.id == entry.fields.name || final_name(p) == entry.fields.name)
+            });
+            if let Some(other) = clash {
+                return Err(name_in_use(&entry.fields.name, other));
+            }
+        }
+
+        Ok(edits)
+    }
+
+    fn set_clipboard_mode(&mut self, id: &str, mode: ClipboardMode) -> Result<()> {
+        let prompt = self.bank.find_mut(id)?;
+        prompt.clipboard = mode;
+        let name = prompt.name.clone();
+        self.storage.save(&self.bank)?;
+
+        let behavior = match mode {
+            ClipboardMode::Always => "always copies to the clipboard",
+            ClipboardMode::Never => "never copies to the clipboard",
+            ClipboardMode::Default => "follows the apply.copy setting",
+        };
+        println!("{} Applying '{}' now {}", "✓".green(), name, behavior);
+        Ok(())
+    }
+
+    fn rename_prompt(&mut self, id: &str, new_name: &str, slug_id: bool, force: bool) -> Result<()> {
+        let prompt = self.bank.find(id)?;
+        let old_name = prompt.name.clone();
+        let old_id = prompt.id.clone();
+
+        // `get` resolves by ID and name, so a shared name hides one of the prompts
+        let collision = self
+            .bank
+            .prompts
+            .iter()
+            .find(|p| p.id != old_id && (p.name == new_name || p.id == new_name));
+        if let Some(other) = collision {
+            println!(
+                "{} '{}' is already used by prompt {} ({})",
+                "⚠".yellow(),
+                new_name,
+                other.id.cyan(),
+                other.name
+            );
+            if !force {
+                return Err(PromptBankError::InvalidInput(
+                    "Name collision (use --force to rename anyway)".to_string(),
+                ));
+            }
+        }
+
+        let new_id = if slug_id {
+            let slug = slugify(new_name);
+            if slug.is_empty() {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Cannot derive an ID from '{}'",
+                    new_name
+                )));
+            }
+            if self.bank.prompts.iter().any(|p| p.id != old_id && p.id == slug) {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "ID '{}' is already in use",
+                    slug
+                )));
+            }
+            slug
+        } else {
+            old_id.clone()
+        };
+
+        let prompt = self.bank.find_mut(&old_id)?;
+        prompt.record_revision();
+        prompt.name = new_name.to_string();
+        prompt.id = new_id.clone();
+        self.storage.save(&self.bank)?;
+
+        println!(
+            "{} Prompt '{}' renamed to '{}' (ID: {})",
+            "✓".green(),
+            old_name,
+            new_name,
+            new_id.cyan()
+        );
+        Ok(())
+    }
+
+    fn chown_prompt(&mut self, id: &str, author: &str) -> Result<()> {
+        let author = author.trim();
+        if author.is_empty() {
+            return Err(PromptBankError::InvalidInput(
+                "Author cannot be empty".to_string(),
+            ));
+        }
+        let prompt = self.bank.find_mut(id)?;
+        let previous = prompt.author.replace(author.to_string());
+        let name = prompt.name.clone();
+        self.storage.save(&self.bank)?;
+
+        match previous {
+            Some(previous) => println!(
+                "{} '{}' transferred from {} to {}",
+                "✓".green(),
+                name,
+                previous,
+                author
+            ),
+            None => println!("{} '{}' now belongs to {}", "✓".green(), name, author),
+        }
+        Ok(())
+    }
+
+    fn duplicate_prompt(&mut self, id: &str, name: Option<String>) -> Result<()> {
+        let original = self.bank.find(id)?;
+
+        let name = match name {
+            Some(name) => {
+                if self.bank.get(&name).is_some() {
+                    return Err(PromptBankError::InvalidInput(format!(
+                        "A prompt named '{}' already exists",
+                        name
+                    )));
+                }
+                name
+            }
+            None => self.bank.unique_name(&format!("{}-copy", original.name)),
+        };
+
+        let copy = original.duplicate(name.clone());
+        let original_name = original.name.clone();
+        let new_id = copy.id.clone();
+        self.bank.add(copy);
+        self.storage.save(&self.bank)?;
+
+        println!(
+            "{} Duplicated '{}' as '{}' with ID: {}",
+            "✓".green(),
+            original_name,
+            name,
+            new_id.cyan()
+        );
+        Ok(())
+    }
+
+    fn delete_prompt(&mut self, id: &str, force: bool, dry_run: bool) -> Result<()> {
+        let prompt = self.bank.find(id)?;
+
+        let name = prompt.name.clone();
+        let prompt_id = prompt.id.clone();
+
+        if dry_run {
+            let mut after = self.bank.clone();
+            after.delete(&prompt_id);
+            print_bank_changes(&self.bank, &after);
+            return Ok(());
+        }
+        if !force && !self.interactive {
+            return Err(PromptBankError::InvalidInput(
+                "Cannot ask for confirmation; pass --force to delete".to_string(),
+            ));
+        }
+        if !force {
+            let confirm = Select::new()
+                .with_prompt(format!("Delete prompt '{}'?", name))
+                .items(&["No", "Yes"])
+                .default(0)
+                .interact()
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+            if confirm == 0 {
+                println!("{}", "Cancelled.".yellow());
+                return Ok(());
+            }
+        }
+
+        self.bank.delete(&prompt_id);
+        self.storage.save(&self.bank)?;
+
+        println!("{} Prompt '{}' deleted.", "✓".green(), name);
+        Ok(())
+    }
+
+    fn delete_selected(&mut self, force: bool, dry_run: bool) -> Result<()> {
+        let selected = self.pick_many("delete")?;
+        if selected.is_empty() {
+            println!("{}", "Nothing selected.".yellow());
+            return Ok(());
+        }
+
+        let ids: Vec<String> = selected
+            .iter()
+            .map(|&i| self.bank.prompts[i].id.clone())
+            .collect();
+        if dry_run {
+            let mut after = self.bank.clone();
+            after.prompts.retain(|p| !ids.contains(&p.id));
+            print_bank_changes(&self.bank, &after);
+            return Ok(());
+        }
+
+        if !force {
+            let confirm = Select::new()
+                .with_prompt(format!("Delete {} prompt(s)?", selected.len()))
+                .items(&["No", "Yes"])
+                .default(0)
+                .interact()
+                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+            if confirm == 0 {
+                println!("{}", "Cancelled.".yellow());
+                return Ok(());
+            }
+        }
+
+        self.bank.prompts.retain(|p| !ids.contains(&p.id));
+        self.storage.save(&self.bank)?;
+
+        println!("{} Deleted {} prompt(s)", "✓".green(), ids.len());
+        Ok(())
+    }
+
+    fn move_prompt(
+        &mut self,
+        id: &str,
+        category: Option<String>,
+        tags: Option<String>,
+        to: Option<String>,
+        force: bool,
+    ) -> Result<()> {
+        if category.is_none() && tags.is_none() && to.is_none() {
+            return Err(PromptBankError::InvalidInput(
+                "Nothing to do: pass --category, --tags, or --to".to_string(),
+            ));
+        }
+
+        if category.is_some() || tags.is_some() {
+            let category = category.map(|c| self.parse_category(&c)).transpose()?;
+            let prompt = self.bank.find_mut(id)?;
+            prompt.record_revision();
+
+            if let Some(category) = category {
+                prompt.category = category;
+            }
+            if let Some(tags) = tags {
+                prompt.tags = tags
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+            }
+
+            println!(
+                "{} Prompt '{}' is now [{}] with tags: {}",
+                "✓".green(),
+                prompt.name,
+                prompt.category.to_string().yellow(),
+                if prompt.tags.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    prompt.tags.join(", ")
+                }
+            );
+            self.storage.save(&self.bank)?;
         }
-    }
 
-    fn run_claude(&mut self, cmd: ClaudeCommands) -> Result<()> {
-        match cmd {
-            ClaudeCommands::Install { id, as_type } => self.claude_install(&id, &as_type),
-            ClaudeCommands::List => self.claude_list(),
-            ClaudeCommands::Remove { name } => self.claude_remove(&name),
+        match to {
+            Some(to) => self.transfer_prompt(id, &to, force, true),
+            None => Ok(()),
         }
     }
 
-    fn claude_install(&self, id: &str, as_type: &str) -> Result<()> {
-        let prompt = self
-            .bank
-            .get(id)
-            .ok_or_else(|| PromptBankError::PromptNotFound(id.to_string()))?;
+    fn transfer_prompt(&mut self, id: &str, to: &str, force: bool, remove: bool) -> Result<()> {
+        let prompt = self.bank.find(id)?.clone();
 
-        let install_type = match as_type {
-            "command" => InstallType::Command,
-            _ => InstallType::Skill,
-        };
+        if to == self.storage.bank_name() {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Prompt '{}' is already in bank '{}'",
+                prompt.name, to
+            )));
+        }
 
-        let claude = ClaudeIntegration::new()?;
-        let path = claude.install(prompt, install_type)?;
+        let target_storage = Storage::open(to, self.config.storage.format)?;
+        self.recover_journal(&target_storage)?;
+        let mut target_bank = target_storage.load()?;
 
-        let type_name = match install_type {
-            InstallType::Skill => "skill",
-            InstallType::Command => "command",
-        };
+        if target_bank.get(&prompt.name).is_some() || target_bank.get(&prompt.id).is_some() {
+            if !force {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Prompt '{}' already exists in bank '{}' (use --force to overwrite)",
+                    prompt.name, to
+                )));
+            }
+            target_bank.delete(&prompt.name);
+            target_bank.delete(&prompt.id);
+        }
+
+        target_bank.add(prompt.clone());
+        target_storage.save(&target_bank)?;
+
+        if remove {
+            self.bank.delete(&prompt.id);
+            self.storage.save(&self.bank)?;
+        }
 
         println!(
-            "{} Installed '{}' as Claude {}",
+            "{} Prompt '{}' {} to bank '{}'",
             "✓".green(),
-            prompt.name.cyan(),
-            type_name
+            prompt.name,
+            if remove { "moved" } else { "copied" },
+            to.cyan()
         );
-        println!("  Path: {:?}", path);
+        Ok(())
+    }
+
+    fn prune_prompts(&mut self, interactive: bool) -> Result<()> {
+        if interactive && !self.interactive {
+            return Err(PromptBankError::InvalidInput(
+                "Cannot prompt for input; run prune without --interactive".to_string(),
+            ));
+        }
+
+        // Least recently used or edited first
+        let mut candidates: Vec<&Prompt> = self.bank.prompts.iter().filter(|p| !p.archived).collect();
+        candidates.sort_by_key(|p| p.last_touched());
+
+        if candidates.is_empty() {
+            println!("{}", "No prompts to prune.".yellow());
+            return Ok(());
+        }
+
+        let labels: Vec<String> = candidates
+            .iter()
+            .map(|p| {
+                let touched = match p.last_used_at {
+                    Some(used) if used > p.updated_at => format!("used {}", format_age(used)),
+                    _ => format!("updated {}", format_age(p.updated_at)),
+                };
+                format!("{} [{}] {}, {} uses", p.name, p.category, touched, p.use_count)
+            })
+            .collect();
+
+        if !interactive {
+            println!("\n{} Prompts by staleness:\n", "→".blue());
+            for label in &labels {
+                println!("  {}", label);
+            }
+            println!(
+                "\nReview and archive or delete with: {}",
+                "promptbank prune --interactive".cyan()
+            );
+            return Ok(());
+        }
+
+        let selected = MultiSelect::new()
+            .with_prompt("Select prompts to prune (space to toggle, enter to confirm)")
+            .items(&labels)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+        if selected.is_empty() {
+            println!("{}", "Nothing selected.".yellow());
+            return Ok(());
+        }
+
+        let ids: Vec<String> = selected.iter().map(|&i| candidates[i].id.clone()).collect();
+
+        let action = Select::new()
+            .with_prompt(format!("What should happen to {} prompt(s)?", ids.len()))
+            .items(&["Archive", "Delete", "Cancel"])
+            .default(0)
+            .interact()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+        match action {
+            0 => {
+                for prompt in self.bank.prompts.iter_mut().filter(|p| ids.contains(&p.id)) {
+                    prompt.archived = true;
+                }
+                self.storage.save(&self.bank)?;
+                println!("{} Archived {} prompt(s)", "✓".green(), ids.len());
+            }
+            1 => {
+                self.bank.prompts.retain(|p| !ids.contains(&p.id));
+                self.storage.save(&self.bank)?;
+                println!("{} Deleted {} prompt(s)", "✓".green(), ids.len());
+            }
+            _ => println!("{}", "Cancelled.".yellow()),
+        }
+
+        Ok(())
+    }
+
+    fn set_archived(&mut self, id: &str, archived: bool) -> Result<()> {
+        let prompt = self.bank.find_mut(id)?;
+
+        if prompt.archived == archived {
+            println!(
+                "{} Prompt '{}' is already {}",
+                "→".yellow(),
+                prompt.name,
+                if archived { "archived" } else { "active" }
+            );
+            return Ok(());
+        }
+
+        prompt.archived = archived;
+        let name = prompt.name.clone();
+        self.storage.save(&self.bank)?;
+
+        if archived {
+            println!(
+                "{} Prompt '{}' archived (see it with {})",
+                "✓".green(),
+                name,
+                "list --archived".cyan()
+            );
+        } else {
+            println!("{} Prompt '{}' restored", "✓".green(), name);
+        }
+        Ok(())
+    }
+
+    fn grep_prompts(
+        &self,
+        pattern: &str,
+        context: usize,
+        ignore_case: bool,
+        fixed_strings: bool,
+        archived: bool,
+    ) -> Result<()> {
+        let source = if fixed_strings {
+            regex::escape(pattern)
+        } else {
+            pattern.to_string()
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| PromptBankError::InvalidInput(format!("Invalid regex: {}", e)))?;
+
+        // Prompts with at least one match, and the indexes of their matching lines
+        let results: Vec<(&Prompt, Vec<&str>, Vec<usize>)> =
+            profile::measure(Phase::Search, || {
+                let mut results = Vec::new();
+                for prompt in self.bank.prompts.iter().filter(|p| archived || !p.archived) {
+                    let lines: Vec<&str> = prompt.content.lines().collect();
+                    let hits: Vec<usize> = (0..lines.len())
+                        .filter(|&i| regex.is_match(lines[i]))
+                        .collect();
+                    if !hits.is_empty() {
+                        results.push((prompt, lines, hits));
+                    }
+                }
+                results
+            });
+
+        if self.format != OutputFormat::Text {
+            let matches: Vec<GrepMatch> = results
+                .iter()
+                .flat_map(|(prompt, lines, hits)| {
+                    hits.iter().map(move |&i| GrepMatch {
+                        prompt_id: &prompt.id,
+                        prompt_name: &prompt.name,
+                        line: i + 1,
+                        text: lines[i],
+                    })
+                })
+                .collect();
+            return self.print_structured(&matches);
+        }
+
+        if results.is_empty() {
+            println!("{} No lines matching '{}'", "→".yellow(), pattern);
+            return Ok(());
+        }
+
+        let theme = &self.config.theme;
+        let mut match_count = 0;
+        for (prompt, lines, hits) in &results {
+            println!(
+                "{} {}",
+                prompt.name.bold(),
+                format!("({})", prompt.id).color(theme.accent())
+            );
+
+            let mut printed: Option<usize> = None;
+            for &hit in hits {
+                let start = hit.saturating_sub(context);
+                let end = (hit + context).min(lines.len() - 1);
+                let from = match printed {
+                    Some(last) if last + 1 >= start => last + 1,
+                    Some(_) => {
+                        println!("{}", "  --".dimmed());
+                        start
+                    }
+                    None => start,
+                };
+                for (i, line) in lines.iter().enumerate().take(end + 1).skip(from) {
+                    let number = (i + 1).to_string();
+                    if hits.binary_search(&i).is_ok() {
+                        let line = highlight_matches(&regex, line);
+                        println!("  {}{} {}", number.green(), ":".dimmed(), line);
+                    } else {
+                        println!("  {}{} {}", number.dimmed(), "-".dimmed(), line);
+                    }
+                }
+                printed = Some(printed.map_or(end, |last| last.max(end)));
+            }
+            println!();
+            match_count += hits.len();
+        }
+
         println!(
-            "\n  Use with: {}{}",
-            "/".cyan(),
-            prompt.name
+            "{} {} matching line(s) in {} prompt(s)",
+            "→".blue(),
+            match_count.to_string().cyan(),
+            results.len().to_string().cyan()
         );
+        Ok(())
+    }
+
+    fn replace_content(
+        &mut self,
+        pattern: &str,
+        replacement: &str,
+        regex: bool,
+        tags: &[String],
+        dry_run: bool,
+    ) -> Result<()> {
+        if pattern.is_empty() {
+            return Err(PromptBankError::InvalidInput(
+                "Pattern must not be empty".to_string(),
+            ));
+        }
+        let regex = if regex {
+            let regex = Regex::new(pattern)
+                .map_err(|e| PromptBankError::InvalidInput(format!("Invalid regex: {}", e)))?;
+            Some(regex)
+        } else {
+            None
+        };
+
+        let mut changes = Vec::new();
+        for prompt in &self.bank.prompts {
+            let tagged = tags
+                .iter()
+                .all(|tag| prompt.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+            if !tagged {
+                continue;
+            }
+
+            let content = match &regex {
+                Some(regex) => regex.replace_all(&prompt.content, replacement).into_owned(),
+                None => prompt.content.replace(pattern, replacement),
+            };
+            if content != prompt.content {
+                println!("{} {} ({})", "→".blue(), prompt.name.bold(), prompt.id.cyan());
+                print_diff(&prompt.content, &content);
+                changes.push((prompt.id.clone(), content));
+            }
+        }
+
+        if changes.is_empty() {
+            println!("{}", "No prompts matched.".yellow());
+            return Ok(());
+        }
+        if dry_run {
+            println!(
+                "\n{} {} prompt(s) would change (dry run, nothing saved)",
+                "→".yellow(),
+                changes.len()
+            );
+            return Ok(());
+        }
 
+        let count = changes.len();
+        for (id, content) in changes {
+            if let Some(prompt) = self.bank.get_mut(&id) {
+                prompt.update_content(content);
+            }
+        }
+        self.storage.save(&self.bank)?;
+        println!("\n{} Updated {} prompt(s)", "✓".green(), count);
         Ok(())
     }
 
-    fn claude_list(&self) -> Result<()> {
-        let claude = ClaudeIntegration::new()?;
-        let (skills, commands) = claude.list_installed()?;
+    fn search_prompts(
+        &self,
+        query: &str,
+        semantic: bool,
+        archived: bool,
+        mut page: PageArgs,
+    ) -> Result<()> {
+        let mut prompts = if semantic {
+            // Every prompt has some similarity, so only the closest are shown
+            page.limit.get_or_insert(SEMANTIC_RESULTS);
+            self.semantic_search(query)?
+        } else {
+            profile::measure(Phase::Search, || self.bank.search(query))
+        };
+        if !archived {
+            prompts.retain(|p| !p.archived);
+        }
 
-        println!("\n{}", "Claude Integrations".bold().underline());
-        println!("  Directory: {:?}", claude.claude_dir());
+        let total = prompts.len();
+        let prompts = page.apply(prompts);
 
-        if skills.is_empty() && commands.is_empty() {
-            println!("\n  No prompts installed in Claude.");
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&prompts);
+        }
+
+        if total == 0 {
+            println!("{} No prompts matching '{}'", "→".yellow(), query);
+            return Ok(());
+        }
+
+        println!(
+            "\n{} {} result(s) for '{}':\n",
+            "→".blue(),
+            total.to_string().cyan(),
+            query
+        );
+
+        let shown = prompts.len();
+        for prompt in prompts {
+            self.print_prompt_summary(prompt, false, false, false);
+        }
+        page.print_footer(shown, total);
+
+        Ok(())
+    }
+
+    /// Prompts ordered by similarity in meaning to the query
+    fn semantic_search(&self, query: &str) -> Result<Vec<&Prompt>> {
+        let embedder = Embedder::new(&self.config.embeddings)?;
+        let mut cache = self.storage.load_embeddings()?;
+        if cache.refresh(&self.bank, &embedder)? {
+            self.storage.save_embeddings(&cache)?;
+        }
+        let query = embedder.embed(&[query])?.pop().unwrap_or_default();
+        Ok(cache
+            .nearest(&self.bank, &query)
+            .into_iter()
+            .map(|(prompt, _)| prompt)
+            .collect())
+    }
+
+    fn export_prompts(&self, output: &PathBuf, with_state: bool, interactive: bool) -> Result<()> {
+        let selection;
+        let bank = if interactive {
+            let selected = self.pick_many("export")?;
+            if selected.is_empty() {
+                println!("{}", "Nothing selected.".yellow());
+                return Ok(());
+            }
+            let prompts: Vec<Prompt> = selected
+                .iter()
+                .map(|&i| self.bank.prompts[i].clone())
+                .collect();
+            // Only pipelines whose every step comes along
+            let pipelines = self
+                .bank
+                .pipelines
+                .iter()
+                .filter(|p| {
+                    p.steps
+                        .iter()
+                        .all(|step| prompts.iter().any(|prompt| prompt.id == step.prompt))
+                })
+                .cloned()
+                .collect();
+            selection = PromptBank {
+                prompts,
+                version: self.bank.version.clone(),
+                pipelines,
+            };
+            &selection
         } else {
-            if !skills.is_empty() {
-                println!("\n  {}:", "Skills".yellow());
-                for skill in skills {
-                    println!("    /{}", skill);
+            &self.bank
+        };
+
+        self.storage.export(bank, output, with_state)?;
+        println!(
+            "{} Exported {} prompts to {:?}",
+            "✓".green(),
+            bank.prompts.len(),
+            output
+        );
+        Ok(())
+    }
+
+    fn lint_prompts(&self, sarif: bool) -> Result<()> {
+        if sarif {
+            self.own_format("--sarif")?;
+        }
+        let findings = lint::lint_bank(&self.bank);
+        let located = findings
+            .iter()
+            .map(|finding| self.locate_finding(finding))
+            .collect::<Result<Vec<_>>>()?;
+
+        match self.format {
+            _ if sarif => println!("{}", serde_json::to_string_pretty(&sarif_log(&located))?),
+            OutputFormat::Text => {
+                for finding in &located {
+                    let marker = match finding.finding.level {
+                        Level::Error => "✗".red(),
+                        Level::Warning => "⚠".yellow(),
+                    };
+                    let location = match finding.line {
+                        Some(line) => format!("{}:{}", finding.file, line),
+                        None => finding.file.clone(),
+                    };
+                    println!(
+                        "  {} {} [{}] {}",
+                        marker,
+                        location.dimmed(),
+                        finding.finding.rule.id(),
+                        finding.finding.message
+                    );
                 }
             }
+            _ => self.print_structured(&located)?,
+        }
 
-            if !commands.is_empty() {
-                println!("\n  {}:", "Commands".yellow());
-                for cmd in commands {
-                    println!("    /{}", cmd);
+        let errors = findings.iter().filter(|f| f.level == Level::Error).count();
+        if self.format == OutputFormat::Text && !sarif {
+            if findings.is_empty() {
+                println!("{} No problems found", "✓".green());
+            } else {
+                println!(
+                    "\n{} error(s), {} warning(s)",
+                    errors,
+                    findings.len() - errors
+                );
+            }
+        }
+
+        if errors > 0 {
+            return Err(PromptBankError::LintFailed(errors));
+        }
+        Ok(())
+    }
+
+    fn validate_prompts(&self, id: Option<&str>, sarif: bool) -> Result<()> {
+        if sarif {
+            self.own_format("--sarif")?;
+        }
+        let prompts: Vec<&Prompt> = match id {
+            Some(id) => vec![self.bank.find(id)?],
+            None => self.bank.prompts.iter().collect(),
+        };
+        let findings: Vec<Finding> = prompts
+            .iter()
+            .flat_map(|p| lint::lint_template(p))
+            .collect();
+        let errors = findings.iter().filter(|f| f.level == Level::Error).count();
+
+        match self.format {
+            OutputFormat::Text if !sarif => {
+                for prompt in &prompts {
+                    let mut own: Vec<&Finding> = findings
+                        .iter()
+                        .filter(|f| f.prompt_id == prompt.id)
+                        .collect();
+                    if own.is_empty() {
+                        continue;
+                    }
+                    own.sort_by_key(|f| match f.span {
+                        Span::Content(line) => line,
+                        _ => 0,
+                    });
+                    println!("{} ({})", prompt.name.bold(), prompt.id.cyan());
+                    for finding in own {
+                        let marker = match finding.level {
+                            Level::Error => "✗".red(),
+                            Level::Warning => "⚠".yellow(),
+                        };
+                        let line = match finding.span {
+                            Span::Content(line) => format!("line {}", line),
+                            _ => String::new(),
+                        };
+                        println!(
+                            "  {} {} [{}] {}",
+                            marker,
+                            line.dimmed(),
+                            finding.rule.id(),
+                            finding.message
+                        );
+                    }
+                }
+                if findings.is_empty() {
+                    println!("{} {} prompt(s) valid", "✓".green(), prompts.len());
+                } else {
+                    println!(
+                        "\n{} error(s), {} warning(s)",
+                        errors,
+                        findings.len() - errors
+                    );
+                }
+            }
+            _ => {
+                let located = findings
+                    .iter()
+                    .map(|finding| self.locate_finding(finding))
+                    .collect::<Result<Vec<_>>>()?;
+                if sarif {
+                    println!("{}", serde_json::to_string_pretty(&sarif_log(&located))?);
+                } else {
+                    self.print_structured(&located)?;
                 }
             }
         }
 
-        println!();
+        if errors > 0 {
+            return Err(PromptBankError::LintFailed(errors));
+        }
         Ok(())
     }
 
-    fn claude_remove(&self, name: &str) -> Result<()> {
-        let claude = ClaudeIntegration::new()?;
-        let removed = claude.remove(name)?;
+    /// Map a finding to a file and line
+    ///
+    /// In the markdown layout this is the prompt's own file (relative to the
+    /// bank directory, which is usually the repository root); otherwise it is
+    /// the bank's data file.
+    fn locate_finding<'a>(&self, finding: &'a Finding) -> Result<LocatedFinding<'a>> {
+        let prompt = self
+            .bank
+            .prompts
+            .iter()
+            .find(|p| p.id == finding.prompt_id)
+            .ok_or_else(|| self.bank.not_found(&finding.prompt_id))?;
+
+        if self.storage.format() != StorageFormat::Markdown {
+            return Ok(LocatedFinding {
+                finding,
+                file: self.storage.data_file_path().display().to_string(),
+                line: None,
+            });
+        }
 
-        if removed {
-            println!("{} Removed '{}' from Claude", "✓".green(), name);
+        let line = match finding.span {
+            Span::Name => markdown::front_matter_line(prompt, "name")?,
+            Span::Description => markdown::front_matter_line(prompt, "description")?,
+            Span::Aliases => markdown::front_matter_line(prompt, "aliases")?,
+            Span::Content(line) => Some(markdown::content_line(prompt)? + line - 1),
+        };
+        Ok(LocatedFinding {
+            finding,
+            file: markdown::prompt_path(prompt)
+                .to_string_lossy()
+                .replace('\\', "/"),
+            line,
+        })
+    }
+
+    fn write_index(&self, write: Option<PathBuf>) -> Result<()> {
+        if self.storage.format() != StorageFormat::Markdown {
+            return Err(PromptBankError::InvalidInput(
+                "An index needs the file-per-prompt layout (set storage.format = \"markdown\")"
+                    .to_string(),
+            ));
+        }
+
+        let index = markdown::render_index(&self.bank);
+        let Some(file) = write else {
+            print!("{}", index);
+            return Ok(());
+        };
+
+        let bank_dir = self.storage.data_file_path();
+        let path = bank_dir.join(&file);
+        if path.exists() && !std::fs::read_to_string(&path)?.starts_with(markdown::INDEX_MARKER) {
+            return Err(PromptBankError::InvalidInput(format!(
+                "{:?} exists and was not generated by promptbank",
+                path
+            )));
+        }
+        info!(path = %path.display(), "writing index");
+        std::fs::write(&path, index)?;
+
+        println!("{} Wrote index to {:?}", "✓".green(), path);
+        if path.parent() != Some(bank_dir.as_path()) {
+            println!(
+                "{} Only indexes at the top of {:?} are regenerated on save",
+                "⚠".yellow(),
+                bank_dir
+            );
+        }
+        Ok(())
+    }
+
+    fn import_prompts(
+        &mut self,
+        input: &PathBuf,
+        merge: bool,
+        map: Option<PathBuf>,
+        with_state: bool,
+        on_conflict: OnConflict,
+        dry_run: bool,
+    ) -> Result<()> {
+        let from_dir = input.is_dir();
+        let before = dry_run.then(|| self.bank.clone());
+        let imported = if from_dir {
+            let map = match map {
+                Some(path) => ImportMap::load(&path)?,
+                None => ImportMap::default(),
+            };
+            let mut bank = PromptBank::new();
+            bank.prompts = ingest::ingest_dir(input, &map, &|c| self.parse_category(c))?
+                .into_iter()
+                .map(|(_, prompt)| prompt)
+                .collect();
+            bank
+        } else if map.is_some() {
+            return Err(PromptBankError::InvalidInput(
+                "--map only applies when importing a directory".to_string(),
+            ));
+        } else {
+            self.storage.import(input, with_state)?
+        };
+        let count = imported.prompts.len();
+
+        // Someone else's prompts keep their author and note where they came from
+        let mut imported = imported;
+        let source = input
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| input.display().to_string());
+        for prompt in &mut imported.prompts {
+            if prompt.author.is_some() && prompt.author != self.config.author {
+                prompt.imported_from = Some(source.clone());
+            }
+        }
+        // Commands are never taken from a file, so importing can't plant one
+        for pipeline in &mut imported.pipelines {
+            if let Some(command) = pipeline.exec.take() {
+                println!(
+                    "{} Dropped the command of pipeline '{}' (`{}`); set one with `pipeline add --force --exec`",
+                    "→".yellow(),
+                    pipeline.name,
+                    command
+                );
+            }
+        }
+
+        if merge {
+            for mut prompt in imported.prompts {
+                // Files get fresh IDs on every import, so match them by name
+                let exists = if from_dir {
+                    self.bank.get(&prompt.name).is_some()
+                } else {
+                    self.bank.get(&prompt.id).is_some()
+                };
+                if !exists {
+                    settle_name_conflict(&mut self.bank, &mut prompt, on_conflict)?;
+                    self.bank.add(prompt);
+                }
+            }
+            for pipeline in imported.pipelines {
+                if !self.bank.pipelines.iter().any(|p| p.name == pipeline.name) {
+                    self.bank.pipelines.push(pipeline);
+                }
+            }
         } else {
-            println!("{} '{}' not found in Claude", "→".yellow(), name);
+            // The file may itself hold prompts sharing a name
+            for mut prompt in std::mem::take(&mut imported.prompts) {
+                settle_name_conflict(&mut imported, &mut prompt, on_conflict)?;
+                imported.add(prompt);
+            }
+            if !with_state {
+                // Keep this machine's usage and history for prompts that survive
+                imported.restore_state(self.bank.split_state().1);
+            }
+            self.bank = imported;
         }
 
+        if let Some(before) = before {
+            print_bank_changes(&before, &self.bank);
+            return Ok(());
+        }
+        self.storage.save(&self.bank)?;
+        println!(
+            "{} Imported {} prompts from {:?}",
+            "✓".green(),
+            count,
+            input
+        );
         Ok(())
     }
 
-    fn run_community(&mut self, cmd: CommunityCommands) -> Result<()> {
-        match cmd {
-            CommunityCommands::Browse { category } => self.community_browse(category),
-            CommunityCommands::Install { name, apply, copy } => self.community_install(&name, apply, copy),
-            CommunityCommands::Search { query } => self.community_search(&query),
-            CommunityCommands::Contribute => self.community_contribute(),
+    fn print_completions(&self, kind: CompletionKind) -> Result<()> {
+        let mut candidates: Vec<&str> = match kind {
+            CompletionKind::Prompts => self
+                .bank
+                .prompts
+                .iter()
+                .flat_map(|p| [p.id.as_str(), p.name.as_str()])
+                .collect(),
+            CompletionKind::Tags => self
+                .bank
+                .prompts
+                .iter()
+                .flat_map(|p| p.tags.iter().map(|t| t.as_str()))
+                .filter(|t| !t.is_empty())
+                .collect(),
+        };
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        for candidate in candidates {
+            println!("{}", candidate);
         }
+        Ok(())
     }
 
-    fn community_browse(&self, category: Option<String>) -> Result<()> {
-        println!("{}", "Fetching community prompts...".dimmed());
-
-        let index = Community::fetch_index()?;
+    fn write_recommendations(&self, names: Vec<String>) -> Result<()> {
+        let cwd = std::env::current_dir()?.canonicalize()?;
 
-        let prompts: Vec<_> = if let Some(cat) = category {
-            index
+        let prompts = if names.is_empty() {
+            self.bank
                 .prompts
                 .iter()
-                .filter(|p| p.category.to_lowercase() == cat.to_lowercase())
+                .filter(|p| p.installed_projects.contains(&cwd))
+                .map(|p| p.name.clone())
                 .collect()
         } else {
-            index.prompts.iter().collect()
+            names
         };
 
         if prompts.is_empty() {
-            println!("{}", "No community prompts found.".yellow());
-            return Ok(());
+            return Err(PromptBankError::InvalidInput(
+                "No prompts to recommend (pass names or install prompts into this project)"
+                    .to_string(),
+            ));
         }
 
+        let count = prompts.len();
+        let path = Recommendations { prompts }.save(&cwd)?;
         println!(
-            "\n{} {} community prompt(s) available:\n",
-            "→".blue(),
-            prompts.len().to_string().cyan()
+            "{} Recommended {} prompt(s) in {:?}",
+            "✓".green(),
+            count,
+            path
         );
+        Ok(())
+    }
 
-        for prompt in prompts {
+    fn install_recommendations(&mut self, yes: bool, on_conflict: OnConflict) -> Result<()> {
+        let cwd = std::env::current_dir()?;
+        let recommendations = Recommendations::load(&cwd)?.ok_or_else(|| {
+            PromptBankError::InvalidInput(format!(
+                "No {} in the current directory",
+                RECOMMENDED_FILE
+            ))
+        })?;
+
+        let missing: Vec<&String> = recommendations
+            .prompts
+            .iter()
+            .filter(|name| self.bank.get(name).is_none())
+            .collect();
+
+        if missing.is_empty() {
             println!(
-                "  {} [{}] by {}",
-                prompt.name.bold(),
-                prompt.category.yellow(),
-                prompt.author.dimmed()
+                "{} All {} recommended prompt(s) are installed",
+                "✓".green(),
+                recommendations.prompts.len()
             );
-            println!("    {}", prompt.description);
-            if !prompt.tags.is_empty() {
-                println!("    Tags: {}", prompt.tags.join(", ").blue());
-            }
-            println!();
+            return Ok(());
         }
 
         println!(
-            "Install with: {} <name>",
-            "promptbank community install".cyan()
+            "\n{} {} recommended prompt(s) missing: {}\n",
+            "→".blue(),
+            missing.len().to_string().cyan(),
+            missing
+                .iter()
+                .map(|n| n.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
         );
 
-        Ok(())
-    }
-
-    fn community_install(&mut self, name: &str, apply: bool, copy: bool) -> Result<()> {
-        println!("{}", "Fetching community index...".dimmed());
-
-        let index = Community::fetch_index()?;
-
-        let entry = index
-            .prompts
-            .iter()
-            .find(|p| p.name.to_lowercase() == name.to_lowercase())
-            .ok_or_else(|| PromptBankError::PromptNotFound(name.to_string()))?;
+        if !yes && !self.interactive {
+            println!("Run with --yes to install them.");
+            return Ok(());
+        }
 
-        println!("Installing '{}'...", entry.name);
+        let community = Community::new(&self.config.community);
+        let mut community_index = None;
+        let mut installed = 0;
 
-        let community_prompt = Community::fetch_prompt(&entry.path)?;
-        let prompt = Community::to_local_prompt(community_prompt)?;
-        let prompt_name = prompt.name.clone();
-        let prompt_id = prompt.id.clone();
-        let prompt_content = prompt.content.clone();
+        for name in missing {
+            if !yes {
+                let confirm = Confirm::new()
+                    .with_prompt(format!("Install '{}'?", name))
+                    .default(true)
+                    .interact()
+                    .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+                if !confirm {
+                    continue;
+                }
+            }
 
-        // Save to local promptbank
-        self.bank.add(prompt.clone());
-        self.storage.save(&self.bank)?;
+            // Prefer prompts from the user's other banks over the community
+            let mut found = None;
+            for bank_name in Storage::bank_names()? {
+                if bank_name == self.storage.bank_name() {
+                    continue;
+                }
+                let bank = Storage::open(&bank_name, self.config.storage.format)?.load()?;
+                if let Some(prompt) = bank.get(name) {
+                    found = Some((prompt.clone(), format!("bank '{}'", bank_name)));
+                    break;
+                }
+            }
 
-        println!(
-            "{} Saved to promptbank with ID: {}",
-            "✓".green(),
-            prompt_id.cyan()
-        );
+            if found.is_none() {
+                if community_index.is_none() {
+                    community_index = Some(community.fetch_index()?);
+                }
+                let entry = community_index
+                    .as_ref()
+                    .and_then(|index| {
+                        index
+                            .prompts
+                            .iter()
+                            .find(|p| p.name.to_lowercase() == name.to_lowercase())
+                    });
+                if let Some(entry) = entry {
+                    let prompt = Community::to_local_prompt(community.fetch_prompt(&entry.path)?)?;
+                    found = Some((prompt, "community".to_string()));
+                }
+            }
 
-        // Install to Claude as skill
-        match ClaudeIntegration::new() {
-            Ok(claude) => {
-                match claude.install(&prompt, InstallType::Skill) {
-                    Ok(_) => {
-                        println!(
-                            "{} Installed as Claude skill: {}",
-                            "✓".green(),
-                            format!("/{}", prompt_name).cyan()
-                        );
-                    }
-                    Err(e) => {
-                        println!(
-                            "{} Could not install to Claude: {}",
-                            "⚠".yellow(),
-                            e
-                        );
+            match found {
+                Some((mut prompt, source)) => {
+                    // A taken name skips the prompt rather than stopping the rest
+                    if let Err(e) = settle_name_conflict(&mut self.bank, &mut prompt, on_conflict) {
+                        println!("{} {}", "✗".red(), e);
+                        continue;
                     }
+                    println!("{} Installed '{}' from {}", "✓".green(), prompt.name, source);
+                    self.bank.add(prompt);
+                    installed += 1;
                 }
+                None => println!("{} '{}' not found in any bank or the community", "✗".red(), name),
             }
-            Err(_) => {
-                println!(
-                    "{} Claude not found - skipping skill installation",
-                    "⚠".yellow()
-                );
-            }
-        }
-
-        if apply {
-            println!("\n{}", "═".repeat(60).dimmed());
-            println!("{}", prompt_content);
-            println!("{}", "═".repeat(60).dimmed());
         }
 
-        if copy {
-            self.copy_to_clipboard(&prompt_content)?;
-            println!("\n{} Copied to clipboard!", "✓".green());
+        if installed > 0 {
+            self.storage.save(&self.bank)?;
         }
-
         Ok(())
     }
 
-    fn community_search(&self, query: &str) -> Result<()> {
-        println!("{}", "Searching community prompts...".dimmed());
+    fn show_variables(&self, archived: bool) -> Result<()> {
+        let mut users: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for prompt in self.bank.prompts.iter().filter(|p| archived || !p.archived) {
+            for variable in &prompt.variables {
+                users.entry(variable).or_default().push(&prompt.name);
+            }
+        }
 
-        let index = Community::fetch_index()?;
-        let results = Community::search(&index, query);
+        let names: Vec<&str> = users.keys().copied().collect();
+        let mut usages: Vec<VariableUsage> = users
+            .into_iter()
+            .map(|(name, prompts)| VariableUsage {
+                name,
+                prompts,
+                similar: names
+                    .iter()
+                    .copied()
+                    .filter(|other| *other != name && similar_variables(name, other))
+                    .collect(),
+            })
+            .collect();
+        usages.sort_by_key(|u| std::cmp::Reverse(u.prompts.len()));
 
-        if results.is_empty() {
-            println!("{} No community prompts matching '{}'", "→".yellow(), query);
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&usages);
+        }
+
+        if usages.is_empty() {
+            println!("{}", "No variables found.".yellow());
             return Ok(());
         }
 
+        let theme = &self.config.theme;
         println!(
-            "\n{} {} result(s) for '{}':\n",
+            "\n{} {} variable(s) found:\n",
             "→".blue(),
-            results.len().to_string().cyan(),
-            query
+            usages.len().to_string().cyan()
         );
-
-        for prompt in results {
+        for usage in &usages {
             println!(
-                "  {} [{}] by {}",
-                prompt.name.bold(),
-                prompt.category.yellow(),
-                prompt.author.dimmed()
+                "  {} ({})",
+                usage.name.color(theme.variables()),
+                usage.prompts.len()
             );
-            println!("    {}", prompt.description);
-            println!();
+            println!("    {}", usage.prompts.join(", ").dimmed());
+            if !usage.similar.is_empty() {
+                println!(
+                    "    {} similar to: {}",
+                    "⚠".yellow(),
+                    usage.similar.join(", ")
+                );
+            }
         }
-
-        Ok(())
-    }
-
-    fn community_contribute(&self) -> Result<()> {
-        println!("\n{}", "Contributing to PromptBank Community".bold().underline());
-        println!();
-        println!("To share your prompts with the community:");
-        println!();
-        println!("  1. Fork the repository:");
-        println!("     {}", Community::repo_url().cyan());
-        println!();
-        println!("  2. Add your prompt file to the appropriate category folder:");
-        println!("     prompts/{{category}}/{{your-prompt-name}}.json");
-        println!();
-        println!("  3. Update index.json with your prompt entry");
-        println!();
-        println!("  4. Submit a Pull Request");
-        println!();
-        println!("See the README for detailed contribution guidelines.");
         println!();
-
-        Ok(())
-    }
-
-    fn add_prompt(
-        &mut self,
-        name: Option<String>,
-        category: Option<String>,
-        description: Option<String>,
-        tags: Option<String>,
-        content: Option<String>,
-        file: Option<PathBuf>,
-    ) -> Result<()> {
-        // Get name interactively if not provided
-        let name = match name {
-            Some(n) => n,
-            None => Input::new()
-                .with_prompt("Prompt name")
-                .interact_text()
-                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?,
-        };
-
-        // Get category interactively if not provided
-        let category = match category {
-            Some(c) => c.parse()?,
-            None => {
-                let categories = vec!["system", "skill", "agent", "role", "task", "template"];
-                let selection = Select::new()
-                    .with_prompt("Select category")
-                    .items(&categories)
-                    .default(0)
-                    .interact()
-                    .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
-                categories[selection].parse()?
-            }
-        };
-
-        // Get description
-        let description = match description {
-            Some(d) => d,
-            None => Input::new()
-                .with_prompt("Description")
-                .interact_text()
-                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?,
-        };
-
-        // Get tags
-        let tags: Vec<String> = match tags {
-            Some(t) => t.split(',').map(|s| s.trim().to_string()).collect(),
-            None => {
-                let tags_str: String = Input::new()
-                    .with_prompt("Tags (comma-separated, optional)")
-                    .allow_empty(true)
-                    .interact_text()
-                    .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
-                if tags_str.is_empty() {
-                    Vec::new()
-                } else {
-                    tags_str.split(',').map(|s| s.trim().to_string()).collect()
-                }
-            }
-        };
-
-        // Get content
-        let content = if let Some(path) = file {
-            std::fs::read_to_string(&path)?
-        } else if let Some(c) = content {
-            c
-        } else {
-            Editor::new()
-                .edit("# Enter your prompt content here\n")
-                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
-                .ok_or_else(|| PromptBankError::InvalidInput("No content provided".to_string()))?
-        };
-
-        let prompt = Prompt::new(name.clone(), category, description, content, tags);
-        let id = prompt.id.clone();
-        self.bank.add(prompt);
-        self.storage.save(&self.bank)?;
-
-        println!("{} Prompt '{}' added with ID: {}", "✓".green(), name, id.cyan());
         Ok(())
     }
 
-    fn list_prompts(&self, category: Option<String>, full: bool) -> Result<()> {
-        let prompts: Vec<&Prompt> = if let Some(cat) = category {
-            let cat: PromptCategory = cat.parse()?;
-            self.bank.list_by_category(&cat)
-        } else {
-            self.bank.prompts.iter().collect()
-        };
-
-        if prompts.is_empty() {
-            println!("{}", "No prompts found.".yellow());
-            return Ok(());
-        }
-
+    fn watch_prompt(&mut self, id: &str, file: &Path) -> Result<()> {
+        use notify::{RecursiveMode, Watcher};
+
+        let prompt_id = self.bank.find(id)?.id.clone();
+        let file = file.canonicalize().map_err(|e| {
+            PromptBankError::InvalidInput(format!("Cannot watch {:?}: {}", file, e))
+        })?;
+        self.sync_from_file(&prompt_id, &file)?;
+
+        // Editors often save by replacing the file, so watch its directory
+        let dir = file.parent().unwrap_or(&file);
+        let watch_error =
+            |e: notify::Error| PromptBankError::Storage(format!("Cannot watch {:?}: {}", dir, e));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+
         println!(
-            "\n{} {} prompt(s) found:\n",
+            "{} Watching {} (Ctrl-C to stop)",
             "→".blue(),
-            prompts.len().to_string().cyan()
+            file.display()
         );
+        for event in &rx {
+            match event {
+                Ok(event) if event.paths.contains(&file) => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    eprintln!("{} {}", "⚠".yellow(), e);
+                    continue;
+                }
+            }
+            // A save arrives as several events; handle them once
+            std::thread::sleep(std::time::Duration::from_millis(WATCH_SETTLE_MS));
+            while rx.try_recv().is_ok() {}
 
-        for prompt in prompts {
-            self.print_prompt_summary(prompt, full);
+            if let Err(e) = self.sync_from_file(&prompt_id, &file) {
+                eprintln!("{} {}", "⚠".yellow(), e);
+            }
         }
-
         Ok(())
     }
 
-    fn get_prompt(&self, id: &str, copy: bool, raw: bool) -> Result<()> {
-        let prompt = self
-            .bank
-            .get(id)
-            .ok_or_else(|| PromptBankError::PromptNotFound(id.to_string()))?;
-
-        if raw {
-            println!("{}", prompt.content);
-        } else {
-            self.print_prompt_full(prompt);
+    /// Copy a file's content into a prompt if it differs
+    ///
+    /// The bank is reloaded first, so changes made elsewhere while watching
+    /// are kept.
+    fn sync_from_file(&mut self, id: &str, file: &Path) -> Result<()> {
+        if !file.exists() {
+            eprintln!(
+                "{} {} is gone; waiting for it to be saved again",
+                "⚠".yellow(),
+                file.display()
+            );
+            return Ok(());
         }
+        let (_, content) = ingest::split_front_matter(&std::fs::read_to_string(file)?);
 
-        if copy {
-            self.copy_to_clipboard(&prompt.content)?;
-            if !raw {
-                println!("\n{} Copied to clipboard!", "✓".green());
-            }
+        self.bank = self.storage.load()?;
+        let prompt = self.bank.find_mut(id)?;
+        if prompt.content == content {
+            return Ok(());
         }
+        prompt.update_content(content);
+        let name = prompt.name.clone();
+        self.storage.save(&self.bank)?;
 
+        println!(
+            "{} {} Updated '{}'",
+            "✓".green(),
+            chrono::Local::now().format("%H:%M:%S").to_string().dimmed(),
+            name
+        );
         Ok(())
     }
 
-    fn apply_prompt(
-        &mut self,
-        id: &str,
-        vars: Vec<String>,
-        copy: bool,
-        interactive: bool,
-    ) -> Result<()> {
-        let prompt = self
-            .bank
-            .get(id)
-            .ok_or_else(|| PromptBankError::PromptNotFound(id.to_string()))?;
+    fn open_path(&self, id: Option<&str>, print: bool) -> Result<()> {
+        let path = match id {
+            Some(id) => {
+                if self.storage.format() != StorageFormat::Markdown {
+                    return Err(PromptBankError::InvalidInput(
+                        "Prompts only have files of their own with storage.format = \"markdown\" \
+                         (run `promptbank open` for the data directory)"
+                            .to_string(),
+                    ));
+                }
+                let prompt = self.bank.find(id)?;
+                self.storage
+                    .data_file_path()
+                    .join(markdown::prompt_path(prompt))
+            }
+            None => Storage::get_data_dir()?,
+        };
 
-        let mut substitutions: Vec<(String, String)> = Vec::new();
+        if print {
+            println!("{}", path.display());
+            return Ok(());
+        }
+        println!("{} Opening {}", "→".blue(), path.display());
+        match id {
+            Some(_) => self.config.edit_file(&path),
+            None => reveal(&path),
+        }
+    }
 
-        // Parse provided variables
-        for var in vars {
-            let parts: Vec<&str> = var.splitn(2, '=').collect();
-            if parts.len() == 2 {
-                substitutions.push((parts[0].to_string(), parts[1].to_string()));
+    fn show_stats(&self, archived: bool) -> Result<()> {
+        let prompts: Vec<&Prompt> = self
+            .bank
+            .prompts
+            .iter()
+            .filter(|p| archived || !p.archived)
+            .collect();
+
+        let mut categories = BTreeMap::new();
+        let mut tags = BTreeMap::new();
+        let mut created = BTreeMap::new();
+        let mut updated = BTreeMap::new();
+        let mut variables = BTreeMap::new();
+        for prompt in &prompts {
+            *categories.entry(prompt.category.to_string()).or_insert(0) += 1;
+            for tag in &prompt.tags {
+                *tags.entry(tag.as_str()).or_insert(0) += 1;
+            }
+            *created
+                .entry(prompt.created_at.format("%Y-%m").to_string())
+                .or_insert(0) += 1;
+            *updated
+                .entry(prompt.updated_at.format("%Y-%m").to_string())
+                .or_insert(0) += 1;
+            for variable in &prompt.variables {
+                *variables.entry(variable.as_str()).or_insert(0) += 1;
             }
         }
+        let total_length: usize = prompts.iter().map(|p| p.content.chars().count()).sum();
+
+        let report = StatsReport {
+            total_prompts: prompts.len(),
+            categories,
+            tags,
+            created,
+            updated,
+            average_length: total_length.checked_div(prompts.len()).unwrap_or(0),
+            variables,
+            most_used: most_used(prompts.iter().copied()),
+        };
 
-        // Interactive mode for remaining variables
-        if interactive && !prompt.variables.is_empty() {
-            println!(
-                "\n{} This prompt has {} variable(s):\n",
-                "→".blue(),
-                prompt.variables.len()
-            );
+        if self.format != OutputFormat::Text {
+            return self.print_structured(&report);
+        }
 
-            for var in &prompt.variables {
-                let existing = substitutions.iter().find(|(k, _)| k == var);
-                if existing.is_none() {
-                    let value: String = Input::new()
-                        .with_prompt(format!("  {}", var))
-                        .interact_text()
-                        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
-                    substitutions.push((var.clone(), value));
-                }
-            }
+        if report.total_prompts == 0 {
+            println!("{}", "No prompts found.".yellow());
+            return Ok(());
         }
 
-        let rendered = prompt.render(&substitutions);
+        println!("\n{}", "Promptbank Stats".bold().underline());
+        println!("  Prompts: {}", report.total_prompts);
+        println!("  Average length: {} characters", report.average_length);
 
-        println!("\n{}", "═".repeat(60).dimmed());
-        println!("{}", rendered);
-        println!("{}", "═".repeat(60).dimmed());
+        print_counts("By category", by_count(&report.categories));
+        print_counts("By tag", by_count(&report.tags));
+        print_counts("Created per month", report.created.iter().collect());
+        print_counts("Updated per month", report.updated.iter().collect());
+        print_counts("Variables (prompts using)", by_count(&report.variables));
 
-        if copy {
-            self.copy_to_clipboard(&rendered)?;
-            println!("\n{} Copied to clipboard!", "✓".green());
+        if !report.most_used.is_empty() {
+            println!("\n  {}:", "Most used".dimmed());
+            for usage in &report.most_used {
+                println!("    {} ({})", usage.name, usage.use_count);
+            }
         }
 
+        println!();
         Ok(())
     }
 
-    fn edit_prompt(&mut self, id: &str) -> Result<()> {
-        let prompt = self
-            .bank
-            .get(id)
-            .ok_or_else(|| PromptBankError::PromptNotFound(id.to_string()))?;
-
-        let current_content = prompt.content.clone();
-
-        let new_content = Editor::new()
-            .edit(&current_content)
-            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?
-            .ok_or_else(|| PromptBankError::InvalidInput("No content provided".to_string()))?;
+    fn show_info(&self) -> Result<()> {
+        let total_uses: u64 = self.bank.prompts.iter().map(|p| p.use_count).sum();
+        let most_used = most_used(self.bank.prompts.iter());
 
-        if new_content == current_content {
-            println!("{}", "No changes made.".yellow());
-            return Ok(());
+        if self.format != OutputFormat::Text {
+            let mut categories = BTreeMap::new();
+            for prompt in &self.bank.prompts {
+                *categories.entry(prompt.category.to_string()).or_insert(0) += 1;
+            }
+            return self.print_structured(&InfoReport {
+                bank: self.storage.bank_name(),
+                data_file: self.storage.data_file_path(),
+                total_prompts: self.bank.prompts.len(),
+                categories,
+                total_uses,
+                most_used,
+                size_bytes: self.storage.size_on_disk()?,
+                warnings: self.quota_warnings()?,
+            });
         }
 
-        let prompt = self
-            .bank
-            .get_mut(id)
-            .ok_or_else(|| PromptBankError::PromptNotFound(id.to_string()))?;
-        prompt.update_content(new_content);
-        self.storage.save(&self.bank)?;
-
-        println!("{} Prompt '{}' updated.", "✓".green(), id);
-        Ok(())
-    }
-
-    fn delete_prompt(&mut self, id: &str, force: bool) -> Result<()> {
-        let prompt = self
-            .bank
-            .get(id)
-            .ok_or_else(|| PromptBankError::PromptNotFound(id.to_string()))?;
+        println!("\n{}", "Promptbank Info".bold().underline());
+        println!("  Bank: {}", self.storage.bank_name());
+        println!("  Data file: {:?}", self.storage.data_file_path());
+        println!("  Total prompts: {}", self.bank.prompts.len());
 
-        let name = prompt.name.clone();
+        // Count by category
+        let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        for prompt in &self.bank.prompts {
+            *counts.entry(prompt.category.to_string()).or_insert(0) += 1;
+        }
 
-        if !force {
-            let confirm = Select::new()
-                .with_prompt(format!("Delete prompt '{}'?", name))
-                .items(&["No", "Yes"])
-                .default(0)
-                .interact()
-                .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        if !counts.is_empty() {
+            println!("\n  {}:", "By category".dimmed());
+            for (cat, count) in counts {
+                println!("    {}: {}", cat, count);
+            }
+        }
 
-            if confirm == 0 {
-                println!("{}", "Cancelled.".yellow());
-                return Ok(());
+        println!("\n  Total uses: {}", total_uses);
+        if !most_used.is_empty() {
+            println!("  {}:", "Most used".dimmed());
+            for usage in most_used {
+                println!("    {} ({})", usage.name, usage.use_count);
             }
         }
 
-        self.bank.delete(id);
-        self.storage.save(&self.bank)?;
+        let warnings = self.quota_warnings()?;
+        if !warnings.is_empty() {
+            println!();
+            for warning in &warnings {
+                println!("  {} {}", "⚠".yellow(), warning);
+            }
+            println!("  {}", QUOTA_HINT.dimmed());
+        }
 
-        println!("{} Prompt '{}' deleted.", "✓".green(), name);
+        println!();
         Ok(())
     }
 
-    fn search_prompts(&self, query: &str) -> Result<()> {
-        let prompts = self.bank.search(query);
-
-        if prompts.is_empty() {
-            println!("{} No prompts matching '{}'", "→".yellow(), query);
-            return Ok(());
+    /// Check the bank against the soft limits in `[quota]`
+    fn quota_warnings(&self) -> Result<Vec<String>> {
+        let quota = &self.config.quota;
+        let mut warnings = Vec::new();
+
+        let count = self.bank.prompts.len();
+        if let Some(max) = quota.max_prompts.filter(|&max| count > max) {
+            warnings.push(format!(
+                "Bank '{}' has {} prompts (quota: {})",
+                self.storage.bank_name(),
+                count,
+                max
+            ));
         }
 
-        println!(
-            "\n{} {} result(s) for '{}':\n",
-            "→".blue(),
-            prompts.len().to_string().cyan(),
-            query
-        );
-
-        for prompt in prompts {
-            self.print_prompt_summary(prompt, false);
+        if let Some(max) = quota.max_bank_bytes()? {
+            let size = self.storage.size_on_disk()?;
+            if size > max {
+                warnings.push(format!(
+                    "Bank '{}' uses {} on disk (quota: {})",
+                    self.storage.bank_name(),
+                    format_size(size),
+                    format_size(max)
+                ));
+            }
         }
 
-        Ok(())
+        Ok(warnings)
     }
 
-    fn export_prompts(&self, output: &PathBuf) -> Result<()> {
-        self.storage.export(&self.bank, output)?;
+    fn run_doctor(&self) -> Result<()> {
+        println!("\n{}", "Promptbank Doctor".bold().underline());
+        println!("  Bank: {}", self.storage.bank_name());
         println!(
-            "{} Exported {} prompts to {:?}",
-            "✓".green(),
+            "  Size: {} prompts, {}",
             self.bank.prompts.len(),
-            output
+            format_size(self.storage.size_on_disk()?)
         );
+
+        let mut healthy = true;
+
+        let warnings = self.quota_warnings()?;
+        for warning in &warnings {
+            println!("  {} {}", "⚠".yellow(), warning);
+        }
+        if !warnings.is_empty() {
+            println!("    {}", QUOTA_HINT.dimmed());
+            healthy = false;
+        }
+
+        // Duplicate names get their own report, naming the prompts involved
+        let (duplicates, findings): (Vec<Finding>, Vec<Finding>) = lint::lint_bank(&self.bank)
+            .into_iter()
+            .partition(|f| f.rule == Rule::DuplicateName);
+        let errors = findings.iter().filter(|f| f.level == Level::Error).count();
+        if !findings.is_empty() {
+            println!(
+                "  {} {} lint error(s), {} warning(s) (see {})",
+                "⚠".yellow(),
+                errors,
+                findings.len() - errors,
+                "promptbank lint".cyan()
+            );
+            healthy = false;
+        }
+
+        let mut by_name: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for finding in &duplicates {
+            by_name
+                .entry(&finding.prompt_name)
+                .or_default()
+                .push(&finding.prompt_id);
+        }
+        for (name, ids) in &by_name {
+            println!(
+                "  {} Name '{}' is shared by {} prompts ({}); only the first is reachable by name",
+                "⚠".yellow(),
+                name,
+                ids.len(),
+                ids.join(", ")
+            );
+            healthy = false;
+        }
+
+        if healthy {
+            println!("  {} No problems found", "✓".green());
+        }
+        println!();
         Ok(())
     }
 
-    fn import_prompts(&mut self, input: &PathBuf, merge: bool) -> Result<()> {
-        let imported = self.storage.import(input)?;
-        let count = imported.prompts.len();
+    fn show_status(&self, strict: bool) -> Result<()> {
+        let problems = self.status_problems()?;
 
-        if merge {
-            for prompt in imported.prompts {
-                if self.bank.get(&prompt.id).is_none() {
-                    self.bank.add(prompt);
-                }
-            }
+        if self.format != OutputFormat::Text {
+            self.print_structured(&StatusReport {
+                bank: self.storage.bank_name(),
+                data_file: self.storage.data_file_path(),
+                problems: &problems,
+            })?;
+        } else if problems.is_empty() {
+            println!(
+                "{} Bank '{}' is up to date",
+                "✓".green(),
+                self.storage.bank_name()
+            );
         } else {
-            self.bank = imported;
+            for problem in &problems {
+                println!("{} {}", "⚠".yellow(), problem);
+            }
         }
 
-        self.storage.save(&self.bank)?;
-        println!(
-            "{} Imported {} prompts from {:?}",
-            "✓".green(),
-            count,
-            input
-        );
+        if strict && !problems.is_empty() {
+            return Err(PromptBankError::StatusFailed(problems.len()));
+        }
         Ok(())
     }
 
-    fn show_info(&self) -> Result<()> {
-        println!("\n{}", "Promptbank Info".bold().underline());
-        println!("  Data file: {:?}", self.storage.data_file_path());
-        println!("  Total prompts: {}", self.bank.prompts.len());
+    /// Everything that keeps the bank from being fully provisioned
+    fn status_problems(&self) -> Result<Vec<String>> {
+        let mut problems = Vec::new();
+
+        if !self.storage.data_file_path().exists() {
+            problems.push(format!(
+                "Bank '{}' does not exist yet",
+                self.storage.bank_name()
+            ));
+        } else if self.bank.version != BANK_VERSION {
+            problems.push(format!(
+                "Bank schema is version {}, expected {}",
+                self.bank.version, BANK_VERSION
+            ));
+        }
 
-        // Count by category
-        let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        for prompt in &self.bank.prompts {
-            *counts.entry(prompt.category.to_string()).or_insert(0) += 1;
+        let cwd = std::env::current_dir()?;
+        if let Some(recommendations) = Recommendations::load(&cwd)? {
+            for name in &recommendations.prompts {
+                if self.bank.get(name).is_none() {
+                    problems.push(format!("Recommended prompt '{}' is not installed", name));
+                }
+            }
         }
 
-        if !counts.is_empty() {
-            println!("\n  {}:", "By category".dimmed());
-            for (cat, count) in counts {
-                println!("    {}: {}", cat, count);
+        // ~/.claude plus every project prompts were installed into
+        let mut targets = Vec::new();
+        if let Ok(claude) = ClaudeIntegration::new() {
+            targets.push(claude);
+        }
+        let mut projects: Vec<&PathBuf> = self
+            .bank
+            .prompts
+            .iter()
+            .flat_map(|p| &p.installed_projects)
+            .filter(|project| project.is_dir())
+            .collect();
+        projects.sort();
+        projects.dedup();
+        for project in projects {
+            targets.push(ClaudeIntegration::for_project(project)?);
+        }
+        for claude in &targets {
+            for artifact in claude.status(&self.bank, self.storage.bank_name())? {
+                let state = match artifact.state {
+                    ArtifactState::InSync => continue,
+                    ArtifactState::Outdated => "outdated",
+                    ArtifactState::Modified => "modified",
+                    ArtifactState::Missing => "missing",
+                    ArtifactState::Orphaned => "orphaned",
+                };
+                problems.push(format!(
+                    "{} is {} in {}",
+                    artifact.install_type.invocation(&artifact.name),
+                    state,
+                    claude.claude_dir().display()
+                ));
             }
         }
 
-        println!();
-        Ok(())
+        Ok(problems)
     }
 
     fn update_self(&self) -> Result<()> {
@@ -846,11 +6394,18 @@ impl App {
 
         // Fetch latest release from GitHub
         let release_url = "https://api.github.com/repos/ff-vivek/promptbank/releases/latest";
-        let response = match ureq::get(release_url)
-            .set("User-Agent", "promptbank")
-            .call() {
-                Ok(r) => r,
-                Err(_) => {
+        info!(url = release_url, "checking for a new release");
+        let response = match net::spin("Checking the latest release", || {
+            profile::measure(Phase::Network, || {
+                net::agent()
+                    .get(release_url)
+                    .set("User-Agent", "promptbank")
+                    .call()
+                    .ok()
+            })
+        }) {
+                Some(r) => r,
+                None => {
                     println!("  No binary releases found, using cargo install...\n");
                     return self.update_via_cargo();
                 }
@@ -883,7 +6438,7 @@ impl App {
 
         let asset = match assets
             .iter()
-            .find(|a| a["name"].as_str().map_or(false, |n| n.contains(&binary_name))) {
+            .find(|a| a["name"].as_str().is_some_and(|n| n.contains(&binary_name))) {
                 Some(a) => a,
                 None => {
                     println!("  No binary for {}, using cargo install...\n", binary_name);
@@ -896,15 +6451,26 @@ impl App {
             .ok_or_else(|| PromptBankError::Storage("No download URL found".to_string()))?;
 
         // Download the binary
-        let response = ureq::get(download_url)
-            .call()
-            .map_err(|e| PromptBankError::Storage(format!("Failed to download: {}", e)))?;
+        info!(url = download_url, "downloading release");
+        let response = profile::measure(Phase::Network, || {
+            net::agent()
+                .get(download_url)
+                .call()
+                .map_err(|e| net::error("Downloading the update", e))
+        })?;
 
         // Save to temp file
         let temp_dir = std::env::temp_dir();
         let tar_path = temp_dir.join(format!("{}.tar.gz", binary_name));
         let mut file = std::fs::File::create(&tar_path)?;
-        std::io::copy(&mut response.into_reader(), &mut file)?;
+        let length = response
+            .header("Content-Length")
+            .and_then(|len| len.parse().ok());
+        let bar = net::download_bar(&binary_name, length);
+        profile::measure(Phase::Network, || {
+            std::io::copy(&mut bar.wrap_read(response.into_reader()), &mut file)
+        })?;
+        bar.finish_and_clear();
 
         // Extract and install
         let current_exe = std::env::current_exe()
@@ -988,67 +6554,214 @@ impl App {
         Ok(name.to_string())
     }
 
-    fn print_prompt_summary(&self, prompt: &Prompt, full: bool) {
+    fn print_prompt_summary(&self, prompt: &Prompt, full: bool, plain: bool, size: bool) {
+        let theme = &self.config.theme;
+        let category = prompt.category.to_string();
         println!(
             "  {} {} [{}]",
-            prompt.id.cyan(),
+            prompt.id.color(theme.accent()),
             prompt.name.bold(),
-            prompt.category.to_string().yellow()
+            category.color(theme.category(&category))
         );
+        if prompt.archived {
+            println!("    {}", "(archived)".dimmed());
+        }
         println!("    {}", prompt.description.dimmed());
+        if prompt.use_count > 0 {
+            println!(
+                "    {}",
+                format!(
+                    "Used {} time{}",
+                    prompt.use_count,
+                    if prompt.use_count == 1 { "" } else { "s" }
+                )
+                .dimmed()
+            );
+        }
 
         if !prompt.tags.is_empty() {
-            println!("    Tags: {}", prompt.tags.join(", ").blue());
+            println!("    Tags: {}", prompt.tags.join(", ").color(theme.tags()));
         }
 
         if !prompt.variables.is_empty() {
             println!(
                 "    Variables: {}",
-                prompt.variables.join(", ").magenta()
+                prompt.variables.join(", ").color(theme.variables())
             );
         }
 
+        if size {
+            println!("    {}", prompt.size().to_string().dimmed());
+        }
+
         if full {
             println!("\n{}", "─".repeat(50).dimmed());
-            println!("{}", prompt.content);
+            print_content(&prompt.content, plain);
             println!("{}\n", "─".repeat(50).dimmed());
         } else {
             println!();
         }
     }
 
-    fn print_prompt_full(&self, prompt: &Prompt) {
+    fn print_prompt_full(&self, prompt: &Prompt, plain: bool) {
+        let theme = &self.config.theme;
+        let category = prompt.category.to_string();
         println!("\n{}", "═".repeat(60).dimmed());
         println!(
             "{}: {} ({})",
             "ID".bold(),
-            prompt.id.cyan(),
-            prompt.category.to_string().yellow()
+            prompt.id.color(theme.accent()),
+            category.color(theme.category(&category))
         );
         println!("{}: {}", "Name".bold(), prompt.name);
         println!("{}: {}", "Description".bold(), prompt.description);
 
         if !prompt.tags.is_empty() {
-            println!("{}: {}", "Tags".bold(), prompt.tags.join(", ").blue());
+            println!(
+                "{}: {}",
+                "Tags".bold(),
+                prompt.tags.join(", ").color(theme.tags())
+            );
         }
 
-        if !prompt.variables.is_empty() {
+        if !prompt.variable_defs.is_empty() {
+            println!("{}:", "Variables".bold());
+            let defaults = prompt.variable_defaults();
+            for var in &prompt.variables {
+                let def = prompt.variable_def(var);
+                let mut notes = Vec::new();
+                if def.kind != VariableType::Text {
+                    notes.push(def.kind.to_string());
+                }
+                if !def.choices.is_empty() {
+                    notes.push(format!("one of {}", def.choices.join(", ")));
+                }
+                if let Some(pattern) = &def.pattern {
+                    notes.push(format!("matching {}", pattern));
+                }
+                if let Some(tag) = &def.wrap {
+                    notes.push(format!("wrapped in <{}>", tag));
+                }
+                if !def.required {
+                    notes.push("optional".to_string());
+                }
+                if let Some(default) = defaults.get(var).filter(|d| !d.is_empty()) {
+                    notes.push(format!("default: {}", default));
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", notes.join(", ")).dimmed().to_string()
+                };
+                match &def.description {
+                    Some(description) => println!(
+                        "  {}{} - {}",
+                        var.color(theme.variables()),
+                        notes,
+                        description
+                    ),
+                    None => println!("  {}{}", var.color(theme.variables()), notes),
+                }
+            }
+        } else if !prompt.variables.is_empty() {
             println!(
                 "{}: {}",
                 "Variables".bold(),
-                prompt.variables.join(", ").magenta()
+                prompt.variables.join(", ").color(theme.variables())
             );
         }
 
+        if !prompt.aliases.is_empty() {
+            println!("{}: {}", "Aliases".bold(), prompt.aliases.join(", "));
+        }
+
+        if let Some(author) = &prompt.author {
+            println!("{}: {}", "Author".bold(), author);
+        }
+        if let Some(source) = &prompt.imported_from {
+            println!("{}: {}", "Imported from".bold(), source);
+        }
+
+        if prompt.clipboard != ClipboardMode::Default {
+            println!("{}: {}", "Clipboard".bold(), prompt.clipboard);
+        }
+        if prompt.engine != TemplateEngine::Simple {
+            println!("{}: {}", "Engine".bold(), prompt.engine);
+        }
+
+        if !prompt.installed_projects.is_empty() {
+            println!("{}:", "Projects".bold());
+            for project in &prompt.installed_projects {
+                println!("  {}", project.display());
+            }
+        }
+
+        println!("{}: {}", "Size".bold(), prompt.size());
+        if let Some(max_tokens) = prompt.max_tokens {
+            println!("{}: ~{} tokens when rendered", "Budget".bold(), max_tokens);
+        }
         println!("{}: {}", "Created".bold(), prompt.created_at.format("%Y-%m-%d %H:%M"));
         println!("{}: {}", "Updated".bold(), prompt.updated_at.format("%Y-%m-%d %H:%M"));
+        if let Some(used) = prompt.last_used_at {
+            println!(
+                "{}: {} ({} uses)",
+                "Last used".bold(),
+                used.format("%Y-%m-%d %H:%M"),
+                prompt.use_count
+            );
+        }
 
         println!("\n{}", "Content:".bold().underline());
         println!("{}", "─".repeat(60).dimmed());
-        println!("{}", prompt.content);
+        print_content(&prompt.content, plain);
         println!("{}", "═".repeat(60).dimmed());
     }
 
+    /// Finish or roll back a save that was interrupted by a crash
+    fn recover_journal(&self, storage: &Storage) -> Result<()> {
+        match storage.recover()? {
+            JournalRecovery::Clean => {}
+            JournalRecovery::Replayed => eprintln!(
+                "{} Recovered unsaved changes to bank '{}' from an interrupted save",
+                "⚠".yellow(),
+                storage.bank_name()
+            ),
+            JournalRecovery::Discarded => eprintln!(
+                "{} Discarded an incomplete save of bank '{}'; the last complete state was kept",
+                "⚠".yellow(),
+                storage.bank_name()
+            ),
+        }
+        Ok(())
+    }
+
+    /// Refuse `--format` alongside a flag that picks a command's own format
+    fn own_format(&self, flag: &str) -> Result<()> {
+        if self.format == OutputFormat::Text {
+            Ok(())
+        } else {
+            Err(PromptBankError::InvalidInput(format!(
+                "{} sets the output format; leave out --format",
+                flag
+            )))
+        }
+    }
+
+    /// Print a value in the selected structured output format
+    fn print_structured<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        print!("{}", self.structured(value)?);
+        Ok(())
+    }
+
+    /// A value in the JSON or YAML output format, ending with a newline
+    fn structured<T: Serialize + ?Sized>(&self, value: &T) -> Result<String> {
+        match self.format {
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+            OutputFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+            OutputFormat::Text => Ok(String::new()),
+        }
+    }
+
     fn copy_to_clipboard(&self, text: &str) -> Result<()> {
         use arboard::Clipboard;
 
@@ -1060,3 +6773,42 @@ impl App {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_are_unique_in_every_command() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn verbosity_flags_follow_the_subcommand() {
+        let cli = Cli::try_parse_from(["promptbank", "list", "-vv"]).unwrap();
+        assert_eq!(cli.verbose, 2);
+        let cli = Cli::try_parse_from(["promptbank", "apply", "greet", "-q"]).unwrap();
+        assert!(cli.quiet);
+    }
+
+    #[test]
+    fn since_accepts_ages_and_dates() {
+        let week_ago = parse_since("1w").unwrap();
+        assert!(Utc::now() - week_ago >= Duration::days(7));
+        assert_eq!(
+            parse_since("2024-03-01").unwrap(),
+            parse_time("2024-03-01", false).unwrap()
+        );
+    }
+
+    #[test]
+    fn since_rejects_an_age_too_far_back() {
+        for value in ["999999999d", "9999999999999w", "9223372036854775807h"] {
+            assert!(matches!(
+                parse_since(value),
+                Err(PromptBankError::InvalidInput(_))
+            ));
+        }
+    }
+}
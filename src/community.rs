@@ -1,10 +1,17 @@
 use serde::{Deserialize, Serialize};
+use tracing::info;
 
+use crate::config::CommunityConfig;
 use crate::error::{PromptBankError, Result};
+use crate::net;
+use crate::profile::{self, Phase};
 use crate::prompt::{Prompt, PromptCategory};
 
 const COMMUNITY_REPO: &str = "ff-vivek/promptbank-community";
-const RAW_BASE_URL: &str = "https://raw.githubusercontent.com/ff-vivek/promptbank-community/main";
+
+/// Registry used unless `community.registry` is configured
+pub const DEFAULT_REGISTRY: &str =
+    "https://raw.githubusercontent.com/ff-vivek/promptbank-community/main";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CommunityIndex {
@@ -36,47 +43,70 @@ pub struct CommunityPrompt {
     pub version: String,
 }
 
-pub struct Community;
+pub struct Community {
+    /// Base URL, without a trailing slash
+    registry: String,
+}
 
 impl Community {
+    pub fn new(config: &CommunityConfig) -> Self {
+        Self {
+            registry: config.registry.trim_end_matches('/').to_string(),
+        }
+    }
+
     /// Fetch the community index
-    pub fn fetch_index() -> Result<CommunityIndex> {
-        let url = format!("{}/index.json", RAW_BASE_URL);
-        let response = ureq::get(&url)
-            .call()
-            .map_err(|e| PromptBankError::Storage(format!("Failed to fetch index: {}", e)))?;
+    pub fn fetch_index(&self) -> Result<CommunityIndex> {
+        let url = format!("{}/index.json", self.registry);
+        info!(%url, "fetching community index");
+        net::spin("Fetching community index", || {
+            profile::measure(Phase::Network, || {
+                let response = net::agent()
+                    .get(&url)
+                    .call()
+                    .map_err(|e| net::error("Fetching the community index", e))?;
 
-        let index: CommunityIndex = response
-            .into_json()
-            .map_err(|e| PromptBankError::Storage(format!("Failed to parse index: {}", e)))?;
+                let index: CommunityIndex = response.into_json().map_err(|e| {
+                    PromptBankError::Storage(format!("Failed to parse index: {}", e))
+                })?;
 
-        Ok(index)
+                Ok(index)
+            })
+        })
     }
 
     /// Fetch a specific prompt from the community
-    pub fn fetch_prompt(path: &str) -> Result<CommunityPrompt> {
-        let url = format!("{}/{}", RAW_BASE_URL, path);
-        let response = ureq::get(&url)
-            .call()
-            .map_err(|e| PromptBankError::Storage(format!("Failed to fetch prompt: {}", e)))?;
+    pub fn fetch_prompt(&self, path: &str) -> Result<CommunityPrompt> {
+        let url = format!("{}/{}", self.registry, path);
+        info!(%url, "fetching community prompt");
+        net::spin("Downloading prompt", || {
+            profile::measure(Phase::Network, || {
+                let response = net::agent()
+                    .get(&url)
+                    .call()
+                    .map_err(|e| net::error("Downloading the prompt", e))?;
 
-        let prompt: CommunityPrompt = response
-            .into_json()
-            .map_err(|e| PromptBankError::Storage(format!("Failed to parse prompt: {}", e)))?;
+                let prompt: CommunityPrompt = response.into_json().map_err(|e| {
+                    PromptBankError::Storage(format!("Failed to parse prompt: {}", e))
+                })?;
 
-        Ok(prompt)
+                Ok(prompt)
+            })
+        })
     }
 
     /// Convert a community prompt to a local prompt
     pub fn to_local_prompt(community_prompt: CommunityPrompt) -> Result<Prompt> {
         let category: PromptCategory = community_prompt.category.parse()?;
-        Ok(Prompt::new(
+        let mut prompt = Prompt::new(
             community_prompt.name,
             category,
             community_prompt.description,
             community_prompt.content,
             community_prompt.tags,
-        ))
+        );
+        prompt.author = Some(community_prompt.author);
+        Ok(prompt)
     }
 
     /// Search community prompts
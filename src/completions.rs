@@ -0,0 +1,102 @@
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::Write;
+
+use crate::cli::Cli;
+use crate::error::Result;
+
+const BIN_NAME: &str = "promptbank";
+
+/// Commands whose first positional argument is a prompt ID or name
+const PROMPT_ID_COMMANDS: &str = "get show apply edit delete copy move";
+
+const ZSH_HELPERS: &str = r#"
+_promptbank_prompts() {
+    local -a candidates
+    candidates=(${(f)"$(promptbank __complete prompts 2>/dev/null)"})
+    compadd -a candidates
+}
+
+_promptbank_tags() {
+    local -a candidates
+    candidates=(${(f)"$(promptbank __complete tags 2>/dev/null)"})
+    _sequence compadd -a candidates
+}
+"#;
+
+const BASH_DYNAMIC: &str = r#"
+_promptbank_dynamic() {
+    local cur prev subcmd i
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+
+    if [[ "$prev" == "--tags" || "$prev" == "-t" ]]; then
+        COMPREPLY=( $(compgen -W "$(promptbank __complete tags 2>/dev/null)" -- "${cur##*,}") )
+        return 0
+    fi
+
+    # Find the subcommand, skipping global options and their values
+    for (( i=1; i < COMP_CWORD; i++ )); do
+        case "${COMP_WORDS[i]}" in
+            --bank|--format) (( i++ )) ;;
+            -*) ;;
+            *) subcmd="${COMP_WORDS[i]}"; break ;;
+        esac
+    done
+
+    if [[ -n "$subcmd" && $i -eq $(( COMP_CWORD - 1 )) && "$cur" != -* ]]; then
+        case " __PROMPT_ID_COMMANDS__ " in
+            *" $subcmd "*)
+                COMPREPLY=( $(compgen -W "$(promptbank __complete prompts 2>/dev/null)" -- "$cur") )
+                return 0
+                ;;
+        esac
+    fi
+
+    _promptbank "$@"
+}
+
+complete -F _promptbank_dynamic -o bashdefault -o default promptbank
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c promptbank -n "__fish_seen_subcommand_from __PROMPT_ID_COMMANDS__" -f -a "(promptbank __complete prompts 2>/dev/null)"
+complete -c promptbank -n "__fish_seen_subcommand_from add" -s t -l tags -x -a "(promptbank __complete tags 2>/dev/null)"
+"#;
+
+/// Write the completion script for a shell to stdout, extended with dynamic
+/// completion of prompt IDs/names and tags where the shell supports it
+pub fn generate(shell: Shell) -> Result<()> {
+    let mut cmd = Cli::command();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, BIN_NAME, &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    let output = match shell {
+        Shell::Bash => format!(
+            "{}{}",
+            script,
+            BASH_DYNAMIC.replace("__PROMPT_ID_COMMANDS__", PROMPT_ID_COMMANDS)
+        ),
+        Shell::Fish => format!(
+            "{}{}",
+            script,
+            FISH_DYNAMIC.replace("__PROMPT_ID_COMMANDS__", PROMPT_ID_COMMANDS)
+        ),
+        Shell::Zsh => {
+            // Keep the #compdef header on the first line
+            let (header, body) = script.split_once('\n').unwrap_or((&script, ""));
+            let body = body
+                .replace(
+                    ":id -- ID or name of the prompt:_default",
+                    ":id -- ID or name of the prompt:_promptbank_prompts",
+                )
+                .replace(":TAGS:_default", ":TAGS:_promptbank_tags");
+            format!("{}\n{}{}", header, ZSH_HELPERS, body)
+        }
+        _ => script.into_owned(),
+    };
+
+    std::io::stdout().write_all(output.as_bytes())?;
+    Ok(())
+}
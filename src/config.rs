@@ -0,0 +1,567 @@
+use colored::Color;
+use dialoguer::Editor;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+use crate::claude::InstallType;
+use crate::community::DEFAULT_REGISTRY;
+use crate::error::{PromptBankError, Result};
+use crate::prompt::closest_names;
+use crate::storage::{StorageFormat, APP_NAME, ORG_NAME};
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Environment variable overriding every other editor choice
+pub const EDITOR_ENV: &str = "PROMPTBANK_EDITOR";
+
+/// Editor used when none is configured, matching dialoguer's fallback
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// GUI editors that return immediately unless told to wait for the file to close
+const WAIT_EDITORS: &[&str] = &["code", "code-insiders", "codium", "cursor", "subl", "zed"];
+
+/// Settings listed by `config list`, with what they control
+pub const KEYS: &[(&str, &str)] = &[
+    ("author", "Name recorded as the author of new prompts"),
+    (
+        "editor",
+        "Command for editing prompts, unless $PROMPTBANK_EDITOR is set",
+    ),
+    ("categories", "Custom categories (see `category add`)"),
+    (
+        "add.default_category",
+        "Category for `add` when --category is omitted",
+    ),
+    (
+        "add.copy",
+        "Copy the new prompt's \"id\" or \"name\" to the clipboard after `add`",
+    ),
+    ("apply.copy", "Copy rendered prompts to the clipboard"),
+    (
+        "apply.separator",
+        "Text between prompts combined with `apply --with`",
+    ),
+    ("list.full", "Show full content in `list`"),
+    ("get.raw", "Only output the content in `get`"),
+    (
+        "theme.accent",
+        "Color for IDs, counts, and highlighted values",
+    ),
+    ("theme.category", "Default color for category labels"),
+    ("theme.tags", "Color for tags"),
+    ("theme.variables", "Color for variables"),
+    ("storage.format", "Encoding of bank files"),
+    (
+        "storage.data_dir",
+        "Directory holding banks, state, and history",
+    ),
+    (
+        "community.registry",
+        "Base URL of the community prompt registry",
+    ),
+    (
+        "quota.max_prompts",
+        "Warn when a bank holds more prompts than this",
+    ),
+    (
+        "quota.max_bank_size",
+        "Warn when a bank is larger than this, e.g. \"5MB\"",
+    ),
+    (
+        "embeddings.url",
+        "Embeddings endpoint used by `search --semantic`",
+    ),
+    ("embeddings.model", "Embeddings model"),
+    (
+        "embeddings.api_key_env",
+        "Environment variable holding the embeddings API key",
+    ),
+];
+
+/// Tables whose entries are keyed by category, e.g. `theme.categories.task`
+const MAP_KEYS: &[&str] = &["theme.categories", "install.categories"];
+
+/// User configuration loaded from config.toml
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Name recorded as the author of prompts added on this machine
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Command for editing prompts, overriding $VISUAL and $EDITOR
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// User-defined categories, usable without the `custom:` prefix
+    pub categories: Vec<String>,
+    pub theme: Theme,
+    pub storage: StorageConfig,
+    pub add: AddConfig,
+    pub list: ListConfig,
+    pub get: GetConfig,
+    pub apply: ApplyConfig,
+    pub install: InstallConfig,
+    pub quota: QuotaConfig,
+    pub community: CommunityConfig,
+    pub embeddings: EmbeddingsConfig,
+}
+
+/// Defaults for `claude install`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallConfig {
+    /// Install type per category when `--as-type` is omitted, e.g. `task = "command"`
+    pub categories: HashMap<String, InstallType>,
+}
+
+impl InstallConfig {
+    /// Install type for prompts in a category, falling back to a skill
+    pub fn install_type(&self, category: &str) -> InstallType {
+        self.categories
+            .get(category)
+            .copied()
+            .unwrap_or(InstallType::Skill)
+    }
+}
+
+/// Soft limits that make `info` and `doctor` warn about bank growth
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    /// Warn when a bank holds more prompts than this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_prompts: Option<usize>,
+    /// Warn when a bank takes more space on disk than this, e.g. "5MB"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bank_size: Option<String>,
+}
+
+impl QuotaConfig {
+    /// Parse `max_bank_size` into bytes
+    pub fn max_bank_bytes(&self) -> Result<Option<u64>> {
+        self.max_bank_size.as_deref().map(parse_size).transpose()
+    }
+}
+
+/// Defaults for `add`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AddConfig {
+    /// Category used when `--category` is omitted
+    #[serde(alias = "category", skip_serializing_if = "Option::is_none")]
+    pub default_category: Option<String>,
+    /// Copy the new prompt's ID or name to the clipboard
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copy: Option<CopyOnAdd>,
+}
+
+/// What `add` copies to the clipboard once the prompt is saved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyOnAdd {
+    Id,
+    Name,
+}
+
+/// Defaults for `list`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListConfig {
+    /// Show full content, as with `--full`
+    pub full: bool,
+}
+
+/// Defaults for `get`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GetConfig {
+    /// Only output the content, as with `--raw`
+    pub raw: bool,
+}
+
+/// Defaults for `apply`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApplyConfig {
+    /// Copy the rendered prompt to the clipboard
+    pub copy: bool,
+    /// Text between prompts combined with `apply --with`
+    pub separator: String,
+}
+
+impl Default for ApplyConfig {
+    fn default() -> Self {
+        Self {
+            copy: false,
+            separator: "\n\n".to_string(),
+        }
+    }
+}
+
+/// Where community prompts come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommunityConfig {
+    /// Base URL serving `index.json` and the prompt files it lists
+    pub registry: String,
+}
+
+impl Default for CommunityConfig {
+    fn default() -> Self {
+        Self {
+            registry: DEFAULT_REGISTRY.to_string(),
+        }
+    }
+}
+
+/// Embeddings API used by `search --semantic`
+///
+/// Any service with an OpenAI-compatible `/embeddings` endpoint works.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub url: String,
+    pub model: String,
+    /// Environment variable holding the API key
+    pub api_key_env: String,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://api.openai.com/v1/embeddings".to_string(),
+            model: "text-embedding-3-small".to_string(),
+            api_key_env: "OPENAI_API_KEY".to_string(),
+        }
+    }
+}
+
+/// Storage settings
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Encoding of bank files: json, zstd, msgpack, markdown, or crdt
+    pub format: StorageFormat,
+    /// Directory holding banks, state, and history instead of the platform default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+}
+
+/// Colors used when printing prompts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Color for IDs, counts, and highlighted values
+    pub accent: String,
+    /// Default color for category labels
+    pub category: String,
+    /// Per-category overrides, keyed by category name
+    pub categories: HashMap<String, String>,
+    pub tags: String,
+    pub variables: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: "cyan".to_string(),
+            category: "yellow".to_string(),
+            categories: HashMap::new(),
+            tags: "blue".to_string(),
+            variables: "magenta".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn accent(&self) -> Color {
+        parse_color(&self.accent, Color::Cyan)
+    }
+
+    /// Color for a category label, honoring per-category overrides
+    pub fn category(&self, category: &str) -> Color {
+        let fallback = parse_color(&self.category, Color::Yellow);
+        match self.categories.get(category) {
+            Some(name) => parse_color(name, fallback),
+            None => fallback,
+        }
+    }
+
+    pub fn tags(&self) -> Color {
+        parse_color(&self.tags, Color::Blue)
+    }
+
+    pub fn variables(&self) -> Color {
+        parse_color(&self.variables, Color::Magenta)
+    }
+}
+
+fn parse_color(name: &str, fallback: Color) -> Color {
+    name.parse().unwrap_or(fallback)
+}
+
+/// Add `--wait` for editors that would otherwise return before the file is saved
+fn with_wait(command: &str) -> String {
+    let words = shell_words::split(command).unwrap_or_default();
+    let program = words
+        .first()
+        .and_then(|program| Path::new(program).file_stem())
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let waits = words.iter().any(|word| word == "--wait" || word == "-w");
+    if WAIT_EDITORS.contains(&program) && !waits {
+        format!("{} --wait", command)
+    } else {
+        command.to_string()
+    }
+}
+
+/// Reject keys that are neither a known setting nor a per-category entry
+fn check_key(key: &str) -> Result<()> {
+    let known = KEYS.iter().any(|(k, _)| *k == key)
+        || MAP_KEYS.iter().any(|map_key| {
+            key == *map_key
+                || key
+                    .strip_prefix(map_key)
+                    .and_then(|rest| rest.strip_prefix('.'))
+                    .is_some_and(|name| !name.is_empty() && !name.contains('.'))
+        });
+    if known {
+        return Ok(());
+    }
+
+    let suggestions = closest_names(key, KEYS.iter().map(|(k, _)| *k));
+    let hint = if suggestions.is_empty() {
+        " (see `promptbank config list`)".to_string()
+    } else {
+        format!(" (did you mean: {}?)", suggestions.join(", "))
+    };
+    Err(PromptBankError::Config(format!(
+        "Unknown setting '{}'{}",
+        key, hint
+    )))
+}
+
+/// Set a dotted key in a TOML tree, creating intermediate tables
+fn insert(root: &mut toml::Value, key: &str, value: toml::Value) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().expect("split yields at least one part");
+    let mut table = root.as_table_mut().expect("config serializes to a table");
+    for part in parts {
+        table = table
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .expect("intermediate settings are tables");
+    }
+    table.insert(last.to_string(), value);
+}
+
+/// Parse a size such as "512KB", "5MB" or "1GB" (plain numbers are bytes)
+fn parse_size(value: &str) -> Result<u64> {
+    let upper = value.trim().to_uppercase();
+    let (number, multiplier) = [("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10), ("B", 1)]
+        .iter()
+        .find_map(|(suffix, multiplier)| {
+            upper
+                .strip_suffix(suffix)
+                .map(|number| (number.trim().to_string(), *multiplier))
+        })
+        .unwrap_or((upper.clone(), 1));
+
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| PromptBankError::Config(format!("Invalid size '{}' (e.g. \"5MB\")", value)))
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults when it does not exist
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        debug!(path = %path.display(), "loading config");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map_err(|e| PromptBankError::Config(format!("{:?}: {}", path, e)))
+    }
+
+    /// Write the config file
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content =
+            toml::to_string_pretty(self).map_err(|e| PromptBankError::Config(e.to_string()))?;
+        info!(path = %path.display(), "writing config");
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Editor for prompt content
+    pub fn editor(&self) -> Editor {
+        let mut editor = Editor::new();
+        if let Some(command) = self.editor_command() {
+            editor.executable(with_wait(&command));
+        }
+        editor
+    }
+
+    /// Open a file in the editor and wait for it to be closed
+    pub fn edit_file(&self, path: &Path) -> Result<()> {
+        let command = self
+            .editor_command()
+            .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+        let command = with_wait(&command);
+        let parts = shell_words::split(&command).map_err(|e| {
+            PromptBankError::InvalidInput(format!("Cannot parse editor '{}': {}", command, e))
+        })?;
+        let (program, args) = parts
+            .split_first()
+            .map_or((DEFAULT_EDITOR, &[][..]), |(program, args)| {
+                (program.as_str(), args)
+            });
+        let status = Command::new(program)
+            .args(args)
+            .arg(path)
+            .status()
+            .map_err(|e| {
+                PromptBankError::InvalidInput(format!("Failed to start '{}': {}", command, e))
+            })?;
+        if !status.success() {
+            return Err(PromptBankError::InvalidInput(format!(
+                "'{}' exited with {}",
+                command, status
+            )));
+        }
+        Ok(())
+    }
+
+    /// Editor command: `$PROMPTBANK_EDITOR`, then the `editor` setting, then
+    /// `$VISUAL` and `$EDITOR`
+    ///
+    /// `None` leaves the choice to the platform default (vi, or notepad on
+    /// Windows).
+    pub fn editor_command(&self) -> Option<String> {
+        let from_env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        from_env(EDITOR_ENV)
+            .or_else(|| self.editor.clone())
+            .or_else(|| from_env("VISUAL"))
+            .or_else(|| from_env("EDITOR"))
+            .filter(|command| !command.trim().is_empty())
+    }
+
+    /// Look up a setting by dotted key, e.g. `apply.copy`
+    ///
+    /// Returns `None` for settings that are unset and have no default.
+    pub fn get(&self, key: &str) -> Result<Option<toml::Value>> {
+        check_key(key)?;
+        let mut value = &self.to_value()?;
+        for part in key.split('.') {
+            match value.get(part) {
+                Some(next) => value = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value.clone()))
+    }
+
+    /// Change a setting by dotted key
+    ///
+    /// The value is read as a TOML literal (`true`, `5`, `["a", "b"]`) when
+    /// that fits the setting, and as a plain string otherwise.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        check_key(key)?;
+        let literal = toml::from_str::<toml::Table>(&format!("value = {}", value))
+            .ok()
+            .and_then(|mut table| table.remove("value"));
+
+        let mut error = None;
+        for candidate in literal
+            .into_iter()
+            .chain([toml::Value::String(value.to_string())])
+        {
+            let mut root = self.to_value()?;
+            insert(&mut root, key, candidate);
+            match root.try_into::<Config>() {
+                Ok(config) => {
+                    *self = config;
+                    return Ok(());
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+
+        let error = error.expect("a string candidate is always tried");
+        Err(PromptBankError::Config(format!(
+            "Invalid value '{}' for {}: {}",
+            value,
+            key,
+            error.message()
+        )))
+    }
+
+    /// Entries of the per-category tables, as `(key, value)` pairs
+    pub fn map_entries(&self) -> Result<Vec<(String, toml::Value)>> {
+        let mut entries = Vec::new();
+        for map_key in MAP_KEYS {
+            if let Some(toml::Value::Table(table)) = self.get(map_key)? {
+                for (name, value) in table {
+                    entries.push((format!("{}.{}", map_key, name), value));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn to_value(&self) -> Result<toml::Value> {
+        toml::Value::try_from(self).map_err(|e| PromptBankError::Config(e.to_string()))
+    }
+
+    /// Get the path to the config file
+    pub fn path() -> Result<PathBuf> {
+        if let Some(proj_dirs) = ProjectDirs::from("com", ORG_NAME, APP_NAME) {
+            Ok(proj_dirs.config_dir().join(CONFIG_FILE))
+        } else {
+            let home = std::env::var("HOME").map_err(|_| {
+                PromptBankError::Config("Could not determine home directory".to_string())
+            })?;
+            Ok(PathBuf::from(home).join(format!(".{}", APP_NAME)).join(CONFIG_FILE))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_take_a_unit() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("512KB").unwrap(), 512 << 10);
+        assert_eq!(parse_size(" 5 mb ").unwrap(), 5 << 20);
+        assert_eq!(parse_size("1GB").unwrap(), 1 << 30);
+        assert!(parse_size("5TB").is_err());
+    }
+
+    #[test]
+    fn sizes_too_large_are_invalid() {
+        assert!(matches!(
+            parse_size("99999999999GB"),
+            Err(PromptBankError::Config(_))
+        ));
+    }
+}
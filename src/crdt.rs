@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::error::{PromptBankError, Result};
+use crate::prompt::{Prompt, PromptBank};
+use crate::storage::{APP_NAME, ORG_NAME};
+
+const REPLICA_ID_FILE: &str = "replica-id";
+const REPLICA_EXTENSION: &str = "json";
+
+/// Last-writer-wins register holding one prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Register {
+    /// `None` is a tombstone left by deleting the prompt
+    pub prompt: Option<Prompt>,
+    /// When the value was written
+    pub stamp: DateTime<Utc>,
+    /// Replica that wrote the value, breaking ties between equal stamps
+    pub replica: String,
+}
+
+impl Register {
+    fn wins_over(&self, other: &Register) -> bool {
+        (self.stamp, &self.replica) > (other.stamp, &other.replica)
+    }
+}
+
+/// Registers keyed by prompt ID
+pub type Registers = BTreeMap<String, Register>;
+
+/// Contents of one replica's file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReplicaFile {
+    registers: Registers,
+}
+
+/// ID of this machine's replica, created on first use
+///
+/// Kept in the cache directory, which sync tools leave alone, so that every
+/// machine gets its own ID even when the data directory is shared.
+pub fn local_replica_id() -> Result<String> {
+    let path = replica_id_path()?;
+    if let Ok(id) = fs::read_to_string(&path) {
+        let id = id.trim();
+        if !id.is_empty() {
+            return Ok(id.to_string());
+        }
+    }
+
+    let id = Uuid::new_v4().to_string()[..8].to_string();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    info!(%id, path = %path.display(), "created replica ID");
+    fs::write(&path, &id)?;
+    Ok(id)
+}
+
+fn replica_id_path() -> Result<PathBuf> {
+    if let Some(proj_dirs) = ProjectDirs::from("com", ORG_NAME, APP_NAME) {
+        return Ok(proj_dirs.cache_dir().join(REPLICA_ID_FILE));
+    }
+    std::env::var("HOME")
+        .map(|home| {
+            PathBuf::from(home)
+                .join(format!(".{}", APP_NAME))
+                .join(REPLICA_ID_FILE)
+        })
+        .map_err(|_| PromptBankError::Storage("Could not determine home directory".to_string()))
+}
+
+/// Merge the registers of every replica file in a bank directory
+///
+/// Extra copies made by sync tools are ordinary replica files, so they merge
+/// like any other.
+pub fn read_registers(dir: &Path) -> Result<Registers> {
+    let mut merged = Registers::new();
+    if !dir.is_dir() {
+        return Ok(merged);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(REPLICA_EXTENSION) {
+            continue;
+        }
+        debug!(path = %path.display(), "merging replica");
+        let file: ReplicaFile = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|e| PromptBankError::Storage(format!("{:?}: {}", path, e)))?;
+        for (id, register) in file.registers {
+            match merged.get(&id) {
+                Some(current) if !register.wins_over(current) => {}
+                _ => {
+                    merged.insert(id, register);
+                }
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Live prompts held by a set of registers
+pub fn to_bank(registers: &Registers) -> PromptBank {
+    let mut bank = PromptBank::new();
+    for register in registers.values() {
+        if let Some(prompt) = &register.prompt {
+            bank.add(prompt.clone());
+        }
+    }
+    bank.prompts.sort_by_key(|p| p.created_at);
+    bank
+}
+
+/// Record a bank's changes since `base` in this replica's file
+///
+/// Only prompts that differ from `base` (the registers as last loaded) get a
+/// new stamp, so edits synced in from other machines meanwhile are kept.
+/// Returns the merged registers after the write.
+pub fn write_replica(
+    dir: &Path,
+    replica: &str,
+    base: &Registers,
+    bank: &PromptBank,
+) -> Result<Registers> {
+    let mut merged = read_registers(dir)?;
+    let now = Utc::now();
+    let stamped = |prompt: Option<Prompt>| Register {
+        prompt,
+        stamp: now,
+        replica: replica.to_string(),
+    };
+
+    for prompt in &bank.prompts {
+        let loaded = base.get(&prompt.id).and_then(|r| r.prompt.as_ref());
+        let changed = match loaded {
+            Some(loaded) => !same_prompt(loaded, prompt)?,
+            None => true,
+        };
+        if changed || !merged.contains_key(&prompt.id) {
+            merged.insert(prompt.id.clone(), stamped(Some(prompt.clone())));
+        }
+    }
+
+    let live: HashSet<&str> = bank.prompts.iter().map(|p| p.id.as_str()).collect();
+    for (id, register) in merged.iter_mut() {
+        let deleted_here =
+            !live.contains(id.as_str()) && base.get(id).is_some_and(|r| r.prompt.is_some());
+        if deleted_here && register.prompt.is_some() {
+            *register = stamped(None);
+        }
+    }
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.{}", replica, REPLICA_EXTENSION));
+    let temp_path = path.with_extension("tmp");
+    info!(path = %path.display(), "writing replica");
+    let file = ReplicaFile {
+        registers: merged.clone(),
+    };
+    fs::write(&temp_path, serde_json::to_vec_pretty(&file)?)?;
+    fs::rename(&temp_path, &path)?;
+    Ok(merged)
+}
+
+fn same_prompt(a: &Prompt, b: &Prompt) -> Result<bool> {
+    Ok(serde_json::to_value(a)? == serde_json::to_value(b)?)
+}
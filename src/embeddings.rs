@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info};
+
+use crate::claude::content_hash;
+use crate::config::EmbeddingsConfig;
+use crate::error::{PromptBankError, Result};
+use crate::net;
+use crate::prompt::{Prompt, PromptBank};
+
+/// Inputs sent per API request
+const BATCH_SIZE: usize = 64;
+
+/// Prompt vectors for one bank, kept between searches
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmbeddingCache {
+    /// Model the vectors came from; vectors of different models don't compare
+    model: String,
+    /// Vectors keyed by prompt ID
+    entries: BTreeMap<String, CachedVector>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedVector {
+    /// Hash of the embedded text, to notice edits
+    hash: String,
+    vector: Vec<f32>,
+}
+
+impl EmbeddingCache {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        // A damaged cache is rebuilt rather than reported
+        Ok(serde_json::from_slice(&fs::read(path)?).unwrap_or_default())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        debug!(path = %path.display(), "writing embedding cache");
+        fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Embed prompts that are new or changed and drop deleted ones
+    ///
+    /// Returns whether anything changed.
+    pub fn refresh(&mut self, bank: &PromptBank, embedder: &Embedder) -> Result<bool> {
+        let mut changed = false;
+        if self.model != embedder.config.model {
+            self.model = embedder.config.model.clone();
+            self.entries.clear();
+            changed = true;
+        }
+
+        let before = self.entries.len();
+        self.entries
+            .retain(|id, _| bank.prompts.iter().any(|p| &p.id == id));
+        changed |= self.entries.len() != before;
+
+        let stale: Vec<(&Prompt, String, String)> = bank
+            .prompts
+            .iter()
+            .map(|p| {
+                let text = embedding_text(p);
+                let hash = content_hash(&text);
+                (p, text, hash)
+            })
+            .filter(|(p, _, hash)| self.entries.get(&p.id).map(|e| &e.hash) != Some(hash))
+            .collect();
+
+        for batch in stale.chunks(BATCH_SIZE) {
+            let texts: Vec<&str> = batch.iter().map(|(_, text, _)| text.as_str()).collect();
+            let vectors = embedder.embed(&texts)?;
+            for ((prompt, _, hash), vector) in batch.iter().zip(vectors) {
+                self.entries.insert(
+                    prompt.id.clone(),
+                    CachedVector {
+                        hash: hash.clone(),
+                        vector,
+                    },
+                );
+            }
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Prompts ordered by cosine similarity to `query`, most similar first
+    pub fn nearest<'a>(&self, bank: &'a PromptBank, query: &[f32]) -> Vec<(&'a Prompt, f32)> {
+        let mut ranked: Vec<(&Prompt, f32)> = bank
+            .prompts
+            .iter()
+            .filter_map(|p| {
+                let entry = self.entries.get(&p.id)?;
+                Some((p, cosine_similarity(&entry.vector, query)))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+}
+
+/// Client for an OpenAI-compatible embeddings endpoint
+pub struct Embedder<'a> {
+    config: &'a EmbeddingsConfig,
+    api_key: String,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl<'a> Embedder<'a> {
+    pub fn new(config: &'a EmbeddingsConfig) -> Result<Self> {
+        let api_key = std::env::var(&config.api_key_env)
+            .ok()
+            .filter(|key| !key.trim().is_empty())
+            .ok_or_else(|| {
+                PromptBankError::Config(format!(
+                    "Semantic search needs an API key in ${}",
+                    config.api_key_env
+                ))
+            })?;
+        Ok(Self { config, api_key })
+    }
+
+    /// One vector per input, in order
+    pub fn embed(&self, inputs: &[&str]) -> Result<Vec<Vec<f32>>> {
+        info!(url = %self.config.url, inputs = inputs.len(), "requesting embeddings");
+        let response = net::spin("Fetching embeddings", || {
+            net::agent()
+                .post(&self.config.url)
+                .set("Authorization", &format!("Bearer {}", self.api_key))
+                .send_json(serde_json::json!({
+                    "model": self.config.model,
+                    "input": inputs,
+                }))
+                .map_err(|e| net::error("Fetching embeddings", e))
+        })?;
+
+        let mut response: EmbeddingResponse = response
+            .into_json()
+            .map_err(|e| PromptBankError::Storage(format!("Failed to parse embeddings: {}", e)))?;
+        if response.data.len() != inputs.len() {
+            return Err(PromptBankError::Storage(format!(
+                "Expected {} embeddings, got {}",
+                inputs.len(),
+                response.data.len()
+            )));
+        }
+        response.data.sort_by_key(|d| d.index);
+        Ok(response.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+/// Text embedded for a prompt: its name, description, tags, and content
+fn embedding_text(prompt: &Prompt) -> String {
+    format!(
+        "{}\n{}\n{}\n\n{}",
+        prompt.name,
+        prompt.description,
+        prompt.tags.join(", "),
+        prompt.content
+    )
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
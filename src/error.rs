@@ -8,20 +8,106 @@ pub enum PromptBankError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
-    #[error("Prompt not found: {0}")]
-    PromptNotFound(String),
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    /// A lookup that failed, with similar names to suggest
+    #[error("Prompt not found: {0}{}", did_you_mean(.1))]
+    PromptNotFound(String, Vec<String>),
+
+    #[error("Pipeline not found: {0}{}", did_you_mean(.1))]
+    PipelineNotFound(String, Vec<String>),
+
+    #[error("'{0}' matches several prompts: {}", .1.join(", "))]
+    AmbiguousId(String, Vec<String>),
 
     #[error("Invalid prompt category: {0}")]
     InvalidCategory(String),
 
+    #[error("Config error: {0}")]
+    Config(String),
+
     #[error("Storage error: {0}")]
     Storage(String),
 
     #[error("Clipboard error: {0}")]
     Clipboard(String),
 
+    #[error("Network error: {0}")]
+    Network(String),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+
+    #[error("{0} lint error(s) found")]
+    LintFailed(usize),
+
+    #[error("{0} status problem(s) found")]
+    StatusFailed(usize),
+}
+
+/// Exit codes, stable across releases so scripts can tell failures apart
+pub mod exit_code {
+    /// Any failure without a more specific code, and lint or status problems
+    pub const FAILURE: i32 = 1;
+    pub const NOT_FOUND: i32 = 2;
+    /// Bad arguments or values, including command-line usage errors
+    pub const INVALID_INPUT: i32 = 3;
+    pub const STORAGE: i32 = 4;
+    pub const NETWORK: i32 = 5;
+    pub const CONFIG: i32 = 6;
+    pub const CLIPBOARD: i32 = 7;
+}
+
+impl PromptBankError {
+    /// Process exit code for this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PromptBankError::PromptNotFound(..) | PromptBankError::PipelineNotFound(..) => {
+                exit_code::NOT_FOUND
+            }
+            PromptBankError::AmbiguousId(..)
+            | PromptBankError::InvalidCategory(_)
+            | PromptBankError::InvalidInput(_) => exit_code::INVALID_INPUT,
+            PromptBankError::Io(_)
+            | PromptBankError::Json(_)
+            | PromptBankError::Yaml(_)
+            | PromptBankError::Storage(_) => exit_code::STORAGE,
+            PromptBankError::Network(_) => exit_code::NETWORK,
+            PromptBankError::Config(_) => exit_code::CONFIG,
+            PromptBankError::Clipboard(_) => exit_code::CLIPBOARD,
+            PromptBankError::LintFailed(_) | PromptBankError::StatusFailed(_) => exit_code::FAILURE,
+        }
+    }
+
+    /// Short machine-readable name for the kind of error, used in JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PromptBankError::Io(_) => "io",
+            PromptBankError::Json(_) => "json",
+            PromptBankError::Yaml(_) => "yaml",
+            PromptBankError::PromptNotFound(..) | PromptBankError::PipelineNotFound(..) => {
+                "not_found"
+            }
+            PromptBankError::AmbiguousId(..) => "ambiguous_id",
+            PromptBankError::InvalidCategory(_) => "invalid_category",
+            PromptBankError::Config(_) => "config",
+            PromptBankError::Storage(_) => "storage",
+            PromptBankError::Clipboard(_) => "clipboard",
+            PromptBankError::Network(_) => "network",
+            PromptBankError::InvalidInput(_) => "invalid_input",
+            PromptBankError::LintFailed(_) => "lint_failed",
+            PromptBankError::StatusFailed(_) => "status_failed",
+        }
+    }
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean: {}?)", suggestions.join(", "))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, PromptBankError>;
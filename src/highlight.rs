@@ -0,0 +1,155 @@
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+const THEME: &str = "base16-ocean.dark";
+
+/// A piece of prompt content: plain text or a fenced code block
+#[derive(Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Code {
+        /// Opening fence line, e.g. "```rust\n"
+        open: &'a str,
+        /// Language named after the opening fence, if any
+        language: &'a str,
+        code: &'a str,
+        /// Closing fence line, missing when the block runs to the end
+        close: Option<&'a str>,
+    },
+}
+
+/// Split content into text and fenced code blocks (``` or ~~~)
+pub fn segments(content: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut offset = 0;
+    let mut lines = content.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let line_start = offset;
+        offset += line.len();
+        let Some((fence, language)) = opening_fence(line) else {
+            continue;
+        };
+
+        if text_start < line_start {
+            segments.push(Segment::Text(&content[text_start..line_start]));
+        }
+        let code_start = offset;
+        let mut code_end = content.len();
+        let mut close = None;
+        for line in lines.by_ref() {
+            let start = offset;
+            offset += line.len();
+            if is_closing_fence(line, fence) {
+                code_end = start;
+                close = Some(line);
+                break;
+            }
+        }
+        segments.push(Segment::Code {
+            open: line,
+            language,
+            code: &content[code_start..code_end],
+            close,
+        });
+        text_start = offset;
+    }
+
+    if text_start < content.len() {
+        segments.push(Segment::Text(&content[text_start..]));
+    }
+    segments
+}
+
+/// Returns the fence and the language of a line that opens a code block
+fn opening_fence(line: &str) -> Option<(&str, &str)> {
+    let trimmed = strip_indent(line)?;
+    let fence_char = trimmed.chars().next()?;
+    if fence_char != '`' && fence_char != '~' {
+        return None;
+    }
+    let fence_len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+    if fence_len < 3 {
+        return None;
+    }
+    let info = trimmed[fence_len..].trim();
+    if fence_char == '`' && info.contains('`') {
+        return None;
+    }
+    let language = info.split_whitespace().next().unwrap_or("");
+    Some((&trimmed[..fence_len], language))
+}
+
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let Some(trimmed) = strip_indent(line) else {
+        return false;
+    };
+    let fence_char = fence.chars().next().unwrap_or('`');
+    let rest = trimmed.trim_start_matches(fence_char);
+    trimmed.len() - rest.len() >= fence.len() && rest.trim().is_empty()
+}
+
+/// Strip up to three spaces of indentation, as Markdown allows for fences
+fn strip_indent(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(' ');
+    (line.len() - trimmed.len() <= 3).then_some(trimmed)
+}
+
+/// Highlights code with syntect, loading syntaxes on first use
+#[derive(Default)]
+pub struct Highlighter {
+    loaded: Option<(SyntaxSet, ThemeSet)>,
+}
+
+impl Highlighter {
+    /// Code with terminal color escapes, or `None` for unknown languages
+    pub fn highlight(&mut self, code: &str, language: &str) -> Option<String> {
+        if language.is_empty() {
+            return None;
+        }
+        let (syntaxes, themes) = self.loaded.get_or_insert_with(|| {
+            (
+                SyntaxSet::load_defaults_newlines(),
+                ThemeSet::load_defaults(),
+            )
+        });
+        let syntax = syntaxes.find_syntax_by_token(language)?;
+        let mut lines = HighlightLines::new(syntax, &themes.themes[THEME]);
+
+        let mut output = String::new();
+        for line in LinesWithEndings::from(code) {
+            let ranges = lines.highlight_line(line, syntaxes).ok()?;
+            output.push_str(&as_24_bit_terminal_escaped(&ranges, false));
+        }
+        output.push_str("\x1b[0m");
+        Some(output)
+    }
+}
+
+/// Content with fenced code blocks highlighted and everything else untouched
+pub fn highlight_code_blocks(content: &str) -> String {
+    let mut highlighter = Highlighter::default();
+    let mut output = String::with_capacity(content.len());
+    for segment in segments(content) {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Code {
+                open,
+                language,
+                code,
+                close,
+            } => {
+                output.push_str(open);
+                match highlighter.highlight(code, language) {
+                    Some(highlighted) => output.push_str(&highlighted),
+                    None => output.push_str(code),
+                }
+                output.push_str(close.unwrap_or(""));
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,94 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use tracing::debug;
+
+use crate::error::Result;
+
+const CSV_HEADER: &str = "prompt_id,prompt_name,timestamp,variables";
+
+/// One application of a prompt, appended to the bank's history log
+///
+/// The values filled in stay on this machine for `history`; values read from
+/// the clipboard or a file are kept as `@clipboard` or the path. Exports keep
+/// only their number, so they can be shared for usage analytics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyEvent {
+    pub prompt_id: String,
+    pub prompt_name: String,
+    pub timestamp: DateTime<Utc>,
+    /// How many variables were filled in
+    pub variables: usize,
+    /// The value given for each variable
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, String>,
+}
+
+/// A `get` of a prompt, appended to the bank's use log so the bank itself
+/// isn't rewritten; loading the bank adds these to its usage counters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UseEvent {
+    pub prompt_id: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Append an event to a log with one JSON object per line
+pub fn append<T: Serialize>(path: &Path, event: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    debug!(path = %path.display(), "appending event");
+    let mut line = serde_json::to_string(event)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read every event in a log, oldest first
+///
+/// A line cut short by an interrupted write is skipped.
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Drop variable values, leaving events safe to share
+pub fn anonymize(events: &mut [ApplyEvent]) {
+    for event in events {
+        event.values.clear();
+    }
+}
+
+/// Render events as CSV with a header row
+pub fn to_csv(events: &[ApplyEvent]) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for event in events {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&event.prompt_id),
+            csv_field(&event.prompt_name),
+            event.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+            event.variables
+        ));
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote, or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{PromptBankError, Result};
+
+/// Largest file or command output that can be inlined into a prompt
+pub const MAX_BYTES: u64 = 256 * 1024;
+
+/// Placeholder name that inlines a file, as in `{{file:notes/spec.md}}`
+pub const FILE_PLACEHOLDER: &str = "file";
+
+/// Placeholder name that inlines a command's output, as in
+/// `{{cmd:git diff --staged}}`
+pub const COMMAND_PLACEHOLDER: &str = "cmd";
+
+/// Shell that runs `{{cmd:...}}` commands, and its flag for a command string
+#[cfg(windows)]
+pub const SHELL: [&str; 2] = ["cmd", "/C"];
+#[cfg(not(windows))]
+pub const SHELL: [&str; 2] = ["sh", "-c"];
+
+/// Prefixes that make a `--var name=@...` value a path to read
+const VALUE_PREFIXES: [&str; 4] = ["@./", "@../", "@/", "@~/"];
+
+/// The path in a `--var` value like `@./spec.md`, if it names a file
+///
+/// Only values that look like paths count, so `@someone` stays literal.
+pub fn value_path(value: &str) -> Option<PathBuf> {
+    let is_path = VALUE_PREFIXES
+        .iter()
+        .any(|prefix| value.starts_with(prefix));
+    if !is_path {
+        return None;
+    }
+    let path = &value[1..];
+    match path.strip_prefix("~/") {
+        Some(rest) => std::env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(rest)),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+/// Contents of a file included by a template with `{{file:path}}`
+///
+/// Only called once the user allowed includes with `--allow-files`. Even
+/// then the path must be relative and stay inside the current directory,
+/// symlinks included, so `{{file:../notes.md}}` or a link to `~/.ssh` is
+/// refused.
+pub fn template_file(path: &str) -> Result<String> {
+    let outside = || {
+        PromptBankError::InvalidInput(format!(
+            "Cannot include '{}': template includes must be relative paths inside the current directory",
+            path
+        ))
+    };
+    let relative = Path::new(path);
+    let escapes = relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if path.is_empty() || escapes {
+        return Err(outside());
+    }
+    // Files that don't exist are left for `read` to report
+    if let (Ok(dir), Ok(target)) = (Path::new(".").canonicalize(), relative.canonicalize()) {
+        if !target.starts_with(dir) {
+            return Err(outside());
+        }
+    }
+    read(relative)
+}
+
+/// Contents of a file to inline, refusing anything over [`MAX_BYTES`]
+///
+/// A final newline is dropped so the file can sit inside a line of text.
+pub fn read(path: &Path) -> Result<String> {
+    let cannot = |reason: String| {
+        PromptBankError::InvalidInput(format!("Cannot include '{}': {}", path.display(), reason))
+    };
+    let metadata = std::fs::metadata(path).map_err(|e| cannot(e.to_string()))?;
+    if !metadata.is_file() {
+        return Err(cannot("not a file".to_string()));
+    }
+    if metadata.len() > MAX_BYTES {
+        return Err(cannot(format!(
+            "{} KB is over the {} KB limit",
+            metadata.len().div_ceil(1024),
+            MAX_BYTES / 1024
+        )));
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| cannot(e.to_string()))?;
+    Ok(trim_final_newline(text))
+}
+
+/// Standard output of a `{{cmd:...}}` command, run in the current directory
+///
+/// A command that fails, prints more than [`MAX_BYTES`], or prints something
+/// other than text is an error. A final newline is dropped, as for files.
+pub fn command_output(command: &str) -> Result<String> {
+    let failed = |reason: String| {
+        PromptBankError::InvalidInput(format!("Command `{}` failed: {}", command, reason))
+    };
+    let output = Command::new(SHELL[0])
+        .args([SHELL[1], command])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| failed(e.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = match stderr.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) => format!("{} ({})", line.trim(), output.status),
+            None => output.status.to_string(),
+        };
+        return Err(failed(reason));
+    }
+    if output.stdout.len() as u64 > MAX_BYTES {
+        return Err(failed(format!(
+            "{} KB of output is over the {} KB limit",
+            (output.stdout.len() as u64).div_ceil(1024),
+            MAX_BYTES / 1024
+        )));
+    }
+    let text = String::from_utf8(output.stdout)
+        .map_err(|_| failed("output is not valid UTF-8 text".to_string()))?;
+    Ok(trim_final_newline(text))
+}
+
+fn trim_final_newline(mut text: String) -> String {
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+    text
+}
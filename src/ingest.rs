@@ -0,0 +1,350 @@
+use globset::{Glob, GlobMatcher};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+use walkdir::WalkDir;
+
+use crate::error::{PromptBankError, Result};
+use crate::markdown::FRONT_MATTER_FENCE;
+use crate::net;
+use crate::profile::{self, Phase};
+use crate::prompt::{Prompt, PromptCategory, TemplateEngine, VariableDef};
+
+/// File extensions picked up when importing a directory
+const PROMPT_EXTENSIONS: [&str; 3] = ["md", "markdown", "txt"];
+
+/// Rules translating file paths into prompt metadata during directory imports
+///
+/// Every rule whose pattern matches a file is applied in order: later rules
+/// override the name, category, and description, and tags accumulate. Name and
+/// description templates may use `{stem}`, `{dir}`, and `{path}`.
+///
+/// `defaults` only fill in what a file's front matter leaves out, while the
+/// matching `rules` override it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ImportMap {
+    pub defaults: MapRule,
+    pub rules: Vec<MapRule>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MapRule {
+    /// Glob matched against the path relative to the imported directory
+    pub pattern: Option<String>,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl ImportMap {
+    /// Load a mapping file (YAML, which also accepts JSON)
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&content)?)
+    }
+}
+
+/// Metadata the mapping gives a single file
+#[derive(Default)]
+struct FileMeta {
+    name: Option<String>,
+    category: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+}
+
+impl FileMeta {
+    /// Apply a rule: its name, category, and description override earlier
+    /// ones, and its tags are added
+    fn apply(&mut self, rule: &MapRule, expand: &dyn Fn(&str) -> String) {
+        if let Some(name) = &rule.name {
+            self.name = Some(expand(name));
+        }
+        if let Some(category) = &rule.category {
+            self.category = Some(expand(category));
+        }
+        if let Some(description) = &rule.description {
+            self.description = Some(expand(description));
+        }
+        for tag in &rule.tags {
+            let tag = expand(tag);
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+}
+
+/// Build prompts from every prompt file below a directory
+///
+/// YAML front matter in a file takes precedence over the mapping's defaults,
+/// and matching rules over the front matter. Categories are read with
+/// `parse_category`. Each prompt is returned with its path relative to `dir`.
+pub fn ingest_dir(
+    dir: &Path,
+    map: &ImportMap,
+    parse_category: &dyn Fn(&str) -> Result<PromptCategory>,
+) -> Result<Vec<(PathBuf, Prompt)>> {
+    let matchers = map
+        .rules
+        .iter()
+        .map(|rule| {
+            let pattern = rule.pattern.as_deref().unwrap_or("**");
+            Glob::new(pattern)
+                .map(|g| g.compile_matcher())
+                .map_err(|e| {
+                    PromptBankError::InvalidInput(format!("Bad pattern '{}': {}", pattern, e))
+                })
+        })
+        .collect::<Result<Vec<GlobMatcher>>>()?;
+
+    let mut prompts = Vec::new();
+    for entry in WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| PromptBankError::Storage(e.to_string()))?;
+        let path = entry.path();
+        let is_prompt_file = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| PROMPT_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if !entry.file_type().is_file() || !is_prompt_file {
+            continue;
+        }
+
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        let (defaults, rules) = resolve_meta(relative, map, &matchers);
+        let (front_matter, content) = split_front_matter(&fs::read_to_string(path)?);
+        let category = rules
+            .category
+            .or(front_matter.category)
+            .or(defaults.category)
+            .unwrap_or_else(|| "task".to_string());
+        let category = parse_category(&category)
+            .map_err(|e| PromptBankError::InvalidInput(format!("{}: {}", relative.display(), e)))?;
+        let mut tags = if front_matter.tags.is_empty() {
+            defaults.tags
+        } else {
+            front_matter.tags
+        };
+        for tag in rules.tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        let stem = relative.file_stem().unwrap_or_default().to_string_lossy();
+        let description = rules
+            .description
+            .or(front_matter.description)
+            .or(defaults.description)
+            .unwrap_or_else(|| format!("Imported from {}", relative.display()));
+
+        let mut prompt = Prompt::new(
+            rules
+                .name
+                .or(front_matter.name)
+                .or(defaults.name)
+                .unwrap_or_else(|| stem.into_owned()),
+            category,
+            description,
+            content,
+            tags,
+        );
+        prompt.author = front_matter.author;
+        prompt.variable_defs = front_matter.variable_defs;
+        prompt.set_engine(front_matter.engine);
+        prompt.max_tokens = front_matter.max_tokens;
+        prompts.push((relative.to_path_buf(), prompt));
+    }
+
+    Ok(prompts)
+}
+
+/// What the mapping's defaults, and then the rules matching a file, give it
+fn resolve_meta(
+    relative: &Path,
+    map: &ImportMap,
+    matchers: &[GlobMatcher],
+) -> (FileMeta, FileMeta) {
+    let stem = relative
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let dir = relative
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let path = relative
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/");
+    let expand = |template: &str| {
+        template
+            .replace("{stem}", &stem)
+            .replace("{dir}", &dir)
+            .replace("{path}", &path)
+    };
+
+    let mut defaults = FileMeta::default();
+    defaults.apply(&map.defaults, &expand);
+
+    let mut rules = FileMeta::default();
+    let matching = map
+        .rules
+        .iter()
+        .zip(matchers)
+        .filter(|(_, matcher)| matcher.is_match(relative));
+    for (rule, _) in matching {
+        rules.apply(rule, &expand);
+    }
+
+    (defaults, rules)
+}
+
+/// Metadata found in the front matter of a downloaded prompt
+#[derive(Debug, Default)]
+pub struct FrontMatter {
+    /// `name`, else `title`, else the file name in the URL
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub author: Option<String>,
+    /// `variable_defs`, or `variables` when written as a mapping
+    pub variable_defs: BTreeMap<String, VariableDef>,
+    /// `engine: tera` for content written as a Tera template
+    pub engine: TemplateEngine,
+    pub max_tokens: Option<usize>,
+}
+
+/// Download a prompt document, reading its metadata from front matter
+///
+/// GitHub file pages and gists are fetched as raw text. HTML pages are
+/// rejected rather than stored as a prompt.
+pub fn fetch_url(url: &str) -> Result<(FrontMatter, String)> {
+    let raw = raw_url(url);
+    info!(url = %raw, "downloading prompt");
+    let text = net::spin("Downloading prompt", || {
+        profile::measure(Phase::Network, || {
+            let response = net::agent()
+                .get(&raw)
+                .call()
+                .map_err(|e| net::error("Downloading the prompt", e))?;
+            if response.content_type() == "text/html" {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "{} is an HTML page; link to the raw file instead",
+                    url
+                )));
+            }
+            response
+                .into_string()
+                .map_err(|e| PromptBankError::Network(format!("Failed to read prompt: {}", e)))
+        })
+    })?;
+
+    let (mut meta, content) = split_front_matter(&text);
+    if meta.name.is_none() {
+        meta.name = url_stem(&raw);
+    }
+    Ok((meta, content))
+}
+
+/// The raw-file URL behind GitHub file pages and gists
+fn raw_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://github.com/") {
+        let parts: Vec<&str> = rest.splitn(4, '/').collect();
+        if let [owner, repo, "blob", path] = parts.as_slice() {
+            return format!(
+                "https://raw.githubusercontent.com/{}/{}/{}",
+                owner, repo, path
+            );
+        }
+    }
+    if let Some(rest) = url.strip_prefix("https://gist.github.com/") {
+        if rest.trim_end_matches('/').split('/').count() == 2 {
+            return format!(
+                "https://gist.githubusercontent.com/{}/raw",
+                rest.trim_end_matches('/')
+            );
+        }
+    }
+    url.to_string()
+}
+
+/// File name of a URL without its extension, if it looks like a name
+fn url_stem(url: &str) -> Option<String> {
+    let url = url.split(['?', '#']).next()?;
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let (_host, path) = without_scheme.split_once('/')?;
+    let file_name = path.trim_end_matches('/').rsplit('/').next()?;
+    let stem = Path::new(file_name).file_stem()?.to_str()?;
+    (stem != "raw").then(|| stem.to_string())
+}
+
+/// Separate optional YAML front matter from the content of a document
+///
+/// Documents whose leading block isn't a YAML mapping are kept whole, since a
+/// `---` line can just as well be a Markdown rule.
+pub fn split_front_matter(text: &str) -> (FrontMatter, String) {
+    let text = text.replace("\r\n", "\n");
+    let parsed = text.strip_prefix(FRONT_MATTER_FENCE).and_then(|rest| {
+        let end = rest.find(&format!("\n{}", FRONT_MATTER_FENCE))?;
+        let value: serde_yaml::Value = serde_yaml::from_str(&rest[..end + 1]).ok()?;
+        let mapping = value.as_mapping()?.clone();
+        let content = &rest[end + 1 + FRONT_MATTER_FENCE.len()..];
+        Some((mapping, content.trim_start_matches('\n').to_string()))
+    });
+    let Some((mapping, content)) = parsed else {
+        return (FrontMatter::default(), text);
+    };
+
+    let field = |key: &str| {
+        mapping
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    // Tags are written both as a list and as a comma-separated string
+    let tags = match mapping.get("tags") {
+        Some(serde_yaml::Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|t| t.as_str())
+            .map(|t| t.trim().to_string())
+            .collect(),
+        Some(serde_yaml::Value::String(list)) => {
+            list.split(',').map(|t| t.trim().to_string()).collect()
+        }
+        _ => Vec::new(),
+    };
+    // A list of bare `variables` names carries nothing worth keeping
+    let variable_defs = ["variable_defs", "variables"]
+        .iter()
+        .filter_map(|key| mapping.get(*key))
+        .find(|v| v.is_mapping())
+        .and_then(|v| serde_yaml::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    let meta = FrontMatter {
+        name: field("name").or_else(|| field("title")),
+        description: field("description"),
+        category: field("category"),
+        tags: tags.into_iter().filter(|t| !t.is_empty()).collect(),
+        author: field("author"),
+        variable_defs,
+        engine: field("engine")
+            .and_then(|e| e.parse().ok())
+            .unwrap_or_default(),
+        max_tokens: mapping
+            .get("max_tokens")
+            .and_then(|n| n.as_u64())
+            .map(|n| n as usize),
+    };
+    (meta, content)
+}
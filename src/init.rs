@@ -0,0 +1,222 @@
+use colored::*;
+use dialoguer::{Confirm, Input, Select};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use crate::claude::{ClaudeIntegration, InstallType};
+use crate::config::Config;
+use crate::error::{PromptBankError, Result};
+use crate::prompt::{Prompt, PromptCategory, BUILTIN_CATEGORIES};
+use crate::storage::{self, Storage};
+
+/// Example prompts offered to new users: name, category, description, tags, content
+const STARTER_PROMPTS: &[(&str, &str, &str, &[&str], &str)] = &[
+    (
+        "code-review",
+        "task",
+        "Review code for bugs, clarity, and style",
+        &["coding", "review"],
+        "Review the following {{language}} code. Point out bugs first, then unclear \
+         naming or structure, then style nits. Explain each suggestion briefly.\n\n\
+         {{code}}",
+    ),
+    (
+        "explain-code",
+        "task",
+        "Explain what a piece of code does",
+        &["coding", "learning"],
+        "Explain what this code does, step by step, for someone who knows \
+         {{language}} but not this codebase.\n\n{{code}}",
+    ),
+    (
+        "commit-message",
+        "template",
+        "Write a commit message for a diff",
+        &["git"],
+        "Write a commit message for the diff below: a short imperative subject line, \
+         a blank line, then a body explaining what changed and why.\n\n{{diff}}",
+    ),
+    (
+        "rubber-duck",
+        "role",
+        "Ask questions until the problem is clear",
+        &["debugging"],
+        "Act as a rubber duck. Don't propose fixes. Ask me one question at a time \
+         about {{problem}} until I've explained my way to the answer.",
+    ),
+    (
+        "concise",
+        "system",
+        "Short, direct answers",
+        &["style"],
+        "Answer directly and concisely. Skip preamble and summaries, and ask before \
+         making assumptions.",
+    ),
+];
+
+/// Walk a new user through the settings that matter on day one, then save them
+pub fn run(bank_name: &str, config: &mut Config) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        return Err(PromptBankError::InvalidInput(
+            "init is interactive and needs a terminal; use `promptbank config set` in scripts"
+                .to_string(),
+        ));
+    }
+
+    println!("\n{}", "Set up promptbank".bold().underline());
+    println!(
+        "Press enter to keep the suggested value. Everything can be changed later\n\
+         with {}.",
+        "promptbank config edit".cyan()
+    );
+
+    // 1. Data directory
+    step(1, "Where should prompts be stored?");
+    let current_dir = Storage::get_data_dir()?;
+    let data_dir: String = Input::new()
+        .with_prompt("Data directory")
+        .default(current_dir.display().to_string())
+        .interact_text()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+    let data_dir = PathBuf::from(data_dir.trim());
+    if data_dir != current_dir {
+        config.storage.data_dir = Some(data_dir.clone());
+        storage::set_data_dir(data_dir);
+        if current_dir.exists() {
+            println!(
+                "  Existing data is not moved; copy it over from {}",
+                current_dir.display()
+            );
+        }
+    }
+
+    // 2. Editor
+    step(2, "Which editor should open prompts?");
+    let editor: String = Input::new()
+        .with_prompt("Editor command (empty for $VISUAL or $EDITOR)")
+        .default(config.editor.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+    config.editor = Some(editor.trim().to_string()).filter(|e| !e.is_empty());
+
+    // 3. Default category
+    step(3, "Which category should new prompts get?");
+    let mut choices: Vec<&str> = vec!["(ask every time)"];
+    choices.extend(BUILTIN_CATEGORIES);
+    choices.extend(config.categories.iter().map(|c| c.as_str()));
+    let current = config
+        .add
+        .default_category
+        .as_deref()
+        .and_then(|c| choices.iter().position(|choice| *choice == c))
+        .unwrap_or(0);
+    let selection = Select::new()
+        .with_prompt("Default category for `add`")
+        .items(&choices)
+        .default(current)
+        .interact()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+    config.add.default_category = (selection > 0).then(|| choices[selection].to_string());
+
+    config.save()?;
+    println!("\n{} Saved {}", "✓".green(), Config::path()?.display());
+
+    // 4. Starter prompts
+    step(4, "Add some example prompts?");
+    for (name, category, description, ..) in STARTER_PROMPTS {
+        println!("  {} [{}] {}", name, category, description.dimmed());
+    }
+    let seeded = if confirm("Add these to your bank?")? {
+        seed(bank_name, config)?
+    } else {
+        Vec::new()
+    };
+
+    // 5. Claude
+    step(5, "Use your prompts in Claude Code?");
+    match ClaudeIntegration::new() {
+        Ok(claude) if !seeded.is_empty() => {
+            if confirm("Install the example prompts as Claude skills and commands?")? {
+                for prompt in &seeded {
+                    let install_type = config.install.install_type(&prompt.category.to_string());
+                    claude.install(prompt, install_type, bank_name)?;
+                    let type_name = match install_type {
+                        InstallType::Skill => "skill",
+                        InstallType::Command => "command",
+                        InstallType::Agent => "agent",
+                    };
+                    println!("  {} {} ({})", "✓".green(), prompt.name, type_name);
+                }
+            }
+        }
+        Ok(_) => {}
+        Err(_) => println!(
+            "  {} No ~/.claude directory found; install Claude Code to use prompts there.",
+            "→".yellow()
+        ),
+    }
+    println!(
+        "\n  {} {}",
+        "Install any prompt with:".dimmed(),
+        "promptbank claude install <name>".cyan()
+    );
+
+    println!("\n{}", "All set!".bold());
+    println!(
+        "Next: {} to see your prompts, {} to write one, or {} for a guided tour.",
+        "promptbank list".cyan(),
+        "promptbank add".cyan(),
+        "promptbank tutorial".cyan()
+    );
+    Ok(())
+}
+
+/// Add the starter prompts whose names are still free, returning them
+fn seed(bank_name: &str, config: &Config) -> Result<Vec<Prompt>> {
+    let storage = Storage::open(bank_name, config.storage.format)?;
+    let mut bank = storage.load()?;
+
+    let mut added = Vec::new();
+    for (name, category, description, tags, content) in STARTER_PROMPTS {
+        if bank.get(name).is_some() {
+            println!("  {} '{}' already exists, skipping", "→".yellow(), name);
+            continue;
+        }
+        let mut prompt = Prompt::new(
+            name.to_string(),
+            category.parse::<PromptCategory>()?,
+            description.to_string(),
+            content.to_string(),
+            tags.iter().map(|t| t.to_string()).collect(),
+        );
+        prompt.author = config.author.clone();
+        added.push(prompt.clone());
+        bank.add(prompt);
+    }
+    storage.save(&bank)?;
+
+    println!(
+        "\n{} Added {} prompt(s) to bank '{}'",
+        "✓".green(),
+        added.len(),
+        bank_name
+    );
+    Ok(added)
+}
+
+fn step(number: usize, title: &str) {
+    println!(
+        "\n{} {}",
+        format!("Step {}:", number).bold().cyan(),
+        title.bold()
+    );
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    Confirm::new()
+        .with_prompt(question)
+        .default(true)
+        .interact()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))
+}
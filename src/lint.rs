@@ -0,0 +1,360 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::prompt::{Prompt, PromptBank, TemplateEngine, EACH_CLOSE, EACH_OPEN};
+use crate::template;
+
+/// Lines longer than this many characters are probably pasted by mistake
+pub const LONG_LINE: usize = 500;
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+/// Checks run by `promptbank lint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    EmptyContent,
+    MissingDescription,
+    DuplicateName,
+    DuplicateAlias,
+    UnclosedVariable,
+    InvalidVariable,
+    SimilarVariable,
+    InvalidLoop,
+    InvalidTemplate,
+    LongLine,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 10] = [
+        Rule::EmptyContent,
+        Rule::MissingDescription,
+        Rule::DuplicateName,
+        Rule::DuplicateAlias,
+        Rule::UnclosedVariable,
+        Rule::InvalidVariable,
+        Rule::SimilarVariable,
+        Rule::InvalidLoop,
+        Rule::InvalidTemplate,
+        Rule::LongLine,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::EmptyContent => "empty-content",
+            Rule::MissingDescription => "missing-description",
+            Rule::DuplicateName => "duplicate-name",
+            Rule::DuplicateAlias => "duplicate-alias",
+            Rule::UnclosedVariable => "unclosed-variable",
+            Rule::InvalidVariable => "invalid-variable",
+            Rule::SimilarVariable => "similar-variable",
+            Rule::InvalidLoop => "invalid-loop",
+            Rule::InvalidTemplate => "invalid-template",
+            Rule::LongLine => "long-line",
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        match self {
+            Rule::MissingDescription
+            | Rule::InvalidVariable
+            | Rule::SimilarVariable
+            | Rule::LongLine => Level::Warning,
+            _ => Level::Error,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::EmptyContent => "Prompt content is empty",
+            Rule::MissingDescription => "Prompt has no description",
+            Rule::DuplicateName => "Another prompt has the same name",
+            Rule::DuplicateAlias => "Alias is already used by another prompt",
+            Rule::UnclosedVariable => "A {{ variable is never closed with }}",
+            Rule::InvalidVariable => "Variable name is empty or contains whitespace",
+            Rule::SimilarVariable => "Two variables differ only by case or whitespace",
+            Rule::InvalidLoop => "A {{#each}} block is unclosed, unopened, or nested",
+            Rule::InvalidTemplate => "Tera template does not parse",
+            Rule::LongLine => "Line is unusually long",
+        }
+    }
+}
+
+/// Part of a prompt a finding points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase", tag = "field", content = "line")]
+pub enum Span {
+    Name,
+    Description,
+    Aliases,
+    /// A 1-based line of the content
+    Content(usize),
+}
+
+/// A single problem found in a prompt
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub rule: Rule,
+    pub level: Level,
+    pub prompt_id: String,
+    pub prompt_name: String,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Finding {
+    fn new(rule: Rule, prompt: &Prompt, span: Span, message: String) -> Self {
+        Self {
+            rule,
+            level: rule.level(),
+            prompt_id: prompt.id.clone(),
+            prompt_name: prompt.name.clone(),
+            message,
+            span,
+        }
+    }
+}
+
+/// Run every lint rule over a bank
+pub fn lint_bank(bank: &PromptBank) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for prompt in &bank.prompts {
+        *names.entry(prompt.name.as_str()).or_default() += 1;
+    }
+
+    for prompt in &bank.prompts {
+        if prompt.content.trim().is_empty() {
+            findings.push(Finding::new(
+                Rule::EmptyContent,
+                prompt,
+                Span::Content(1),
+                format!("'{}' has no content", prompt.name),
+            ));
+        }
+
+        if prompt.description.trim().is_empty() {
+            findings.push(Finding::new(
+                Rule::MissingDescription,
+                prompt,
+                Span::Description,
+                format!("'{}' has no description", prompt.name),
+            ));
+        }
+
+        if names[prompt.name.as_str()] > 1 {
+            findings.push(Finding::new(
+                Rule::DuplicateName,
+                prompt,
+                Span::Name,
+                format!("Name '{}' is used by more than one prompt", prompt.name),
+            ));
+        }
+
+        for alias in &prompt.aliases {
+            let clash = bank.prompts.iter().find(|other| {
+                other.id != prompt.id
+                    && (other.name == *alias || other.aliases.iter().any(|a| a == alias))
+            });
+            if let Some(other) = clash {
+                findings.push(Finding::new(
+                    Rule::DuplicateAlias,
+                    prompt,
+                    Span::Aliases,
+                    format!("Alias '{}' is also used by '{}'", alias, other.name),
+                ));
+            }
+        }
+
+        findings.extend(lint_template(prompt));
+    }
+
+    findings
+}
+
+/// Check a prompt's content for template mistakes, as run by `promptbank validate`
+pub fn lint_template(prompt: &Prompt) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    match prompt.engine {
+        TemplateEngine::Simple => {
+            lint_variables(prompt, &mut findings);
+            lint_similar_variables(prompt, &mut findings);
+            lint_loops(prompt, &mut findings);
+        }
+        TemplateEngine::Tera => lint_tera(prompt, &mut findings),
+    }
+
+    for (index, line) in prompt.content.lines().enumerate() {
+        let length = line.chars().count();
+        if length > LONG_LINE {
+            findings.push(Finding::new(
+                Rule::LongLine,
+                prompt,
+                Span::Content(index + 1),
+                format!("Line of {} characters in '{}'", length, prompt.name),
+            ));
+        }
+    }
+
+    findings
+}
+
+fn lint_variables(prompt: &Prompt, findings: &mut Vec<Finding>) {
+    for (index, line) in prompt.content.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                findings.push(Finding::new(
+                    Rule::UnclosedVariable,
+                    prompt,
+                    Span::Content(index + 1),
+                    format!("Unclosed variable in '{}'", prompt.name),
+                ));
+                break;
+            };
+
+            // A default after the colon may contain anything
+            let name = variable_name(&after[..end]);
+            if name.trim().is_empty() || name.chars().any(char::is_whitespace) {
+                findings.push(Finding::new(
+                    Rule::InvalidVariable,
+                    prompt,
+                    Span::Content(index + 1),
+                    format!(
+                        "Invalid variable name '{{{{{}}}}}' in '{}'",
+                        name, prompt.name
+                    ),
+                ));
+            }
+            rest = &after[end + 2..];
+        }
+    }
+}
+
+/// Tera has its own syntax for variables, so only check that it parses
+fn lint_tera(prompt: &Prompt, findings: &mut Vec<Finding>) {
+    if let Some((line, message)) = template::syntax_error(&prompt.content) {
+        findings.push(Finding::new(
+            Rule::InvalidTemplate,
+            prompt,
+            Span::Content(line),
+            format!("Invalid Tera template in '{}': {}", prompt.name, message),
+        ));
+    }
+}
+
+/// Flag `{{#each}}` blocks that are never closed, closed without being
+/// opened, or opened inside another, since loops don't nest
+fn lint_loops(prompt: &Prompt, findings: &mut Vec<Finding>) {
+    let mut open: Option<usize> = None;
+    for (index, line) in prompt.content.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            let tag = &after[..end];
+            let problem = if tag.starts_with(EACH_OPEN) {
+                let outer = open.replace(index + 1);
+                outer.map(|outer| format!("Loop inside the loop from line {}", outer))
+            } else if tag == EACH_CLOSE && open.take().is_none() {
+                Some("{{/each}} without a {{#each}} before it".to_string())
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                findings.push(Finding::new(
+                    Rule::InvalidLoop,
+                    prompt,
+                    Span::Content(index + 1),
+                    format!("{} in '{}'", problem, prompt.name),
+                ));
+            }
+            rest = &after[end + 2..];
+        }
+    }
+    if let Some(line) = open {
+        findings.push(Finding::new(
+            Rule::InvalidLoop,
+            prompt,
+            Span::Content(line),
+            format!(
+                "{{{{#each}}}} is never closed with {{{{/each}}}} in '{}'",
+                prompt.name
+            ),
+        ));
+    }
+}
+
+/// The name part of a `{{name:default}}` placeholder, or the list looped
+/// over by `{{#each list}}`
+fn variable_name(placeholder: &str) -> &str {
+    if let Some(list) = placeholder.strip_prefix(EACH_OPEN) {
+        return list.trim();
+    }
+    match placeholder.split_once(':') {
+        Some((name, _)) if !name.chars().any(char::is_whitespace) => name,
+        _ => placeholder,
+    }
+}
+
+/// Flag variables that are probably one variable spelled two ways, e.g.
+/// `{{name}}` and `{{Name}}`, since each is filled in separately
+fn lint_similar_variables(prompt: &Prompt, findings: &mut Vec<Finding>) {
+    let normalize = |name: &str| -> String {
+        name.chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    let mut seen: Vec<(&str, String)> = Vec::new();
+    for (index, line) in prompt.content.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            let name = variable_name(&after[..end]);
+            let key = normalize(name);
+            if !key.is_empty() {
+                match seen.iter().find(|(_, other)| *other == key) {
+                    Some((first, _)) if *first != name => findings.push(Finding::new(
+                        Rule::SimilarVariable,
+                        prompt,
+                        Span::Content(index + 1),
+                        format!(
+                            "'{{{{{}}}}}' and '{{{{{}}}}}' in '{}' differ only by case or whitespace",
+                            first, name, prompt.name
+                        ),
+                    )),
+                    Some(_) => {}
+                    None => seen.push((name, key)),
+                }
+            }
+            rest = &after[end + 2..];
+        }
+    }
+}
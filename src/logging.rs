@@ -0,0 +1,65 @@
+use std::fs::{self, OpenOptions};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt;
+use tracing_subscriber::prelude::*;
+
+use crate::error::Result;
+use crate::storage::Storage;
+
+const LOG_FILE: &str = "promptbank.log";
+
+/// Log level chosen by `-v`/`--quiet`; warnings and errors by default
+pub fn level(verbose: u8, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::ERROR;
+    }
+    match verbose {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Send log events to stderr, and with `to_file` also to a log in the data dir
+///
+/// The file always gets at least debug detail, so it is useful after the fact
+/// even when the failing run wasn't verbose.
+pub fn init(level: LevelFilter, to_file: bool) -> Result<()> {
+    let stderr = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .without_time()
+        .with_target(false)
+        .with_filter(level);
+
+    let file = if to_file {
+        let path = log_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Some(
+            fmt::layer()
+                .with_writer(Mutex::new(file))
+                .with_ansi(false)
+                .with_filter(level.max(LevelFilter::DEBUG)),
+        )
+    } else {
+        None
+    };
+
+    tracing_subscriber::registry()
+        .with(stderr)
+        .with(file)
+        .init();
+    Ok(())
+}
+
+/// Where `--log-file` appends log events
+pub fn log_path() -> Result<PathBuf> {
+    Ok(Storage::get_data_dir()?.join(LOG_FILE))
+}
@@ -1,22 +1,96 @@
+mod builtins;
 mod claude;
 mod cli;
 mod community;
+mod completions;
+mod config;
+mod crdt;
+mod embeddings;
 mod error;
+mod highlight;
+mod history;
+mod include;
+mod ingest;
+mod init;
+mod lint;
+mod logging;
+mod markdown;
+mod net;
+mod pager;
+mod pipeline;
+mod profile;
 mod prompt;
+mod recommend;
 mod storage;
+mod template;
+mod tui;
+mod tutorial;
 
 use clap::Parser;
-use cli::{App, Cli};
+use cli::{App, Cli, Commands, OutputFormat};
+use config::Config;
+use error::{exit_code, PromptBankError};
 
 fn main() {
-    let cli = Cli::parse();
+    // Usage errors share the invalid-input exit code; help and version exit 0
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        if e.use_stderr() {
+            let _ = e.print();
+            std::process::exit(exit_code::INVALID_INPUT);
+        }
+        e.exit()
+    });
+    let format = cli.format;
 
-    let result = App::new().and_then(|mut app| app.run(cli));
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+    if cli.profile {
+        profile::enable();
+    }
+
+    // The data dir must be known before logging to a file or opening a bank
+    let config = match Config::load() {
+        Ok(config) => config,
+        // The config commands are how a broken config file gets fixed
+        Err(e) if matches!(cli.command, Commands::Config(_)) => {
+            eprintln!("{} {}", "⚠".yellow(), e);
+            eprintln!("  Using the default settings until the file is fixed");
+            Config::default()
+        }
+        Err(e) => fail(&e, format),
+    };
+    if let Some(dir) = &config.storage.data_dir {
+        storage::set_data_dir(dir.clone());
+    }
+    if let Err(e) = logging::init(logging::level(cli.verbose, cli.quiet), cli.log_file) {
+        fail(&e, format);
+    }
+
+    let start = std::time::Instant::now();
+    let result = App::new(&cli.bank, config).and_then(|mut app| app.run(cli));
+    profile::report(start.elapsed());
 
     if let Err(e) = result {
+        fail(&e, format);
+    }
+}
+
+/// Report an error on stderr, as JSON with `--format json`, and exit with its code
+fn fail(e: &PromptBankError, format: OutputFormat) -> ! {
+    if format == OutputFormat::Json {
+        let error = serde_json::json!({
+            "error": {
+                "kind": e.kind(),
+                "message": e.to_string(),
+                "exit_code": e.exit_code(),
+            }
+        });
+        eprintln!("{}", error);
+    } else {
         eprintln!("{}: {}", "Error".red(), e);
-        std::process::exit(1);
     }
+    std::process::exit(e.exit_code())
 }
 
 // Re-export colored trait for main
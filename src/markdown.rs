@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::error::{PromptBankError, Result};
+use crate::pipeline;
+use crate::prompt::{slugify, Prompt, PromptBank};
+
+/// First line of index files that are regenerated on every save
+pub const INDEX_MARKER: &str = "<!-- generated by promptbank index -->";
+
+pub(crate) const FRONT_MATTER_FENCE: &str = "---\n";
+const PROMPT_EXTENSION: &str = "md";
+
+/// Path of a prompt's file relative to the bank directory
+pub fn prompt_path(prompt: &Prompt) -> PathBuf {
+    let category = slugify(&prompt.category.to_string());
+    let mut file_name = slugify(&prompt.id);
+    if file_name.is_empty() {
+        file_name = prompt.id.clone();
+    }
+    PathBuf::from(category).join(format!("{}.{}", file_name, PROMPT_EXTENSION))
+}
+
+/// Render a prompt as markdown with its metadata in YAML front matter
+pub fn render_prompt(prompt: &Prompt) -> Result<String> {
+    let mut value = serde_yaml::to_value(prompt)?;
+    if let Some(mapping) = value.as_mapping_mut() {
+        mapping.remove("content");
+    }
+    let front_matter = serde_yaml::to_string(&value)?;
+    Ok(format!(
+        "{}{}{}{}",
+        FRONT_MATTER_FENCE, front_matter, FRONT_MATTER_FENCE, prompt.content
+    ))
+}
+
+/// 1-based line of a top-level front matter key in a prompt's file
+pub fn front_matter_line(prompt: &Prompt, key: &str) -> Result<Option<usize>> {
+    let prefix = format!("{}:", key);
+    Ok(render_prompt(prompt)?
+        .lines()
+        .position(|line| line.starts_with(&prefix))
+        .map(|index| index + 1))
+}
+
+/// 1-based line on which a prompt's content starts in its file
+pub fn content_line(prompt: &Prompt) -> Result<usize> {
+    let rendered = render_prompt(prompt)?;
+    let front_matter_len = rendered.len() - prompt.content.len();
+    Ok(rendered[..front_matter_len].matches('\n').count() + 1)
+}
+
+/// Parse a prompt file written by [`render_prompt`]
+pub fn parse_prompt(text: &str) -> Result<Prompt> {
+    let text = text.replace("\r\n", "\n");
+    let rest = text
+        .strip_prefix(FRONT_MATTER_FENCE)
+        .ok_or_else(|| PromptBankError::Storage("Missing front matter".to_string()))?;
+    let end = rest
+        .find(&format!("\n{}", FRONT_MATTER_FENCE))
+        .ok_or_else(|| PromptBankError::Storage("Unterminated front matter".to_string()))?;
+
+    let mut value: serde_yaml::Value = serde_yaml::from_str(&rest[..end + 1])?;
+    let content = &rest[end + 1 + FRONT_MATTER_FENCE.len()..];
+    value
+        .as_mapping_mut()
+        .ok_or_else(|| PromptBankError::Storage("Front matter is not a mapping".to_string()))?
+        .insert("content".into(), content.into());
+    Ok(serde_yaml::from_value(value)?)
+}
+
+/// Read every prompt file in a bank directory
+pub fn read_bank(dir: &Path) -> Result<PromptBank> {
+    let mut bank = PromptBank::new();
+    for path in prompt_files(dir)? {
+        let text = fs::read_to_string(&path)?;
+        let prompt = parse_prompt(&text)
+            .map_err(|e| PromptBankError::Storage(format!("{:?}: {}", path, e)))?;
+        bank.add(prompt);
+    }
+    bank.prompts.sort_by_key(|p| p.created_at);
+    bank.pipelines = pipeline::read_file(dir)?;
+    Ok(bank)
+}
+
+/// Write a bank as one file per prompt and refresh generated indexes
+///
+/// Pipelines go in a single [`pipeline::FILE_NAME`] at the top.
+///
+/// Files are only rewritten when their contents change, so a bank kept in
+/// git produces minimal diffs. Prompt files that no longer belong to the
+/// bank are removed.
+pub fn write_bank(dir: &Path, bank: &PromptBank) -> Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let mut expected = HashSet::new();
+    for prompt in &bank.prompts {
+        let path = dir.join(prompt_path(prompt));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_if_changed(&path, &render_prompt(prompt)?)?;
+        expected.insert(path);
+    }
+
+    for path in prompt_files(dir)? {
+        if !expected.contains(&path) {
+            debug!(path = %path.display(), "removing stale prompt file");
+            fs::remove_file(&path)?;
+            if let Some(parent) = path.parent() {
+                // Only succeeds once the category directory is empty
+                let _ = fs::remove_dir(parent);
+            }
+        }
+    }
+
+    pipeline::write_file(dir, &bank.pipelines)?;
+
+    let index = render_index(bank);
+    for path in index_files(dir)? {
+        write_if_changed(&path, &index)?;
+    }
+    Ok(())
+}
+
+/// Remove the prompt files, pipelines, and generated indexes of a bank
+/// directory
+///
+/// Anything else (such as a `.git` directory) is left in place.
+pub fn remove_bank(dir: &Path) -> Result<()> {
+    for path in prompt_files(dir)?.into_iter().chain(index_files(dir)?) {
+        fs::remove_file(&path)?;
+        if let Some(parent) = path.parent() {
+            let _ = fs::remove_dir(parent);
+        }
+    }
+    pipeline::write_file(dir, &[])
+}
+
+/// Render a categorized markdown index linking to every active prompt
+pub fn render_index(bank: &PromptBank) -> String {
+    let mut by_category: BTreeMap<String, Vec<&Prompt>> = BTreeMap::new();
+    for prompt in bank.prompts.iter().filter(|p| !p.archived) {
+        by_category
+            .entry(prompt.category.to_string())
+            .or_default()
+            .push(prompt);
+    }
+
+    let mut index = format!("{}\n# Prompts\n", INDEX_MARKER);
+    for (category, mut prompts) in by_category {
+        prompts.sort_by_key(|p| p.name.to_lowercase());
+        index.push_str(&format!("\n## {}\n\n", category));
+        for prompt in prompts {
+            let link = prompt_path(prompt).to_string_lossy().replace('\\', "/");
+            index.push_str(&format!("- [{}]({})", escape_link_text(&prompt.name), link));
+            if !prompt.description.is_empty() {
+                index.push_str(&format!(" — {}", prompt.description));
+            }
+            index.push('\n');
+        }
+    }
+    index
+}
+
+/// Prompt files one level below the bank directory
+///
+/// Only markdown files with an `id` in their front matter count, so other
+/// files kept alongside, like a category's README, are never read or removed.
+fn prompt_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir)? {
+        let category_dir = entry?.path();
+        let hidden = category_dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with('.'));
+        if hidden || !category_dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&category_dir)? {
+            let path = entry?.path();
+            if path.is_file() && has_prompt_extension(&path) && is_prompt_file(&path)? {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Top-level markdown files that start with [`INDEX_MARKER`]
+fn index_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && has_prompt_extension(&path) {
+            let text = fs::read_to_string(&path)?;
+            if text.starts_with(INDEX_MARKER) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Whether a file starts with front matter holding a top-level `id`, as
+/// written by [`render_prompt`]
+fn is_prompt_file(path: &Path) -> Result<bool> {
+    let text = fs::read_to_string(path)?.replace("\r\n", "\n");
+    let Some(rest) = text.strip_prefix(FRONT_MATTER_FENCE) else {
+        return Ok(false);
+    };
+    Ok(rest
+        .lines()
+        .take_while(|line| *line != FRONT_MATTER_FENCE.trim_end())
+        .any(|line| line.starts_with("id:")))
+}
+
+fn has_prompt_extension(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(PROMPT_EXTENSION)
+}
+
+fn write_if_changed(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).ok().as_deref() != Some(content) {
+        debug!(path = %path.display(), "writing");
+        fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+fn escape_link_text(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
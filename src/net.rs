@@ -0,0 +1,80 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+use crate::error::PromptBankError;
+
+/// How long to wait for a server to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a response may stall before the request is abandoned
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP agent for every request, so a dead server can't hang the CLI
+pub fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build()
+}
+
+/// Run `f` behind a spinner showing `message` and the time elapsed
+///
+/// Drawn on stderr only when it is a terminal, and cleared once `f` returns.
+pub fn spin<T>(message: &str, f: impl FnOnce() -> T) -> T {
+    let spinner = ProgressBar::new_spinner()
+        .with_style(
+            ProgressStyle::with_template("{spinner:.cyan} {msg} {elapsed:.dim}")
+                .expect("spinner template is valid"),
+        )
+        .with_message(message.to_string());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    let result = f();
+    spinner.finish_and_clear();
+    result
+}
+
+/// Progress bar for a download of `len` bytes, or a spinner if the size is unknown
+pub fn download_bar(message: &str, len: Option<u64>) -> ProgressBar {
+    let bar = match len {
+        Some(len) => ProgressBar::new(len).with_style(
+            ProgressStyle::with_template(
+                "{msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} {elapsed:.dim}",
+            )
+            .expect("download template is valid")
+            .progress_chars("=> "),
+        ),
+        None => ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template("{spinner:.cyan} {msg} {bytes} {elapsed:.dim}")
+                .expect("spinner template is valid"),
+        ),
+    };
+    bar.set_message(message.to_string());
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// Describe a failed request, with advice when the server stopped answering
+pub fn error(what: &str, e: ureq::Error) -> PromptBankError {
+    if timed_out(&e) {
+        return PromptBankError::Network(format!(
+            "{} timed out after {}s; check your connection or try again later",
+            what,
+            READ_TIMEOUT.as_secs()
+        ));
+    }
+    PromptBankError::Network(format!("{} failed: {}", what, e))
+}
+
+fn timed_out(e: &ureq::Error) -> bool {
+    let ureq::Error::Transport(transport) = e else {
+        return false;
+    };
+    std::error::Error::source(transport)
+        .and_then(|source| source.downcast_ref::<std::io::Error>())
+        .is_some_and(|io| {
+            matches!(
+                io.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            )
+        })
+}
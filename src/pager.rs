@@ -0,0 +1,84 @@
+use std::io::{IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+
+/// Routes stdout through `$PAGER` until dropped
+pub struct Pager {
+    child: Child,
+    #[cfg(unix)]
+    saved_stdout: i32,
+    #[cfg(unix)]
+    saved_sigpipe: libc::sighandler_t,
+}
+
+/// Start a pager if stdout is a terminal and `lines` won't fit on screen
+pub fn start(lines: usize) -> Option<Pager> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let (_, height) = ratatui::crossterm::terminal::size().ok()?;
+    if lines < height as usize {
+        return None;
+    }
+    spawn()
+}
+
+#[cfg(unix)]
+fn spawn() -> Option<Pager> {
+    use std::os::fd::AsRawFd;
+
+    let command = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    let mut parts = command.split_whitespace();
+    let mut pager = Command::new(parts.next()?);
+    pager.args(parts).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        // Keep colors, and quit right away if it fits after all
+        pager.env("LESS", "FRX");
+    }
+
+    // Decide on colors while stdout is still the terminal
+    if colored::control::SHOULD_COLORIZE.should_colorize() {
+        colored::control::set_override(true);
+    }
+
+    let mut child = pager.spawn().ok()?;
+    let stdin = child.stdin.take()?;
+    let _ = std::io::stdout().flush();
+    // SAFETY: only duplicates file descriptors owned by this process
+    let saved_stdout = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if saved_stdout < 0 || unsafe { libc::dup2(stdin.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        let _ = child.kill();
+        return None;
+    }
+    // Quitting the pager early closes the pipe. Like git, end quietly on the
+    // next write instead of letting `println!` panic on the broken pipe.
+    // SAFETY: restores the default action of a signal Rust ignores
+    let saved_sigpipe = unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
+
+    Some(Pager {
+        child,
+        saved_stdout,
+        saved_sigpipe,
+    })
+}
+
+#[cfg(not(unix))]
+fn spawn() -> Option<Pager> {
+    None
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        // Restoring stdout closes the pipe, which lets the pager see the end
+        #[cfg(unix)]
+        unsafe {
+            libc::dup2(self.saved_stdout, libc::STDOUT_FILENO);
+            libc::close(self.saved_stdout);
+            libc::signal(libc::SIGPIPE, self.saved_sigpipe);
+        }
+        let _ = self.child.wait();
+    }
+}
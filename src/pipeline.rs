@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+use crate::builtins;
+use crate::error::{PromptBankError, Result};
+use crate::prompt::PromptBank;
+
+/// File holding a bank's pipelines in the markdown and CRDT layouts
+pub const FILE_NAME: &str = "pipelines.yaml";
+
+/// Prompts applied one after another, each fed the output of the one before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub steps: Vec<Step>,
+    /// Command each rendered step is piped into; its output is the step's
+    /// output. Without one, a step's output is the rendered prompt itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    /// ID of the prompt to apply
+    pub prompt: String,
+    /// Variable that receives the previous step's output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+}
+
+impl Step {
+    /// A step from `PROMPT` or `PROMPT:VARIABLE`, checked against the bank
+    ///
+    /// A step after the first must pass the previous output into a variable;
+    /// when none is named, the prompt's only variable is used.
+    pub fn parse(bank: &PromptBank, spec: &str, first: bool) -> Result<Step> {
+        let (prompt, variable) = match (bank.resolve(spec)?, spec.rsplit_once(':')) {
+            (Some(prompt), _) => (prompt, None),
+            (None, Some((id, variable))) => (bank.find(id)?, Some(variable.trim())),
+            (None, None) => (bank.find(spec)?, None),
+        };
+        let variables: Vec<&String> = prompt
+            .variables
+            .iter()
+            .filter(|v| !builtins::is_builtin(v))
+            .collect();
+
+        let input = match (first, variable) {
+            (true, None) => None,
+            (true, Some(variable)) => {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "'{}' is the first step, so there is no output to pass into '{}'",
+                    prompt.name, variable
+                )));
+            }
+            (false, Some(variable)) if variables.iter().any(|v| *v == variable) => {
+                Some(variable.to_string())
+            }
+            (false, Some(variable)) => {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "'{}' has no variable '{}'",
+                    prompt.name, variable
+                )));
+            }
+            (false, None) => match variables[..] {
+                [only] => Some(only.clone()),
+                [] => {
+                    return Err(PromptBankError::InvalidInput(format!(
+                        "'{}' has no variable to take the previous step's output",
+                        prompt.name
+                    )));
+                }
+                _ => {
+                    return Err(PromptBankError::InvalidInput(format!(
+                        "'{}' has several variables; name the one for the previous step's output, as in {}:{}",
+                        prompt.name, prompt.name, variables[0]
+                    )));
+                }
+            },
+        };
+        Ok(Step {
+            prompt: prompt.id.clone(),
+            input,
+        })
+    }
+}
+
+/// Pipelines kept next to the prompts of a bank directory
+pub fn read_file(dir: &Path) -> Result<Vec<Pipeline>> {
+    let path = dir.join(FILE_NAME);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    serde_yaml::from_str(&fs::read_to_string(&path)?)
+        .map_err(|e| PromptBankError::Storage(format!("{:?}: {}", path, e)))
+}
+
+/// Write a bank directory's pipelines, removing the file once there are none
+pub fn write_file(dir: &Path, pipelines: &[Pipeline]) -> Result<()> {
+    let path = dir.join(FILE_NAME);
+    if pipelines.is_empty() {
+        if path.exists() {
+            debug!(path = %path.display(), "removing pipelines");
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    let content = serde_yaml::to_string(pipelines)?;
+    if fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+        debug!(path = %path.display(), "writing pipelines");
+        fs::write(&path, content)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use colored::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Mutex<Vec<(Phase, Duration)>> = Mutex::new(Vec::new());
+
+/// Stages of a command reported by `--profile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Load,
+    Search,
+    Render,
+    Network,
+    Save,
+}
+
+impl Phase {
+    const ALL: [Phase; 5] = [
+        Phase::Load,
+        Phase::Search,
+        Phase::Render,
+        Phase::Network,
+        Phase::Save,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Phase::Load => "storage load",
+            Phase::Search => "search",
+            Phase::Render => "render",
+            Phase::Network => "network",
+            Phase::Save => "storage save",
+        }
+    }
+}
+
+/// Start recording timings for this process
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Run `f`, adding its duration to `phase` when profiling is enabled
+pub fn measure<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.push((phase, elapsed));
+    }
+    result
+}
+
+/// Print the per-phase breakdown to stderr
+pub fn report(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(timings) = TIMINGS.lock() else {
+        return;
+    };
+
+    eprintln!("\n{}", "Profile".bold().underline());
+    let mut measured = Duration::ZERO;
+    for phase in Phase::ALL {
+        let (count, time) = timings
+            .iter()
+            .filter(|(p, _)| *p == phase)
+            .fold((0, Duration::ZERO), |(count, sum), (_, d)| {
+                (count + 1, sum + *d)
+            });
+        if count == 0 {
+            continue;
+        }
+        measured += time;
+        eprintln!(
+            "  {:<14} {:>10}  ({} call{})",
+            phase.label(),
+            format_duration(time),
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+    }
+    eprintln!(
+        "  {:<14} {:>10}",
+        "other",
+        format_duration(total.saturating_sub(measured))
+    );
+    eprintln!("  {:<14} {:>10}", "total".bold(), format_duration(total));
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
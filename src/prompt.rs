@@ -1,10 +1,22 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 use uuid::Uuid;
 
+use crate::builtins;
 use crate::error::{PromptBankError, Result};
+use crate::include;
+use crate::pipeline::Pipeline;
+use crate::template;
+
+/// Names of the built-in categories
+pub const BUILTIN_CATEGORIES: [&str; 6] = ["system", "skill", "agent", "role", "task", "template"];
 
 /// Categories of prompts supported by the system
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -52,6 +64,299 @@ impl FromStr for PromptCategory {
     }
 }
 
+/// Whether applying a prompt copies the result to the clipboard
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardMode {
+    /// Follow the global `apply.copy` setting
+    #[default]
+    Default,
+    Always,
+    Never,
+}
+
+impl fmt::Display for ClipboardMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardMode::Default => write!(f, "default"),
+            ClipboardMode::Always => write!(f, "always"),
+            ClipboardMode::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl FromStr for ClipboardMode {
+    type Err = PromptBankError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(ClipboardMode::Default),
+            "always" => Ok(ClipboardMode::Always),
+            "never" => Ok(ClipboardMode::Never),
+            other => Err(PromptBankError::InvalidInput(format!(
+                "Invalid clipboard mode '{}' (use always, never, or default)",
+                other
+            ))),
+        }
+    }
+}
+
+/// How a prompt's content is turned into the text it's applied as
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateEngine {
+    /// `{{name}}` placeholders replaced by their values
+    #[default]
+    Simple,
+    /// Tera templates, with conditionals, loops, and filters
+    Tera,
+}
+
+impl TemplateEngine {
+    fn is_simple(&self) -> bool {
+        *self == TemplateEngine::Simple
+    }
+}
+
+impl fmt::Display for TemplateEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateEngine::Simple => write!(f, "simple"),
+            TemplateEngine::Tera => write!(f, "tera"),
+        }
+    }
+}
+
+impl FromStr for TemplateEngine {
+    type Err = PromptBankError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "simple" => Ok(TemplateEngine::Simple),
+            "tera" => Ok(TemplateEngine::Tera),
+            other => Err(PromptBankError::InvalidInput(format!(
+                "Invalid template engine '{}' (use simple or tera)",
+                other
+            ))),
+        }
+    }
+}
+
+/// How much room a prompt's content takes up in a context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ContentSize {
+    pub chars: usize,
+    pub words: usize,
+    /// Estimated tokens, at roughly four characters each as with the cl100k
+    /// and Claude tokenizers on English text
+    pub tokens: usize,
+}
+
+impl ContentSize {
+    pub fn of(content: &str) -> Self {
+        let chars = content.chars().count();
+        let words = content.split_whitespace().count();
+        // Short words and punctuation-heavy text take at least a token per word
+        let tokens = chars.div_ceil(4).max(words);
+        Self {
+            chars,
+            words,
+            tokens,
+        }
+    }
+}
+
+impl fmt::Display for ContentSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} chars, {} words, ~{} tokens",
+            self.chars, self.words, self.tokens
+        )
+    }
+}
+
+/// Kind of value a variable expects, checked when the prompt is applied
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    #[default]
+    Text,
+    Number,
+    Boolean,
+    /// A calendar date written as YYYY-MM-DD
+    Date,
+    /// Items separated by commas, or one per line
+    List,
+}
+
+impl VariableType {
+    fn is_text(&self) -> bool {
+        *self == VariableType::Text
+    }
+
+    /// Whether a value can be read as this type
+    fn accepts(self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            VariableType::Text | VariableType::List => true,
+            VariableType::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            VariableType::Boolean => matches!(
+                value.to_lowercase().as_str(),
+                "true" | "false" | "yes" | "no"
+            ),
+            VariableType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        }
+    }
+
+    /// What a valid value looks like, for error messages
+    fn expected(self) -> &'static str {
+        match self {
+            VariableType::Text => "text",
+            VariableType::Number => "a number such as 3 or 2.5",
+            VariableType::Boolean => "true, false, yes, or no",
+            VariableType::Date => "a date as YYYY-MM-DD",
+            VariableType::List => "a list",
+        }
+    }
+}
+
+impl fmt::Display for VariableType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableType::Text => write!(f, "text"),
+            VariableType::Number => write!(f, "number"),
+            VariableType::Boolean => write!(f, "boolean"),
+            VariableType::Date => write!(f, "date"),
+            VariableType::List => write!(f, "list"),
+        }
+    }
+}
+
+/// Guidance for whoever fills in one of a prompt's variables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Optional variables without a default are left empty when not given
+    #[serde(default = "yes", skip_serializing_if = "is_true")]
+    pub required: bool,
+    #[serde(rename = "type")]
+    #[serde(default, skip_serializing_if = "VariableType::is_text")]
+    pub kind: VariableType,
+    /// Takes precedence over a `{{name:default}}` in the content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// The only values allowed, offered as a list when applying interactively
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+    /// Regular expression the whole value must match, e.g. `[A-Z]+-\d+`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Filled in with the editor when applying interactively, for code
+    /// snippets and other long text
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub multiline: bool,
+    /// Environment variable read by `apply --env`, instead of the one named
+    /// after the variable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    /// XML tag the value is wrapped in when substituted, e.g. `document`, so
+    /// pasted text is clearly set apart from the instructions around it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<String>,
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+impl Default for VariableDef {
+    fn default() -> Self {
+        Self {
+            description: None,
+            required: true,
+            kind: VariableType::Text,
+            default: None,
+            choices: Vec::new(),
+            pattern: None,
+            multiline: false,
+            env: None,
+            wrap: None,
+        }
+    }
+}
+
+impl VariableDef {
+    /// Check that a value fits the variable's type and choices
+    pub fn check(&self, name: &str, value: &str) -> Result<()> {
+        if value.is_empty() && !self.required {
+            return Ok(());
+        }
+        // Choices and patterns apply to each item of a list
+        if self.kind == VariableType::List {
+            for item in list_items(value) {
+                self.check_item(name, item)?;
+            }
+            return Ok(());
+        }
+        self.check_item(name, value)
+    }
+
+    fn check_item(&self, name: &str, value: &str) -> Result<()> {
+        if !self.choices.is_empty() && !self.choices.iter().any(|c| c == value) {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Variable '{}' must be one of {}, got '{}'",
+                name,
+                self.choices.join(", "),
+                value
+            )));
+        }
+        if !self.kind.accepts(value) {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Variable '{}' expects {}, got '{}'",
+                name,
+                self.kind.expected(),
+                value
+            )));
+        }
+        if let Some(regex) = self.compiled_pattern(name)? {
+            if !regex.is_match(value) {
+                return Err(PromptBankError::InvalidInput(format!(
+                    "Variable '{}' must match {}, got '{}'",
+                    name,
+                    self.pattern.as_deref().unwrap_or_default(),
+                    value
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The pattern, anchored so that it has to match the entire value
+    pub fn compiled_pattern(&self, name: &str) -> Result<Option<Regex>> {
+        let Some(pattern) = &self.pattern else {
+            return Ok(None);
+        };
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(Some)
+            .map_err(|e| {
+                PromptBankError::InvalidInput(format!(
+                    "Variable '{}' has an invalid pattern: {}",
+                    name, e
+                ))
+            })
+    }
+}
+
 /// A single prompt entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Prompt {
@@ -64,6 +369,90 @@ pub struct Prompt {
     pub variables: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Alternative short names, installed as extra Claude commands on request
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Clipboard behavior on apply, overriding the global default
+    #[serde(default)]
+    pub clipboard: ClipboardMode,
+    /// Retired prompts are kept but hidden from everyday listings
+    #[serde(default)]
+    pub archived: bool,
+    /// Project directories this prompt has been installed into
+    #[serde(default)]
+    pub installed_projects: Vec<PathBuf>,
+    /// Who wrote the prompt, kept as-is when the prompt is shared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Export file or URL the prompt came from when someone else wrote it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_from: Option<String>,
+    /// When the prompt was last fetched or applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// How many times the prompt has been fetched or applied
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub use_count: u64,
+    /// Earlier versions, oldest first, recorded whenever the content changes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<PromptRevision>,
+    /// Variable values from the last time the prompt was applied
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_values: BTreeMap<String, String>,
+    /// Descriptions, types, and defaults of variables, keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variable_defs: BTreeMap<String, VariableDef>,
+    /// Template engine the content is written for
+    #[serde(default, skip_serializing_if = "TemplateEngine::is_simple")]
+    pub engine: TemplateEngine,
+    /// Most tokens the rendered prompt should take, as estimated by
+    /// [`ContentSize`]; `apply` warns when a substitution goes over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// Personal data about a prompt, stored apart from the shared bank
+///
+/// Usage counters, history, and last-used variable values live in a sidecar
+/// file so that sharing or exporting a bank doesn't leak them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptState {
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub use_count: u64,
+    pub history: Vec<PromptRevision>,
+    pub last_values: BTreeMap<String, String>,
+}
+
+impl PromptState {
+    fn is_empty(&self) -> bool {
+        self.last_used_at.is_none()
+            && self.use_count == 0
+            && self.history.is_empty()
+            && self.last_values.is_empty()
+    }
+}
+
+/// Personal state of every prompt in a bank, keyed by prompt ID
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BankState {
+    pub prompts: BTreeMap<String, PromptState>,
+}
+
+/// A past version of a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRevision {
+    pub name: String,
+    pub description: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    /// When this version was saved
+    pub updated_at: DateTime<Utc>,
 }
 
 impl Prompt {
@@ -75,7 +464,7 @@ impl Prompt {
         tags: Vec<String>,
     ) -> Self {
         let now = Utc::now();
-        let variables = Self::extract_variables(&content);
+        let variables = Self::extract_variables(&content, TemplateEngine::Simple);
 
         Self {
             id: Uuid::new_v4().to_string()[..8].to_string(),
@@ -87,67 +476,532 @@ impl Prompt {
             variables,
             created_at: now,
             updated_at: now,
+            aliases: Vec::new(),
+            clipboard: ClipboardMode::Default,
+            archived: false,
+            installed_projects: Vec::new(),
+            author: None,
+            imported_from: None,
+            last_used_at: None,
+            use_count: 0,
+            history: Vec::new(),
+            last_values: BTreeMap::new(),
+            variable_defs: BTreeMap::new(),
+            engine: TemplateEngine::Simple,
+            max_tokens: None,
+        }
+    }
+
+    /// Extract variables from content (format: {{variable_name}} or
+    /// {{variable_name:default}})
+    ///
+    /// Built-in variables such as `{{__date}}`, file includes, and commands
+    /// are left out, since nobody has to fill them in. Tera templates list
+    /// every name they read without setting it themselves.
+    fn extract_variables(content: &str, engine: TemplateEngine) -> Vec<String> {
+        if engine == TemplateEngine::Tera {
+            let mut variables = template::variables(content);
+            variables.retain(|v| !builtins::is_builtin(v));
+            return variables;
+        }
+        let mut variables: Vec<String> = Vec::new();
+        for placeholder in placeholders(content) {
+            let name = placeholder.each().unwrap_or(placeholder.name);
+            if builtins::is_builtin(name)
+                || placeholder.directive().is_some()
+                || [EACH_CLOSE, EACH_ITEM].contains(&name)
+            {
+                continue;
+            }
+            if !variables.iter().any(|v| v == name) {
+                variables.push(name.to_string());
+            }
+        }
+        variables
+    }
+
+    /// Default values given in `variable_defs` or in the content with
+    /// `{{name:default}}`, or `| default(value="...")` in Tera templates
+    ///
+    /// If the content gives a variable more than one default, the first one
+    /// wins. Optional variables without a default default to empty.
+    pub fn variable_defaults(&self) -> BTreeMap<String, String> {
+        let mut defaults = match self.engine {
+            TemplateEngine::Simple => content_defaults(&self.content),
+            TemplateEngine::Tera => template::defaults(&self.content),
+        };
+        for (name, def) in &self.variable_defs {
+            if let Some(default) = &def.default {
+                defaults.insert(name.clone(), default.clone());
+            } else if !def.required {
+                defaults.entry(name.clone()).or_default();
+            }
+        }
+        defaults
+    }
+
+    /// Definition of a variable, or the default one if it has none
+    pub fn variable_def(&self, name: &str) -> VariableDef {
+        self.variable_defs.get(name).cloned().unwrap_or_default()
+    }
+
+    /// A copy that also has the variables of `others`, for filling in the
+    /// values of prompts applied together
+    ///
+    /// A variable several of them share keeps its definition and default from
+    /// the first one that has it. Only the variables change; each prompt is
+    /// still rendered on its own.
+    pub fn combined_with(&self, others: &[&Prompt]) -> Prompt {
+        let mut combined = self.clone();
+        for other in others {
+            let defaults = other.variable_defaults();
+            for name in &other.variables {
+                if combined.variables.contains(name) {
+                    continue;
+                }
+                combined.variables.push(name.clone());
+                let mut def = other.variable_def(name);
+                def.default = defaults.get(name).cloned();
+                combined.variable_defs.insert(name.clone(), def);
+            }
         }
+        combined
     }
 
-    /// Extract variables from content (format: {{variable_name}})
-    fn extract_variables(content: &str) -> Vec<String> {
-        let mut variables = Vec::new();
-        let mut i = 0;
-        let chars: Vec<char> = content.chars().collect();
-
-        while i < chars.len() {
-            if i + 1 < chars.len() && chars[i] == '{' && chars[i + 1] == '{' {
-                i += 2;
-                let mut var_name = String::new();
-                while i < chars.len() {
-                    if i + 1 < chars.len() && chars[i] == '}' && chars[i + 1] == '}' {
-                        i += 2;
-                        break;
-                    }
-                    var_name.push(chars[i]);
-                    i += 1;
+    /// Apply variable substitutions to the prompt content
+    ///
+    /// Built-in variables are filled in unless given a value. Variables without
+    /// a value fall back to their default, and are left as written when they
+    /// have none. Escaped braces (`\{{`) come out as `{{`.
+    ///
+    /// `{{file:path}}` is replaced by the file's contents and `{{cmd:command}}`
+    /// by the command's output, but only when `includes` allows it; otherwise
+    /// the prompt can't be rendered, so a prompt from someone else never reads
+    /// files or runs anything unasked.
+    ///
+    /// Tera templates get every value they read, as its variable's type, and
+    /// are rendered by Tera instead.
+    pub fn render(&self, substitutions: &[(String, String)], includes: Includes) -> Result<String> {
+        let defaults = self.variable_defaults();
+        let lookup = |name: &str| {
+            substitutions
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .or_else(|| builtins::value(name))
+                .or_else(|| defaults.get(name).cloned())
+        };
+        if self.engine == TemplateEngine::Tera {
+            // Variables looped over are lists even without a `list` type
+            let lists = template::lists(&self.content);
+            let mut values = BTreeMap::new();
+            for name in template::variables(&self.content) {
+                if let Some(value) = lookup(&name) {
+                    let kind = match self.variable_def(&name).kind {
+                        VariableType::Text if lists.contains(&name) => VariableType::List,
+                        kind => kind,
+                    };
+                    let value = match kind {
+                        VariableType::Text => self.wrapped(&name, value),
+                        _ => value,
+                    };
+                    values.insert(name, template::value(kind, &value));
                 }
-                if !var_name.is_empty() && !variables.contains(&var_name) {
-                    variables.push(var_name);
+            }
+            return template::render(&self.name, &self.content, &values);
+        }
+        self.fill(&self.content, &lookup, includes)
+    }
+
+    /// Replace the placeholders in `content` with what `lookup` finds for
+    /// them, repeating each `{{#each list}}` block once per item
+    ///
+    /// Loops whose list has no value, or that are never closed, are left as
+    /// written.
+    fn fill(
+        &self,
+        content: &str,
+        lookup: &dyn Fn(&str) -> Option<String>,
+        includes: Includes,
+    ) -> Result<String> {
+        let found = placeholders(content);
+        let mut result = String::with_capacity(content.len());
+        let mut copied = 0;
+        let mut index = 0;
+        while let Some(placeholder) = found.get(index) {
+            index += 1;
+            let (value, end) = if let Some(list) = placeholder.each() {
+                let close = found[index..].iter().position(|p| p.name == EACH_CLOSE);
+                let (Some(close), Some(items)) = (close, lookup(list)) else {
+                    continue;
+                };
+                index += close + 1;
+                let close = &found[index - 1];
+                // Tags on lines of their own don't leave blank lines behind
+                let own_line = content[placeholder.end..].starts_with('\n');
+                let body = &content[placeholder.end + usize::from(own_line)..close.start];
+                let mut repeated = String::new();
+                for item in list_items(&items) {
+                    let with_item = |name: &str| match name {
+                        EACH_ITEM => Some(item.to_string()),
+                        _ => lookup(name),
+                    };
+                    repeated.push_str(&self.fill(body, &with_item, includes)?);
                 }
+                let trailing = own_line && content[close.end..].starts_with('\n');
+                (repeated, close.end + usize::from(trailing))
+            } else if let Some(directive) = placeholder.directive() {
+                (self.inline(directive, includes)?, placeholder.end)
+            } else if let Some(value) = lookup(placeholder.name) {
+                (self.wrapped(placeholder.name, value), placeholder.end)
             } else {
-                i += 1;
+                continue;
+            };
+            result.push_str(&unescape(&content[copied..placeholder.start]));
+            result.push_str(&value);
+            copied = end;
+        }
+        result.push_str(&unescape(&content[copied..]));
+        Ok(result)
+    }
+
+    /// A variable's value inside the tag its definition wraps it in, if any
+    ///
+    /// Empty values stay empty rather than becoming an empty element.
+    fn wrapped(&self, name: &str, value: String) -> String {
+        let tag = self.variable_defs.get(name).and_then(|d| d.wrap.as_deref());
+        match tag {
+            Some(tag) if !value.is_empty() => wrap_xml(tag, &value),
+            _ => value,
+        }
+    }
+
+    /// Contents of an included file, wrapped if it asks to be, or output of
+    /// a command, if allowed
+    fn inline(&self, directive: Directive, includes: Includes) -> Result<String> {
+        match directive {
+            Directive::File { path, wrap } if includes.files => {
+                let text = include::template_file(path)?;
+                Ok(match wrap {
+                    Some(tag) if !text.is_empty() => wrap_xml(tag, &text),
+                    _ => text,
+                })
             }
+            Directive::File { path, .. } => Err(PromptBankError::InvalidInput(format!(
+                "'{}' includes `{}`; pass --allow-files to read it",
+                self.name, path
+            ))),
+            Directive::Command(command) if includes.commands => include::command_output(command),
+            Directive::Command(command) => Err(PromptBankError::InvalidInput(format!(
+                "'{}' runs `{}`; pass --allow-exec to run it",
+                self.name, command
+            ))),
         }
+    }
 
-        variables
+    /// Record that the prompt was just fetched or applied
+    pub fn mark_used(&mut self) {
+        self.mark_used_at(Utc::now());
     }
 
-    /// Apply variable substitutions to the prompt content
-    pub fn render(&self, substitutions: &[(String, String)]) -> String {
-        let mut result = self.content.clone();
-        for (key, value) in substitutions {
-            let pattern = format!("{{{{{}}}}}", key);
-            result = result.replace(&pattern, value);
+    /// Record a use at `time`, which may be older than the last one
+    pub fn mark_used_at(&mut self, time: DateTime<Utc>) {
+        self.last_used_at = self.last_used_at.max(Some(time));
+        self.use_count += 1;
+    }
+
+    /// Length of the content in characters, words, and estimated tokens
+    pub fn size(&self) -> ContentSize {
+        ContentSize::of(&self.content)
+    }
+
+    /// When the prompt was last used, or else last edited
+    pub fn last_touched(&self) -> DateTime<Utc> {
+        self.last_used_at.unwrap_or(self.updated_at).max(self.updated_at)
+    }
+
+    /// Reconstruct the prompt as it was at a point in time
+    ///
+    /// Returns `None` if the prompt did not exist yet. Versions older than the
+    /// recorded history resolve to the oldest known version.
+    pub fn as_of(&self, time: DateTime<Utc>) -> Option<Prompt> {
+        if time < self.created_at {
+            return None;
+        }
+
+        let mut version = self.clone();
+        version.history.clear();
+        if time >= self.updated_at {
+            return Some(version);
         }
-        result
+
+        let revision = self
+            .history
+            .iter()
+            .rev()
+            .find(|r| r.updated_at <= time)
+            .or(self.history.first());
+        if let Some(revision) = revision {
+            version.name = revision.name.clone();
+            version.description = revision.description.clone();
+            version.content = revision.content.clone();
+            version.tags = revision.tags.clone();
+            version.variables = Self::extract_variables(&revision.content, self.engine);
+            version.updated_at = revision.updated_at;
+        }
+        Some(version)
+    }
+
+    /// Move usage counters, history, and last values out of the prompt
+    pub fn take_state(&mut self) -> PromptState {
+        PromptState {
+            last_used_at: self.last_used_at.take(),
+            use_count: std::mem::take(&mut self.use_count),
+            history: std::mem::take(&mut self.history),
+            last_values: std::mem::take(&mut self.last_values),
+        }
+    }
+
+    /// A copy under a new name and ID, with fresh timestamps and no usage or
+    /// install history
+    pub fn duplicate(&self, name: String) -> Prompt {
+        let now = Utc::now();
+        let mut copy = Prompt {
+            id: Uuid::new_v4().to_string()[..8].to_string(),
+            name,
+            created_at: now,
+            updated_at: now,
+            installed_projects: Vec::new(),
+            ..self.clone()
+        };
+        copy.take_state();
+        copy
+    }
+
+    /// Put back state taken with [`Prompt::take_state`]
+    pub fn restore_state(&mut self, state: PromptState) {
+        self.last_used_at = state.last_used_at;
+        self.use_count = state.use_count;
+        self.history = state.history;
+        self.last_values = state.last_values;
     }
 
     pub fn update_content(&mut self, content: String) {
+        self.record_revision();
         self.content = content.clone();
-        self.variables = Self::extract_variables(&content);
+        self.variables = Self::extract_variables(&content, self.engine);
+    }
+
+    /// Keep the current version in the history before the prompt is changed
+    pub fn record_revision(&mut self) {
+        self.history.push(PromptRevision {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            content: self.content.clone(),
+            tags: self.tags.clone(),
+            updated_at: self.updated_at,
+        });
         self.updated_at = Utc::now();
     }
+
+    /// Find the variables in the content again, in case it was edited outside
+    /// promptbank or saved by a release that read them differently
+    pub fn refresh_variables(&mut self) {
+        self.variables = Self::extract_variables(&self.content, self.engine);
+    }
+
+    /// Switch template engines, finding the variables again in the new syntax
+    pub fn set_engine(&mut self, engine: TemplateEngine) {
+        self.engine = engine;
+        self.variables = Self::extract_variables(&self.content, engine);
+    }
+}
+
+/// Turn a name into a lowercase, dash-separated identifier
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Opens a block repeated for each item of a list, as in `{{#each files}}`
+pub const EACH_OPEN: &str = "#each ";
+
+/// Closes a `{{#each}}` block
+pub const EACH_CLOSE: &str = "/each";
+
+/// Stands for the current item inside a `{{#each}}` block
+const EACH_ITEM: &str = "this";
+
+/// Items of a list variable's value: one per line, or separated by commas
+/// when the value is a single line
+pub fn list_items(value: &str) -> Vec<&str> {
+    let separator = if value.contains('\n') { '\n' } else { ',' };
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Text inside `<tag>` and `</tag>`, on lines of their own when the text
+/// spans several lines
+pub fn wrap_xml(tag: &str, text: &str) -> String {
+    if text.contains('\n') {
+        format!("<{}>\n{}\n</{}>", tag, text.trim_end_matches('\n'), tag)
+    } else {
+        format!("<{}>{}</{}>", tag, text, tag)
+    }
+}
+
+/// Whether a tag to wrap text in is a plain XML name, like `document`
+pub fn is_xml_name(tag: &str) -> bool {
+    let mut chars = tag.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+}
+
+/// A `{{name}}` or `{{name:default}}` variable in prompt content
+struct Placeholder<'a> {
+    /// Byte offset of the opening `{{`
+    start: usize,
+    /// Byte offset just past the closing `}}`
+    end: usize,
+    name: &'a str,
+    default: Option<&'a str>,
+}
+
+/// Which `{{file:...}}` and `{{cmd:...}}` placeholders a render may fill
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Includes {
+    /// Read files with `{{file:path}}` (`--allow-files`)
+    pub files: bool,
+    /// Run commands with `{{cmd:command}}` (`--allow-exec`)
+    pub commands: bool,
+}
+
+/// A placeholder replaced by something other than a variable's value
+enum Directive<'a> {
+    /// `{{file:path}}`, inlining a file, or `{{file:path|tag}}` to put it
+    /// inside XML tags
+    File {
+        path: &'a str,
+        wrap: Option<&'a str>,
+    },
+    /// `{{cmd:command}}`, inlining a command's output
+    Command(&'a str),
+}
+
+impl<'a> Placeholder<'a> {
+    /// The list a `{{#each list}}` placeholder loops over
+    fn each(&self) -> Option<&'a str> {
+        let list = self.name.strip_prefix(EACH_OPEN)?.trim();
+        (!list.is_empty()).then_some(list)
+    }
+
+    /// What a `{{file:...}}` or `{{cmd:...}}` placeholder inlines
+    fn directive(&self) -> Option<Directive<'a>> {
+        let argument = self.default?.trim();
+        match self.name {
+            include::FILE_PLACEHOLDER => {
+                let (path, wrap) = match argument.rsplit_once('|') {
+                    Some((path, tag)) if is_xml_name(tag.trim()) => (path.trim(), Some(tag.trim())),
+                    _ => (argument, None),
+                };
+                Some(Directive::File { path, wrap })
+            }
+            include::COMMAND_PLACEHOLDER => Some(Directive::Command(argument)),
+            _ => None,
+        }
+    }
+}
+
+/// Find the variables in `content`, in order
+///
+/// The default is everything after the first `:`, so it may itself contain
+/// colons and spaces. A colon only starts a default when the name before it
+/// has no whitespace, so text like `{{ "key": 1 }}` isn't mistaken for one.
+/// A `{{` written as `\{{` is literal text, and an unclosed `{{` ends the scan.
+fn placeholders(content: &str) -> Vec<Placeholder<'_>> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(open) = content[offset..].find("{{") {
+        let start = offset + open;
+        if content[..start].ends_with('\\') {
+            offset = start + 2;
+            continue;
+        }
+        let Some(close) = content[start + 2..].find("}}") else {
+            break;
+        };
+        let inner = &content[start + 2..start + 2 + close];
+        let end = start + 2 + close + 2;
+        let (name, default) = match inner.split_once(':') {
+            Some((name, default)) if !name.chars().any(char::is_whitespace) => {
+                (name, Some(default))
+            }
+            _ => (inner, None),
+        };
+        if !name.is_empty() {
+            found.push(Placeholder {
+                start,
+                end,
+                name,
+                default,
+            });
+        }
+        offset = end;
+    }
+    found
+}
+
+/// Defaults written in the content as `{{name:default}}`, first one winning
+fn content_defaults(content: &str) -> BTreeMap<String, String> {
+    let mut defaults = BTreeMap::new();
+    for placeholder in placeholders(content) {
+        if placeholder.directive().is_some() {
+            continue;
+        }
+        if let Some(default) = placeholder.default {
+            defaults
+                .entry(placeholder.name.to_string())
+                .or_insert_with(|| default.to_string());
+        }
+    }
+    defaults
+}
+
+/// Turn escaped braces (`\{{`) back into the literal `{{` they stand for
+fn unescape(text: &str) -> String {
+    text.replace("\\{{", "{{")
 }
 
+/// Schema version written by this release
+pub const BANK_VERSION: &str = "1.0";
+
 /// The prompt bank containing all prompts
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PromptBank {
     pub prompts: Vec<Prompt>,
     pub version: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipelines: Vec<Pipeline>,
 }
 
 impl PromptBank {
     pub fn new() -> Self {
         Self {
             prompts: Vec::new(),
-            version: "1.0".to_string(),
+            version: BANK_VERSION.to_string(),
+            pipelines: Vec::new(),
         }
     }
 
@@ -155,6 +1009,35 @@ impl PromptBank {
         self.prompts.push(prompt);
     }
 
+    /// Split into a shareable copy of the bank and its personal state
+    pub fn split_state(&self) -> (PromptBank, BankState) {
+        let mut shared = self.clone();
+        let mut state = BankState::default();
+        for prompt in &mut shared.prompts {
+            let prompt_state = prompt.take_state();
+            if !prompt_state.is_empty() {
+                state.prompts.insert(prompt.id.clone(), prompt_state);
+            }
+        }
+        (shared, state)
+    }
+
+    /// Merge personal state back into the prompts it belongs to
+    pub fn restore_state(&mut self, mut state: BankState) {
+        for prompt in &mut self.prompts {
+            if let Some(prompt_state) = state.prompts.remove(&prompt.id) {
+                prompt.restore_state(prompt_state);
+            }
+        }
+    }
+
+    /// Find every prompt's variables again after reading a bank from a file
+    pub fn refresh_variables(&mut self) {
+        for prompt in &mut self.prompts {
+            prompt.refresh_variables();
+        }
+    }
+
     pub fn get(&self, id: &str) -> Option<&Prompt> {
         self.prompts.iter().find(|p| p.id == id || p.name == id)
     }
@@ -163,6 +1046,80 @@ impl PromptBank {
         self.prompts.iter_mut().find(|p| p.id == id || p.name == id)
     }
 
+    /// `base`, or `base` with the first free numeric suffix ("base-2")
+    pub fn unique_name(&self, base: &str) -> String {
+        let mut name = base.to_string();
+        let mut n = 2;
+        while self.get(&name).is_some() {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        name
+    }
+
+    /// Look up a prompt by ID, name, or unique ID prefix
+    ///
+    /// Returns `Ok(None)` when nothing matches and an error listing the
+    /// candidates when a prefix fits several IDs.
+    pub fn resolve(&self, id: &str) -> Result<Option<&Prompt>> {
+        if let Some(prompt) = self.get(id) {
+            return Ok(Some(prompt));
+        }
+        if id.chars().count() < MIN_ID_PREFIX {
+            return Ok(None);
+        }
+        let candidates: Vec<&Prompt> = self
+            .prompts
+            .iter()
+            .filter(|p| p.id.starts_with(id))
+            .collect();
+        match candidates[..] {
+            [] => Ok(None),
+            [prompt] => Ok(Some(prompt)),
+            _ => Err(PromptBankError::AmbiguousId(
+                id.to_string(),
+                candidates
+                    .iter()
+                    .map(|p| format!("{} ({})", p.id, p.name))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Like [`PromptBank::resolve`], failing with name suggestions
+    pub fn find(&self, id: &str) -> Result<&Prompt> {
+        self.resolve(id)?.ok_or_else(|| self.not_found(id))
+    }
+
+    /// Mutable version of [`PromptBank::find`]
+    pub fn find_mut(&mut self, id: &str) -> Result<&mut Prompt> {
+        // By position, as another prompt may be named after the found one's ID
+        let found: *const Prompt = self.find(id)?;
+        let index = self
+            .prompts
+            .iter()
+            .position(|p| std::ptr::eq(p, found))
+            .ok_or_else(|| self.not_found(id))?;
+        Ok(&mut self.prompts[index])
+    }
+
+    /// Error for an ID or name that matches no prompt
+    pub fn not_found(&self, id: &str) -> PromptBankError {
+        let names = self.prompts.iter().map(|p| p.name.as_str());
+        PromptBankError::PromptNotFound(id.to_string(), closest_names(id, names))
+    }
+
+    /// Look up a pipeline by name, failing with name suggestions
+    pub fn pipeline(&self, name: &str) -> Result<&Pipeline> {
+        self.pipelines
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| {
+                let names = self.pipelines.iter().map(|p| p.name.as_str());
+                PromptBankError::PipelineNotFound(name.to_string(), closest_names(name, names))
+            })
+    }
+
     pub fn delete(&mut self, id: &str) -> bool {
         let len_before = self.prompts.len();
         self.prompts.retain(|p| p.id != id && p.name != id);
@@ -173,16 +1130,443 @@ impl PromptBank {
         self.prompts.iter().filter(|p| &p.category == category).collect()
     }
 
+    /// Find prompts matching every term of a query, best matches first
+    ///
+    /// See [`parse_query`] for the syntax. Plain words tolerate typos.
     pub fn search(&self, query: &str) -> Vec<&Prompt> {
-        let query_lower = query.to_lowercase();
-        self.prompts
+        self.search_scored(query)
+            .into_iter()
+            .map(|(prompt, _)| prompt)
+            .collect()
+    }
+
+    /// Like [`PromptBank::search`], with each prompt's relevance score
+    pub fn search_scored(&self, query: &str) -> Vec<(&Prompt, i64)> {
+        let terms = parse_query(query);
+        let mut results: Vec<(&Prompt, i64)> = self
+            .prompts
             .iter()
-            .filter(|p| {
-                p.name.to_lowercase().contains(&query_lower)
-                    || p.description.to_lowercase().contains(&query_lower)
-                    || p.tags.iter().any(|t| t.to_lowercase().contains(&query_lower))
-                    || p.content.to_lowercase().contains(&query_lower)
+            .filter_map(|prompt| {
+                terms
+                    .iter()
+                    .map(|term| term.score(prompt))
+                    .sum::<Option<i64>>()
+                    .map(|score| (prompt, score))
             })
-            .collect()
+            .collect();
+        // Stable, so equally relevant prompts keep their order
+        results.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        results
+    }
+
+    /// The prompt whose name most closely resembles a mistyped one, ranked
+    /// as in the suggestions of [`closest_names`]
+    pub fn closest_match(&self, name: &str) -> Option<&Prompt> {
+        let names = self.prompts.iter().map(|p| p.name.as_str());
+        let closest = closest_names(name, names).into_iter().next()?;
+        self.prompts.iter().find(|p| p.name == closest)
+    }
+}
+
+/// Shortest ID prefix accepted in place of a full ID
+const MIN_ID_PREFIX: usize = 3;
+
+/// Most suggestions offered for a mistyped name
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Names within a few edits of a mistyped one, closest first
+///
+/// Swapping two neighboring letters counts as one edit.
+pub fn closest_names<'a>(name: &str, names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let name = name.to_lowercase();
+    // Allow roughly one typo per three characters
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut close: Vec<(usize, &str)> = names
+        .map(|candidate| {
+            let distance = strsim::damerau_levenshtein(&name, &candidate.to_lowercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    close.sort();
+    close.dedup_by_key(|(_, candidate)| *candidate);
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+// Relevance of the different ways a plain search word can match
+const SCORE_NAME_EXACT: i64 = 1000;
+const SCORE_NAME: i64 = 500;
+const SCORE_TAG: i64 = 300;
+const SCORE_DESCRIPTION: i64 = 200;
+const SCORE_CONTENT: i64 = 100;
+const SCORE_FUZZY: i64 = 50;
+
+/// Score a typo-tolerant match of a lowercase query against text
+///
+/// Words of the text within a small edit distance of the query match, as do
+/// abbreviations of the whole text (`cdrev` for "code-review").
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query_len = query.chars().count();
+    let max_distance = match query_len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+
+    let text = text.to_lowercase();
+    let closest = text
+        .split(|c: char| !c.is_alphanumeric())
+        .chain(std::iter::once(text.as_str()))
+        .filter(|word| !word.is_empty())
+        .map(|word| strsim::damerau_levenshtein(query, word))
+        .min();
+    if let Some(distance) = closest.filter(|d| *d <= max_distance) {
+        return Some(SCORE_FUZZY - distance as i64 * 10);
+    }
+
+    if query_len >= 3 {
+        // An abbreviation must cover most of the text's words to count
+        let matcher = SkimMatcherV2::default();
+        if let Some((_, indices)) = matcher.fuzzy_indices(&text, query) {
+            let words = text.split(|c: char| !c.is_alphanumeric()).count();
+            if indices.len() >= words {
+                return Some(SCORE_FUZZY / 2);
+            }
+        }
+    }
+    None
+}
+
+/// One condition of a search query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTerm {
+    /// Text found in the name, description, tags, or content
+    Text(String),
+    /// A tag equal to the value
+    Tag(String),
+    /// The category, e.g. `skill` or `custom:research`
+    Category(String),
+    Name(String),
+    Description(String),
+    Content(String),
+    /// A variable equal to the value
+    Variable(String),
+}
+
+impl SearchTerm {
+    fn from_filter(key: &str, value: String) -> Option<Self> {
+        let term = match key {
+            "tag" => SearchTerm::Tag(value),
+            "category" | "cat" => SearchTerm::Category(value),
+            "name" => SearchTerm::Name(value),
+            "description" | "desc" => SearchTerm::Description(value),
+            "content" => SearchTerm::Content(value),
+            "var" | "variable" => SearchTerm::Variable(value),
+            _ => return None,
+        };
+        Some(term)
+    }
+
+    /// Relevance of a prompt for this term, or `None` if it doesn't match
+    fn score(&self, prompt: &Prompt) -> Option<i64> {
+        let SearchTerm::Text(text) = self else {
+            return self.matches(prompt).then_some(0);
+        };
+
+        let contains = |field: &str| field.to_lowercase().contains(text.as_str());
+        if prompt.name.to_lowercase() == *text {
+            Some(SCORE_NAME_EXACT)
+        } else if contains(&prompt.name) {
+            Some(SCORE_NAME)
+        } else if prompt.tags.iter().any(|t| contains(t)) {
+            Some(SCORE_TAG)
+        } else if contains(&prompt.description) {
+            Some(SCORE_DESCRIPTION)
+        } else if contains(&prompt.content) {
+            Some(SCORE_CONTENT)
+        } else {
+            std::iter::once(&prompt.name)
+                .chain(&prompt.tags)
+                .chain(std::iter::once(&prompt.description))
+                .filter_map(|field| fuzzy_score(text, field))
+                .max()
+        }
+    }
+
+    fn matches(&self, prompt: &Prompt) -> bool {
+        let contains = |field: &str, value: &str| field.to_lowercase().contains(value);
+        match self {
+            SearchTerm::Text(_) => self.score(prompt).is_some(),
+            SearchTerm::Tag(tag) => prompt.tags.iter().any(|t| t.to_lowercase() == *tag),
+            SearchTerm::Category(category) => {
+                // The query is lowercased, and custom categories may not be
+                let name = prompt.category.to_string().to_lowercase();
+                name == *category || name.strip_prefix("custom:") == Some(category.as_str())
+            }
+            SearchTerm::Name(name) => contains(&prompt.name, name),
+            SearchTerm::Description(text) => contains(&prompt.description, text),
+            SearchTerm::Content(text) => contains(&prompt.content, text),
+            SearchTerm::Variable(var) => prompt.variables.iter().any(|v| v.to_lowercase() == *var),
+        }
+    }
+}
+
+/// Parse a query such as `tag:rust category:skill "error handling"`
+///
+/// Words are matched separately unless quoted, and `field:value` filters
+/// narrow the match to one field. Unknown fields are searched as plain text.
+pub fn parse_query(query: &str) -> Vec<SearchTerm> {
+    let mut terms = Vec::new();
+    for (token, quoted) in tokenize(&query.to_lowercase()) {
+        let filter = (!quoted)
+            .then(|| token.split_once(':'))
+            .flatten()
+            .filter(|(_, value)| !value.is_empty())
+            .and_then(|(key, value)| SearchTerm::from_filter(key, value.to_string()));
+        terms.push(filter.unwrap_or(SearchTerm::Text(token)));
+    }
+    terms
+}
+
+/// Split on whitespace, keeping double-quoted runs together
+///
+/// Returns each token with whether it started with a quote.
+fn tokenize(query: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut started_quoted = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => {
+                if current.is_empty() && !in_quotes {
+                    started_quoted = true;
+                }
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push((std::mem::take(&mut current), started_quoted));
+                }
+                started_quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push((current, started_quoted));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(content: &str) -> Prompt {
+        Prompt::new(
+            "test".to_string(),
+            PromptCategory::Task,
+            String::new(),
+            content.to_string(),
+            Vec::new(),
+        )
+    }
+
+    fn render(content: &str, values: &[(&str, &str)]) -> String {
+        let substitutions: Vec<(String, String)> = values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        prompt(content)
+            .render(&substitutions, Includes::default())
+            .expect("renders")
+    }
+
+    #[test]
+    fn defaults_may_contain_colons() {
+        let content = "Fetch {{url:http://localhost:8080/a b}} now";
+
+        assert_eq!(prompt(content).variables, ["url"]);
+        assert_eq!(render(content, &[]), "Fetch http://localhost:8080/a b now");
+        assert_eq!(render(content, &[("url", "x")]), "Fetch x now");
+    }
+
+    #[test]
+    fn escaped_braces_are_literal() {
+        let content = "Write \\{{name}} for {{name}}";
+
+        assert_eq!(prompt(content).variables, ["name"]);
+        assert_eq!(
+            render(content, &[("name", "Ada")]),
+            "Write {{name}} for Ada"
+        );
+    }
+
+    #[test]
+    fn unclosed_braces_end_the_scan() {
+        let content = "Hi {{name}}, see {{oops";
+
+        assert_eq!(prompt(content).variables, ["name"]);
+        assert_eq!(render(content, &[("name", "Ada")]), "Hi Ada, see {{oops");
+    }
+
+    #[test]
+    fn each_tags_on_own_lines_leave_no_blank_lines() {
+        let content = "Files:\n{{#each files}}\n- {{this}}\n{{/each}}\nDone";
+
+        assert_eq!(prompt(content).variables, ["files"]);
+        assert_eq!(
+            render(content, &[("files", "a.rs, b.rs")]),
+            "Files:\n- a.rs\n- b.rs\nDone"
+        );
+    }
+
+    #[test]
+    fn json_in_braces_is_not_a_default() {
+        let content = "Reply with {{ \"json\": 1 }} for {{name}}";
+
+        assert!(placeholders(content).iter().all(|p| p.default.is_none()));
+        assert!(prompt(content).variable_defaults().is_empty());
+        assert_eq!(
+            render(content, &[("name", "Ada")]),
+            "Reply with {{ \"json\": 1 }} for Ada"
+        );
+    }
+
+    #[test]
+    fn find_mut_returns_the_prompt_an_id_prefix_resolves_to() {
+        let mut named = prompt("named after an ID");
+        named.id = "aaaa0000".to_string();
+        named.name = "bbbb1111".to_string();
+        let mut target = prompt("the prompt with that ID");
+        target.id = "bbbb1111".to_string();
+        let mut bank = PromptBank::new();
+        bank.prompts = vec![named, target];
+
+        assert_eq!(
+            bank.find_mut("bbbb").unwrap().content,
+            "the prompt with that ID"
+        );
+    }
+
+    #[test]
+    fn duplicate_keeps_settings_but_not_usage() {
+        let mut original = prompt("Hi {{ name }}");
+        original.set_engine(TemplateEngine::Tera);
+        original.aliases = vec!["hi".to_string()];
+        original.max_tokens = Some(100);
+        original.use_count = 3;
+        original.installed_projects = vec![PathBuf::from("/repo")];
+        original.update_content("Hello {{ name }}".to_string());
+
+        let copy = original.duplicate("copy".to_string());
+
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.name, "copy");
+        assert_eq!(copy.engine, TemplateEngine::Tera);
+        assert_eq!(copy.aliases, ["hi"]);
+        assert_eq!(copy.max_tokens, Some(100));
+        assert_eq!(copy.use_count, 0);
+        assert!(copy.history.is_empty());
+        assert!(copy.installed_projects.is_empty());
+    }
+
+    #[test]
+    fn typed_variables_reject_values_of_another_type() {
+        let typed = |kind| VariableDef {
+            kind,
+            required: true,
+            ..VariableDef::default()
+        };
+
+        assert!(typed(VariableType::Number).check("n", "2.5").is_ok());
+        assert!(typed(VariableType::Number).check("n", "two").is_err());
+        assert!(typed(VariableType::Number).check("n", "NaN").is_err());
+        assert!(typed(VariableType::Boolean).check("b", "Yes").is_ok());
+        assert!(typed(VariableType::Boolean).check("b", "maybe").is_err());
+        assert!(typed(VariableType::Date).check("d", "2026-02-28").is_ok());
+        assert!(typed(VariableType::Date).check("d", "2026-02-30").is_err());
+        assert!(typed(VariableType::Text).check("t", "anything").is_ok());
+    }
+
+    #[test]
+    fn wrap_tags_are_xml_names() {
+        assert!(is_xml_name("document"));
+        assert!(is_xml_name("_code-1.rs"));
+        assert!(!is_xml_name(""));
+        assert!(!is_xml_name("1st"));
+        assert!(!is_xml_name("a b"));
+        assert!(!is_xml_name("a>"));
+    }
+
+    #[test]
+    fn wrapped_text_puts_several_lines_on_lines_of_their_own() {
+        assert_eq!(wrap_xml("code", "x = 1"), "<code>x = 1</code>");
+        assert_eq!(wrap_xml("code", "a\nb\n"), "<code>\na\nb\n</code>");
+    }
+
+    fn wrapped_prompt(content: &str, engine: TemplateEngine) -> Prompt {
+        let mut prompt = prompt(content);
+        prompt.set_engine(engine);
+        prompt.variable_defs.insert(
+            "code".to_string(),
+            VariableDef {
+                wrap: Some("code".to_string()),
+                ..VariableDef::default()
+            },
+        );
+        prompt
+    }
+
+    #[test]
+    fn wrapped_variables_in_both_engines() {
+        let values = [("code".to_string(), "fn main() {}".to_string())];
+        for (content, engine) in [
+            ("Review {{code}}", TemplateEngine::Simple),
+            ("Review {{ code }}", TemplateEngine::Tera),
+        ] {
+            let prompt = wrapped_prompt(content, engine);
+            assert_eq!(
+                prompt.render(&values, Includes::default()).unwrap(),
+                "Review <code>fn main() {}</code>"
+            );
+        }
+    }
+
+    #[test]
+    fn file_includes_take_a_wrap_tag() {
+        let includes = Includes {
+            files: true,
+            commands: false,
+        };
+        let rendered = prompt("{{file:Cargo.toml|manifest}}")
+            .render(&[], includes)
+            .unwrap();
+
+        assert!(rendered.starts_with("<manifest>\n[package]"));
+        assert!(rendered.ends_with("\n</manifest>"));
+        assert!(prompt("{{file:Cargo.toml|manifest}}")
+            .render(&[], Includes::default())
+            .is_err());
+    }
+
+    #[test]
+    fn category_search_ignores_case() {
+        let mut devops = prompt("Deploy");
+        devops.category = PromptCategory::Custom("DevOps".to_string());
+        let mut bank = PromptBank::new();
+        bank.prompts = vec![devops, prompt("Other")];
+
+        for query in ["category:DevOps", "cat:devops", "category:custom:DEVOPS"] {
+            let found = bank.search(query);
+            assert_eq!(found.len(), 1, "{}", query);
+            assert_eq!(found[0].content, "Deploy");
+        }
     }
 }
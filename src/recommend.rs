@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::error::Result;
+
+pub const RECOMMENDED_FILE: &str = ".promptbank-recommended.json";
+
+/// Prompts suggested for everyone working in a repository
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Recommendations {
+    pub prompts: Vec<String>,
+}
+
+impl Recommendations {
+    /// Path of the recommendations file inside a directory
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(RECOMMENDED_FILE)
+    }
+
+    /// Load recommendations from a directory, if the file exists
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path_in(dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Write recommendations into a directory
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let path = Self::path_in(dir);
+        let content = serde_json::to_string_pretty(self)?;
+        info!(path = %path.display(), "writing recommendations");
+        fs::write(&path, content + "\n")?;
+        Ok(path)
+    }
+}
@@ -1,80 +1,540 @@
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, info};
 
+use crate::crdt::{self, Registers};
+use crate::embeddings::EmbeddingCache;
 use crate::error::{PromptBankError, Result};
+use crate::history::{self, ApplyEvent, UseEvent};
+use crate::markdown;
+use crate::pipeline;
+use crate::profile::{self, Phase};
 use crate::prompt::PromptBank;
 
-const APP_NAME: &str = "promptbank";
-const ORG_NAME: &str = "claude";
-const DATA_FILE: &str = "prompts.json";
+pub(crate) const APP_NAME: &str = "promptbank";
+pub(crate) const ORG_NAME: &str = "claude";
+const DATA_STEM: &str = "prompts";
+const BANKS_DIR: &str = "banks";
+const STATE_DIR: &str = "state";
+const HISTORY_DIR: &str = "history";
+const EMBEDDINGS_DIR: &str = "embeddings";
+const ZSTD_LEVEL: i32 = 3;
+const JOURNAL_SUFFIX: &str = "journal";
+const TEMP_SUFFIX: &str = "tmp";
+
+/// Name of the bank stored in the top-level data file
+pub const DEFAULT_BANK: &str = "default";
+
+/// Data directory chosen by the `storage.data_dir` setting
+static DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Keep data in `dir` instead of the platform data directory
+///
+/// Called at startup, before any bank is opened, and by `init` when a new
+/// directory is chosen. Banks already open keep their paths.
+pub fn set_data_dir(dir: PathBuf) {
+    if let Ok(mut data_dir) = DATA_DIR.lock() {
+        *data_dir = Some(dir);
+    }
+}
+
+/// On-disk encoding of a bank
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// Pretty-printed JSON
+    #[default]
+    Json,
+    /// Zstandard-compressed JSON
+    Zstd,
+    /// MessagePack binary encoding
+    Msgpack,
+    /// A directory with one markdown file per prompt
+    Markdown,
+    /// A directory with one file per machine, merged on load so that
+    /// concurrent edits synced from other machines never conflict
+    Crdt,
+}
+
+impl StorageFormat {
+    const ALL: [StorageFormat; 5] = [StorageFormat::Json, StorageFormat::Zstd, StorageFormat::Msgpack, StorageFormat::Markdown, StorageFormat::Crdt];
+
+    /// Name of a bank's data file (or directory) on disk
+    fn file_name(&self, stem: &str) -> String {
+        match self {
+            StorageFormat::Json => format!("{}.json", stem),
+            StorageFormat::Zstd => format!("{}.json.zst", stem),
+            StorageFormat::Msgpack => format!("{}.msgpack", stem),
+            StorageFormat::Markdown => stem.to_string(),
+            StorageFormat::Crdt => format!("{}.crdt", stem),
+        }
+    }
+
+    /// Encode a bank as a single blob
+    ///
+    /// The markdown and CRDT layouts are spread over many files, so their
+    /// journal holds a JSON snapshot instead.
+    fn encode(&self, bank: &PromptBank) -> Result<Vec<u8>> {
+        match self {
+            StorageFormat::Json | StorageFormat::Markdown | StorageFormat::Crdt => {
+                Ok(serde_json::to_vec_pretty(bank)?)
+            }
+            StorageFormat::Zstd => {
+                let json = serde_json::to_vec(bank)?;
+                Ok(zstd::encode_all(json.as_slice(), ZSTD_LEVEL)?)
+            }
+            StorageFormat::Msgpack => rmp_serde::to_vec_named(bank)
+                .map_err(|e| PromptBankError::Storage(format!("Failed to encode bank: {}", e))),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<PromptBank> {
+        match self {
+            StorageFormat::Json | StorageFormat::Markdown | StorageFormat::Crdt => {
+                Ok(serde_json::from_slice(bytes)?)
+            }
+            StorageFormat::Zstd => {
+                let json = zstd::decode_all(bytes)?;
+                Ok(serde_json::from_slice(&json)?)
+            }
+            StorageFormat::Msgpack => rmp_serde::from_slice(bytes)
+                .map_err(|e| PromptBankError::Storage(format!("Failed to decode bank: {}", e))),
+        }
+    }
+}
+
+/// Outcome of checking for a journal left behind by an interrupted save
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalRecovery {
+    /// No pending journal was found
+    Clean,
+    /// A complete journal was replayed into the data file
+    Replayed,
+    /// An incomplete journal was discarded
+    Discarded,
+}
 
 pub struct Storage {
     data_path: PathBuf,
+    /// Sidecar holding usage counters and history, kept out of the bank file
+    state_path: PathBuf,
+    /// Log of apply events, one JSON object per line
+    history_path: PathBuf,
+    /// Log of uses not yet counted in the state file
+    uses_path: PathBuf,
+    /// Cached prompt vectors for semantic search
+    embeddings_path: PathBuf,
+    bank_name: String,
+    format: StorageFormat,
+    /// CRDT registers as last loaded or saved, to tell local edits apart
+    crdt_base: RefCell<Registers>,
 }
 
 impl Storage {
-    pub fn new() -> Result<Self> {
-        let data_path = Self::get_data_path()?;
+    /// Open a named bank (the default bank lives in the top-level data file)
+    pub fn open(bank_name: &str, format: StorageFormat) -> Result<Self> {
+        let data_path = Self::get_bank_path(bank_name, format)?;
+        // Machines syncing a CRDT bank each keep their own state and history,
+        // so these files never conflict either
+        let local_stem = if format == StorageFormat::Crdt {
+            format!("{}.{}", bank_name, crdt::local_replica_id()?)
+        } else {
+            bank_name.to_string()
+        };
+        let data_dir = Self::get_data_dir()?;
+        let state_path = data_dir
+            .join(STATE_DIR)
+            .join(format!("{}.json", local_stem));
+        let history_path = data_dir
+            .join(HISTORY_DIR)
+            .join(format!("{}.jsonl", local_stem));
+        let uses_path = data_dir
+            .join(HISTORY_DIR)
+            .join(format!("{}.uses.jsonl", local_stem));
+        let embeddings_path = data_dir
+            .join(EMBEDDINGS_DIR)
+            .join(format!("{}.json", local_stem));
 
-        // Ensure directory exists
-        if let Some(parent) = data_path.parent() {
-            fs::create_dir_all(parent)?;
+        debug!(bank = bank_name, path = %data_path.display(), "opening bank");
+        debug!(path = %state_path.display(), "state file");
+
+        // Ensure directories exist
+        for path in [&data_path, &state_path] {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
         }
 
-        Ok(Self { data_path })
+        Ok(Self {
+            data_path,
+            state_path,
+            history_path,
+            uses_path,
+            embeddings_path,
+            bank_name: bank_name.to_string(),
+            format,
+            crdt_base: RefCell::default(),
+        })
     }
 
-    /// Get the path to the data file
-    fn get_data_path() -> Result<PathBuf> {
+    /// Open a bank stored at an explicit path outside the data directory
+    pub fn at_path(bank_name: &str, data_path: PathBuf, format: StorageFormat) -> Self {
+        let mut state_path = data_path.clone().into_os_string();
+        state_path.push(".state.json");
+        let mut history_path = data_path.clone().into_os_string();
+        history_path.push(".history.jsonl");
+        let mut uses_path = data_path.clone().into_os_string();
+        uses_path.push(".uses.jsonl");
+        let mut embeddings_path = data_path.clone().into_os_string();
+        embeddings_path.push(".embeddings.json");
+        Self {
+            data_path,
+            state_path: PathBuf::from(state_path),
+            history_path: PathBuf::from(history_path),
+            uses_path: PathBuf::from(uses_path),
+            embeddings_path: PathBuf::from(embeddings_path),
+            bank_name: bank_name.to_string(),
+            format,
+            crdt_base: RefCell::default(),
+        }
+    }
+
+    /// Get the directory holding all data files
+    pub(crate) fn get_data_dir() -> Result<PathBuf> {
+        if let Some(dir) = DATA_DIR.lock().ok().and_then(|dir| dir.clone()) {
+            return Ok(dir);
+        }
         if let Some(proj_dirs) = ProjectDirs::from("com", ORG_NAME, APP_NAME) {
-            let data_dir = proj_dirs.data_dir();
-            Ok(data_dir.join(DATA_FILE))
+            Ok(proj_dirs.data_dir().to_path_buf())
         } else {
             // Fallback to home directory
             let home = dirs_fallback()?;
-            Ok(home.join(format!(".{}", APP_NAME)).join(DATA_FILE))
+            Ok(home.join(format!(".{}", APP_NAME)))
+        }
+    }
+
+    /// List the names of all banks that have data on disk
+    pub fn bank_names() -> Result<Vec<String>> {
+        let mut names = vec![DEFAULT_BANK.to_string()];
+
+        let banks_dir = Self::get_data_dir()?.join(BANKS_DIR);
+        if banks_dir.exists() {
+            for entry in fs::read_dir(&banks_dir)? {
+                let entry = entry?;
+                let file_name = entry.file_name();
+                let Some(file_name) = file_name.to_str() else {
+                    continue;
+                };
+                let name = if entry.path().is_dir() {
+                    let crdt_suffix = StorageFormat::Crdt.file_name("");
+                    Some(file_name.strip_suffix(&crdt_suffix).unwrap_or(file_name))
+                } else {
+                    [
+                        StorageFormat::Json,
+                        StorageFormat::Zstd,
+                        StorageFormat::Msgpack,
+                    ]
+                    .iter()
+                    .find_map(|f| file_name.strip_suffix(&f.file_name("")))
+                };
+                if let Some(name) = name {
+                    if !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+
+        names[1..].sort();
+        Ok(names)
+    }
+
+    /// Get the path to the data file of a bank
+    fn get_bank_path(bank_name: &str, format: StorageFormat) -> Result<PathBuf> {
+        let valid = !bank_name.is_empty()
+            && bank_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(PromptBankError::InvalidInput(format!(
+                "Invalid bank name '{}' (use letters, digits, '-' or '_')",
+                bank_name
+            )));
+        }
+
+        let data_dir = Self::get_data_dir()?;
+        if bank_name == DEFAULT_BANK {
+            Ok(data_dir.join(format.file_name(DATA_STEM)))
+        } else {
+            Ok(data_dir.join(BANKS_DIR).join(format.file_name(bank_name)))
         }
     }
 
     /// Load the prompt bank from storage
     pub fn load(&self) -> Result<PromptBank> {
-        if !self.data_path.exists() {
-            return Ok(PromptBank::new());
+        profile::measure(Phase::Load, || {
+            let mut bank = self.read()?;
+            bank.refresh_variables();
+            if self.state_path.exists() {
+                let state = serde_json::from_slice(&fs::read(&self.state_path)?)?;
+                bank.restore_state(state);
+            }
+            for event in history::read::<UseEvent>(&self.uses_path)? {
+                if let Some(prompt) = bank.prompts.iter_mut().find(|p| p.id == event.prompt_id) {
+                    prompt.mark_used_at(event.timestamp);
+                }
+            }
+            Ok(bank)
+        })
+    }
+
+    fn read(&self) -> Result<PromptBank> {
+        if self.data_path.exists() {
+            match self.format {
+                StorageFormat::Markdown => return markdown::read_bank(&self.data_path),
+                StorageFormat::Crdt => {
+                    let registers = crdt::read_registers(&self.data_path)?;
+                    let mut bank = crdt::to_bank(&registers);
+                    bank.pipelines = pipeline::read_file(&self.data_path)?;
+                    *self.crdt_base.borrow_mut() = registers;
+                    return Ok(bank);
+                }
+                _ => {}
+            }
+            let bytes = fs::read(&self.data_path)?;
+            return self.format.decode(&bytes);
         }
 
-        let content = fs::read_to_string(&self.data_path)?;
-        let bank: PromptBank = serde_json::from_str(&content)?;
-        Ok(bank)
+        // Transparently convert a bank stored in another format
+        for format in StorageFormat::ALL {
+            if format == self.format {
+                continue;
+            }
+            let path = Self::get_bank_path(&self.bank_name, format)?;
+            if !path.exists() {
+                continue;
+            }
+            info!(from = %path.display(), to = ?self.format, "converting bank");
+            if format == StorageFormat::Markdown {
+                let bank = markdown::read_bank(&path)?;
+                self.write(&bank)?;
+                markdown::remove_bank(&path)?;
+                return Ok(bank);
+            }
+            if format == StorageFormat::Crdt {
+                let mut bank = crdt::to_bank(&crdt::read_registers(&path)?);
+                bank.pipelines = pipeline::read_file(&path)?;
+                self.write(&bank)?;
+                fs::remove_dir_all(&path)?;
+                return Ok(bank);
+            }
+            if path.is_file() {
+                let bank = format.decode(&fs::read(&path)?)?;
+                self.write(&bank)?;
+                fs::remove_file(&path)?;
+                return Ok(bank);
+            }
+        }
+
+        Ok(PromptBank::new())
     }
 
     /// Save the prompt bank to storage
+    ///
+    /// The new contents are first flushed to a journal, then written to a
+    /// temporary file that atomically replaces the data file. A crash at any
+    /// point leaves either the old data file or a journal to recover from.
+    ///
+    /// Usage counters and history are written to a separate state file,
+    /// which then also counts the uses logged since the bank was loaded.
     pub fn save(&self, bank: &PromptBank) -> Result<()> {
-        let content = serde_json::to_string_pretty(bank)?;
-        fs::write(&self.data_path, content)?;
+        profile::measure(Phase::Save, || {
+            self.write(bank)?;
+            if self.uses_path.exists() {
+                fs::remove_file(&self.uses_path)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn write(&self, bank: &PromptBank) -> Result<()> {
+        let (bank, state) = bank.split_state();
+        self.write_bank(&bank)?;
+
+        if !state.prompts.is_empty() || self.state_path.exists() {
+            let temp_path = self.state_path.with_extension(TEMP_SUFFIX);
+            write_synced(&temp_path, &serde_json::to_vec_pretty(&state)?)?;
+            fs::rename(&temp_path, &self.state_path)?;
+        }
+        Ok(())
+    }
+
+    fn write_bank(&self, bank: &PromptBank) -> Result<()> {
+        info!(path = %self.data_path.display(), prompts = bank.prompts.len(), "saving bank");
+        let content = self.format.encode(bank)?;
+        let journal_path = self.sibling_path(JOURNAL_SUFFIX);
+
+        write_synced(&journal_path, &content)?;
+        self.write_data(bank, &content)?;
+        fs::remove_file(&journal_path)?;
         Ok(())
     }
 
+    /// Replay or discard a journal left behind by an interrupted save
+    pub fn recover(&self) -> Result<JournalRecovery> {
+        let journal_path = self.sibling_path(JOURNAL_SUFFIX);
+        if !journal_path.exists() {
+            return Ok(JournalRecovery::Clean);
+        }
+
+        let content = fs::read(&journal_path)?;
+        let recovery = if let Ok(bank) = self.format.decode(&content) {
+            self.write_data(&bank, &content)?;
+            JournalRecovery::Replayed
+        } else {
+            JournalRecovery::Discarded
+        };
+        info!(path = %journal_path.display(), ?recovery, "recovered journal");
+
+        fs::remove_file(&journal_path)?;
+        Ok(recovery)
+    }
+
+    /// Write a bank in this storage's layout from its encoded contents
+    fn write_data(&self, bank: &PromptBank, content: &[u8]) -> Result<()> {
+        match self.format {
+            StorageFormat::Markdown => markdown::write_bank(&self.data_path, bank),
+            StorageFormat::Crdt => {
+                let replica = crdt::local_replica_id()?;
+                let base = self.crdt_base.borrow().clone();
+                let merged = crdt::write_replica(&self.data_path, &replica, &base, bank)?;
+                *self.crdt_base.borrow_mut() = merged;
+                // Shared by every replica, so the last machine to save wins
+                pipeline::write_file(&self.data_path, &bank.pipelines)
+            }
+            _ => self.replace_data_file(content),
+        }
+    }
+
+    /// Get the storage format of this bank
+    pub fn format(&self) -> StorageFormat {
+        self.format
+    }
+
+    /// Atomically replace the data file with new contents
+    fn replace_data_file(&self, content: &[u8]) -> Result<()> {
+        let temp_path = self.sibling_path(TEMP_SUFFIX);
+        write_synced(&temp_path, content)?;
+        fs::rename(&temp_path, &self.data_path)?;
+        Ok(())
+    }
+
+    /// Path next to the data file with an extra suffix
+    fn sibling_path(&self, suffix: &str) -> PathBuf {
+        let mut name = self.data_path.as_os_str().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+
+    /// Total size of the bank on disk in bytes
+    pub fn size_on_disk(&self) -> Result<u64> {
+        if !self.data_path.exists() {
+            return Ok(0);
+        }
+        if self.data_path.is_file() {
+            return Ok(fs::metadata(&self.data_path)?.len());
+        }
+
+        let mut size = 0;
+        for entry in walkdir::WalkDir::new(&self.data_path) {
+            let entry = entry.map_err(|e| PromptBankError::Storage(e.to_string()))?;
+            if entry.file_type().is_file() {
+                size += entry
+                    .metadata()
+                    .map_err(|e| PromptBankError::Storage(e.to_string()))?
+                    .len();
+            }
+        }
+        Ok(size)
+    }
+
     /// Get the data file path for display
     pub fn data_file_path(&self) -> &PathBuf {
         &self.data_path
     }
 
-    /// Export prompts to a file
-    pub fn export(&self, bank: &PromptBank, path: &PathBuf) -> Result<()> {
-        let content = serde_json::to_string_pretty(bank)?;
+    /// Get the name of the bank this storage points to
+    pub fn bank_name(&self) -> &str {
+        &self.bank_name
+    }
+
+    /// Record that a prompt was applied
+    pub fn append_history(&self, event: &ApplyEvent) -> Result<()> {
+        history::append(&self.history_path, event)
+    }
+
+    /// Record that a prompt was read, without rewriting the bank
+    pub fn append_use(&self, event: &UseEvent) -> Result<()> {
+        history::append(&self.uses_path, event)
+    }
+
+    /// Read the apply history of this bank, oldest first
+    pub fn load_history(&self) -> Result<Vec<ApplyEvent>> {
+        history::read(&self.history_path)
+    }
+
+    /// Read the cached prompt vectors of this bank
+    pub fn load_embeddings(&self) -> Result<EmbeddingCache> {
+        EmbeddingCache::load(&self.embeddings_path)
+    }
+
+    pub fn save_embeddings(&self, cache: &EmbeddingCache) -> Result<()> {
+        cache.save(&self.embeddings_path)
+    }
+
+    /// Export prompts to a file (always portable JSON)
+    ///
+    /// Usage counters and history are left out unless `with_state` is set.
+    pub fn export(&self, bank: &PromptBank, path: &Path, with_state: bool) -> Result<()> {
+        let content = if with_state {
+            serde_json::to_string_pretty(bank)?
+        } else {
+            serde_json::to_string_pretty(&bank.split_state().0)?
+        };
+        info!(path = %path.display(), "exporting bank");
         fs::write(path, content)?;
         Ok(())
     }
 
-    /// Import prompts from a file
-    pub fn import(&self, path: &PathBuf) -> Result<PromptBank> {
+    /// Import prompts from a file, dropping any state unless `with_state` is set
+    pub fn import(&self, path: &Path, with_state: bool) -> Result<PromptBank> {
+        debug!(path = %path.display(), "importing bank");
         let content = fs::read_to_string(path)?;
-        let bank: PromptBank = serde_json::from_str(&content)?;
-        Ok(bank)
+        let mut bank: PromptBank = serde_json::from_str(&content)?;
+        bank.refresh_variables();
+        if with_state {
+            Ok(bank)
+        } else {
+            Ok(bank.split_state().0)
+        }
     }
 }
 
+/// Write a file and flush it to disk before returning
+fn write_synced(path: &Path, content: &[u8]) -> Result<()> {
+    debug!(path = %path.display(), bytes = content.len(), "writing");
+    let mut file = fs::File::create(path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    Ok(())
+}
+
 fn dirs_fallback() -> Result<PathBuf> {
     std::env::var("HOME")
         .map(PathBuf::from)
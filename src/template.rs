@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, HashMap};
+use tera::ast::{Expr, ExprVal, FunctionCall, Node};
+use tera::{Context, Tera, Value};
+
+use crate::error::{PromptBankError, Result};
+use crate::prompt::{self, VariableType};
+
+/// Names Tera defines inside templates, which are never the caller's to fill
+const TERA_NAMES: [&str; 2] = ["loop", "__tera_context"];
+
+/// Variables a Tera template reads without setting them itself, in order
+///
+/// Loop variables and `set` targets are left out, as is everything when the
+/// template doesn't parse.
+pub fn variables(content: &str) -> Vec<String> {
+    scan(content).names
+}
+
+/// Defaults given in the template with `{{ name | default(value="...") }}`
+pub fn defaults(content: &str) -> BTreeMap<String, String> {
+    scan(content).defaults
+}
+
+/// Variables looped over with `{% for item in name %}`
+pub fn lists(content: &str) -> Vec<String> {
+    scan(content).lists
+}
+
+/// Why content isn't a valid Tera template, with the 1-based line at fault
+pub fn syntax_error(content: &str) -> Option<(usize, String)> {
+    let error = tera::Template::new("prompt", None, content).err()?;
+    let message = describe(&error);
+    // Parse errors point at the problem with `--> line:column`, and explain
+    // it on a last line starting with `=`
+    let line = message
+        .split_once("--> ")
+        .and_then(|(_, rest)| rest.split(':').next())
+        .and_then(|line| line.trim().parse().ok())
+        .unwrap_or(1);
+    let reason = message
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("= "))
+        .map_or(message.clone(), str::to_string);
+    Some((line, reason))
+}
+
+/// Render a Tera template named after its prompt
+pub fn render(name: &str, content: &str, values: &BTreeMap<String, Value>) -> Result<String> {
+    let mut context = Context::new();
+    for (key, value) in values {
+        context.insert(key, value);
+    }
+    let mut tera = Tera::default();
+    tera.autoescape_on(Vec::new());
+    // Tera registers `get_env` even without its builtins; environment values
+    // are often secrets, so only `apply --env` may hand them to a prompt
+    tera.register_function("get_env", |_: &HashMap<String, Value>| {
+        Err(tera::Error::msg(
+            "get_env() is not available in prompts; use a variable with `apply --env`",
+        ))
+    });
+    tera.add_raw_template(name, content)
+        .and_then(|()| tera.render(name, &context))
+        .map_err(|e| PromptBankError::InvalidInput(describe(&e)))
+}
+
+/// A variable's value as its type, so `false` is false in `{% if %}` and
+/// numbers can be compared
+pub fn value(kind: VariableType, text: &str) -> Value {
+    let trimmed = text.trim();
+    match kind {
+        VariableType::Number => {
+            if let Ok(int) = trimmed.parse::<i64>() {
+                return Value::from(int);
+            }
+            match trimmed.parse::<f64>() {
+                Ok(float) => Value::from(float),
+                Err(_) => Value::from(text),
+            }
+        }
+        VariableType::Boolean => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" => Value::Bool(true),
+            "false" | "no" => Value::Bool(false),
+            _ => Value::from(text),
+        },
+        VariableType::List => prompt::list_items(text)
+            .into_iter()
+            .map(Value::from)
+            .collect(),
+        VariableType::Text | VariableType::Date => Value::from(text),
+    }
+}
+
+/// A Tera error with the causes it wraps, which carry the useful detail
+fn describe(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(cause.to_string().trim());
+        source = cause.source();
+    }
+    message
+}
+
+fn scan(content: &str) -> Scan {
+    let mut scan = Scan::default();
+    if let Ok(template) = tera::Template::new("prompt", None, content) {
+        scan.nodes(&template.ast);
+    }
+    scan
+}
+
+/// Walks a template, noting the variables it reads from outside
+#[derive(Default)]
+struct Scan {
+    names: Vec<String>,
+    defaults: BTreeMap<String, String>,
+    lists: Vec<String>,
+    /// Names set inside the template by loops and `set`
+    bound: Vec<String>,
+}
+
+impl Scan {
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::VariableBlock(_, expr) => self.expr(expr),
+                Node::Set(_, set) => {
+                    self.expr(&set.value);
+                    self.bound.push(set.key.clone());
+                }
+                Node::FilterSection(_, section, _) => {
+                    self.call(&section.filter);
+                    self.nodes(&section.body);
+                }
+                Node::Block(_, block, _) => self.nodes(&block.body),
+                Node::Forloop(_, forloop, _) => {
+                    self.expr(&forloop.container);
+                    // Only a variable looped over as it is, not `items | split(...)`
+                    if let ExprVal::Ident(ident) = &forloop.container.val {
+                        let free = self.names.contains(ident) && !self.bound.contains(ident);
+                        if forloop.container.filters.is_empty() && free {
+                            self.lists.push(ident.clone());
+                        }
+                    }
+                    let outer = self.bound.len();
+                    self.bound.extend(forloop.key.clone());
+                    self.bound.push(forloop.value.clone());
+                    self.nodes(&forloop.body);
+                    self.bound.truncate(outer);
+                    if let Some(body) = &forloop.empty_body {
+                        self.nodes(body);
+                    }
+                }
+                Node::If(branches, _) => {
+                    for (_, condition, body) in &branches.conditions {
+                        self.expr(condition);
+                        self.nodes(body);
+                    }
+                    if let Some((_, body)) = &branches.otherwise {
+                        self.nodes(body);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        self.val(&expr.val);
+        if let (ExprVal::Ident(ident), Some(filter)) = (&expr.val, expr.filters.first()) {
+            let literal = filter.args.get("value").map(|arg| &arg.val);
+            if let (true, Some(ExprVal::String(default))) = (filter.name == "default", literal) {
+                self.defaults
+                    .entry(root(ident).to_string())
+                    .or_insert_with(|| default.clone());
+            }
+        }
+        for filter in &expr.filters {
+            self.call(filter);
+        }
+    }
+
+    fn call(&mut self, call: &FunctionCall) {
+        // Arguments are kept in a hash map, so visit them in a stable order
+        let mut args: Vec<_> = call.args.iter().collect();
+        args.sort_by_key(|(name, _)| *name);
+        for (_, arg) in args {
+            self.expr(arg);
+        }
+    }
+
+    fn val(&mut self, val: &ExprVal) {
+        match val {
+            ExprVal::Ident(ident) => self.ident(ident),
+            ExprVal::Math(math) => {
+                self.expr(&math.lhs);
+                self.expr(&math.rhs);
+            }
+            ExprVal::Logic(logic) => {
+                self.expr(&logic.lhs);
+                self.expr(&logic.rhs);
+            }
+            ExprVal::In(within) => {
+                self.expr(&within.lhs);
+                self.expr(&within.rhs);
+            }
+            ExprVal::Test(test) => {
+                self.ident(&test.ident);
+                for arg in &test.args {
+                    self.expr(arg);
+                }
+            }
+            ExprVal::FunctionCall(call) => self.call(call),
+            ExprVal::Array(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            ExprVal::StringConcat(concat) => {
+                for value in &concat.values {
+                    self.val(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn ident(&mut self, ident: &str) {
+        let name = root(ident);
+        let known = TERA_NAMES.contains(&name)
+            || self.bound.iter().any(|b| b == name)
+            || self.names.iter().any(|n| n == name);
+        if !name.is_empty() && !known {
+            self.names.push(name.to_string());
+        }
+    }
+}
+
+/// The variable an identifier like `user.name` or `items[0]` starts from
+fn root(ident: &str) -> &str {
+    ident.split(['.', '[']).next().unwrap_or(ident)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_typed_values() {
+        let mut values = BTreeMap::new();
+        values.insert("strict".to_string(), value(VariableType::Boolean, "no"));
+        values.insert("name".to_string(), value(VariableType::Text, "Ada"));
+        let output = render("t", "Hi {{ name }}{% if strict %}!{% endif %}", &values);
+
+        assert_eq!(output.unwrap(), "Hi Ada");
+    }
+
+    #[test]
+    fn environment_is_not_readable() {
+        std::env::set_var("PROMPTBANK_TEST_SECRET", "hunter2");
+        let output = render(
+            "t",
+            "Hi {{ get_env(name=\"PROMPTBANK_TEST_SECRET\") }}",
+            &BTreeMap::new(),
+        );
+
+        let error = output.expect_err("get_env is refused");
+        assert!(!error.to_string().contains("hunter2"));
+        assert!(error.to_string().contains("apply --env"));
+    }
+}
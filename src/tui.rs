@@ -0,0 +1,390 @@
+use arboard::Clipboard;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::error::{PromptBankError, Result};
+use crate::prompt::{Includes, PromptBank};
+use crate::storage::Storage;
+
+const HELP: &str = "↑/↓ move  / search  c copy  a apply  e edit  d delete  q quit";
+
+/// What keystrokes are currently interpreted as
+enum Mode {
+    Browse,
+    Search,
+    /// Collecting values for the selected prompt's variables, one at a time
+    Variables {
+        /// Variables in order, with their value once entered
+        values: Vec<(String, Option<String>)>,
+        /// Used when a variable is left empty
+        defaults: BTreeMap<String, String>,
+        input: String,
+    },
+    ConfirmDelete,
+}
+
+/// Interactive browser over a prompt bank
+pub struct Tui<'a> {
+    bank: &'a mut PromptBank,
+    storage: &'a Storage,
+    config: &'a Config,
+    mode: Mode,
+    query: String,
+    /// Indices into `bank.prompts` matching the current query
+    visible: Vec<usize>,
+    list_state: ListState,
+    /// Rendered output shown instead of the raw content after an apply
+    rendered: Option<String>,
+    status: String,
+}
+
+impl<'a> Tui<'a> {
+    pub fn new(bank: &'a mut PromptBank, storage: &'a Storage, config: &'a Config) -> Self {
+        let mut tui = Self {
+            bank,
+            storage,
+            config,
+            mode: Mode::Browse,
+            query: String::new(),
+            visible: Vec::new(),
+            list_state: ListState::default(),
+            rendered: None,
+            status: HELP.to_string(),
+        };
+        tui.refilter();
+        tui
+    }
+
+    /// Run the browser until the user quits
+    pub fn run(mut self) -> Result<()> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match self.mode {
+                Mode::Browse => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+                    KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+                    KeyCode::Char('/') => {
+                        self.mode = Mode::Search;
+                        self.status = "Type to search, Enter to confirm, Esc to clear".to_string();
+                    }
+                    KeyCode::Char('c') => self.copy_selected(),
+                    KeyCode::Char('a') | KeyCode::Enter => self.start_apply(),
+                    KeyCode::Char('e') => {
+                        ratatui::restore();
+                        let edited = self.edit_selected();
+                        *terminal = ratatui::init();
+                        edited?;
+                    }
+                    KeyCode::Char('d') => {
+                        if let Some(index) = self.selected_index() {
+                            self.status =
+                                format!("Delete '{}'? (y/n)", self.bank.prompts[index].name);
+                            self.mode = Mode::ConfirmDelete;
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::Search => match key.code {
+                    KeyCode::Enter => {
+                        self.mode = Mode::Browse;
+                        self.status = HELP.to_string();
+                    }
+                    KeyCode::Esc => {
+                        self.query.clear();
+                        self.refilter();
+                        self.mode = Mode::Browse;
+                        self.status = HELP.to_string();
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                        self.refilter();
+                    }
+                    KeyCode::Char(c) => {
+                        self.query.push(c);
+                        self.refilter();
+                    }
+                    _ => {}
+                },
+                Mode::Variables {
+                    ref mut values,
+                    ref defaults,
+                    ref mut input,
+                } => match key.code {
+                    KeyCode::Esc => {
+                        self.mode = Mode::Browse;
+                        self.status = HELP.to_string();
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) => input.push(c),
+                    KeyCode::Enter => {
+                        if let Some(slot) = values.iter_mut().find(|(_, v)| v.is_none()) {
+                            let value = std::mem::take(input);
+                            slot.1 = if value.is_empty() {
+                                defaults.get(&slot.0).cloned()
+                            } else {
+                                Some(value)
+                            };
+                        }
+                        if values.iter().all(|(_, v)| v.is_some()) {
+                            let values: Vec<(String, String)> = std::mem::take(values)
+                                .into_iter()
+                                .map(|(k, v)| (k, v.unwrap_or_default()))
+                                .collect();
+                            self.finish_apply(&values);
+                        }
+                    }
+                    _ => {}
+                },
+                Mode::ConfirmDelete => {
+                    if key.code == KeyCode::Char('y') {
+                        self.delete_selected()?;
+                    } else {
+                        self.status = HELP.to_string();
+                    }
+                    self.mode = Mode::Browse;
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let prompt = &self.bank.prompts[i];
+                ListItem::new(Line::from(vec![
+                    Span::styled(prompt.name.clone(), Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("[{}]", prompt.category),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list_title = if self.query.is_empty() {
+            format!(" Prompts ({}) ", self.visible.len())
+        } else {
+            format!(" Prompts ({}) /{} ", self.visible.len(), self.query)
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(list_title))
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let (preview_title, preview_text) = match (self.selected_index(), &self.rendered) {
+            (Some(_), Some(rendered)) => (" Rendered ".to_string(), rendered.clone()),
+            (Some(i), None) => {
+                let prompt = &self.bank.prompts[i];
+                let mut text = format!("{}\n", prompt.description);
+                if !prompt.tags.is_empty() {
+                    text.push_str(&format!("Tags: {}\n", prompt.tags.join(", ")));
+                }
+                if !prompt.variables.is_empty() {
+                    text.push_str(&format!("Variables: {}\n", prompt.variables.join(", ")));
+                }
+                text.push('\n');
+                text.push_str(&prompt.content);
+                (format!(" {} ", prompt.id), text)
+            }
+            (None, _) => (" Preview ".to_string(), String::new()),
+        };
+        let preview = Paragraph::new(preview_text)
+            .block(Block::default().borders(Borders::ALL).title(preview_title))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(preview, preview_area);
+
+        let footer_text = match &self.mode {
+            Mode::Search => format!("/{}", self.query),
+            Mode::Variables {
+                values,
+                defaults,
+                input,
+            } => {
+                let name = values
+                    .iter()
+                    .find(|(_, v)| v.is_none())
+                    .map(|(k, _)| k.as_str())
+                    .unwrap_or_default();
+                match defaults.get(name).filter(|d| !d.is_empty()) {
+                    Some(default) => format!("{} [{}]: {}", name, default, input),
+                    None => format!("{}: {}", name, input),
+                }
+            }
+            _ => self.status.clone(),
+        };
+        let footer_widget =
+            Paragraph::new(footer_text).block(Block::default().borders(Borders::ALL));
+        frame.render_widget(footer_widget, footer);
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.visible.get(i).copied())
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.visible.len() as isize - 1);
+        self.list_state.select(Some(next as usize));
+        self.rendered = None;
+    }
+
+    /// Recompute the visible prompts from the search query
+    fn refilter(&mut self) {
+        let query = self.query.to_lowercase();
+        self.visible = self
+            .bank
+            .prompts
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !p.archived)
+            .filter(|(_, p)| {
+                query.is_empty()
+                    || p.name.to_lowercase().contains(&query)
+                    || p.description.to_lowercase().contains(&query)
+                    || p.tags.iter().any(|t| t.to_lowercase().contains(&query))
+                    || p.content.to_lowercase().contains(&query)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.list_state
+            .select(if self.visible.is_empty() { None } else { Some(0) });
+        self.rendered = None;
+    }
+
+    fn copy_selected(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let text = self
+            .rendered
+            .clone()
+            .unwrap_or_else(|| self.bank.prompts[index].content.clone());
+        self.status = match copy_to_clipboard(&text).and_then(|()| self.record_use(index)) {
+            Ok(()) => "✓ Copied to clipboard".to_string(),
+            Err(e) => format!("✗ {}", e),
+        };
+    }
+
+    fn start_apply(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let prompt = &self.bank.prompts[index];
+        if prompt.variables.is_empty() {
+            self.finish_apply(&[]);
+        } else {
+            self.mode = Mode::Variables {
+                values: prompt.variables.iter().map(|v| (v.clone(), None)).collect(),
+                defaults: prompt.variable_defaults(),
+                input: String::new(),
+            };
+        }
+    }
+
+    fn finish_apply(&mut self, values: &[(String, String)]) {
+        self.mode = Mode::Browse;
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        let rendered = match self.bank.prompts[index].render(values, Includes::default()) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                self.status = format!("✗ {}", e);
+                return;
+            }
+        };
+        self.status = match copy_to_clipboard(&rendered) {
+            Ok(()) => "✓ Rendered and copied to clipboard".to_string(),
+            Err(_) => "✓ Rendered".to_string(),
+        };
+        if let Err(e) = self.record_use(index) {
+            self.status = format!("✗ {}", e);
+        }
+        self.rendered = Some(rendered);
+    }
+
+    fn record_use(&mut self, index: usize) -> Result<()> {
+        self.bank.prompts[index].mark_used();
+        self.storage.save(self.bank)
+    }
+
+    fn edit_selected(&mut self) -> Result<()> {
+        let Some(index) = self.selected_index() else {
+            return Ok(());
+        };
+        let current = self.bank.prompts[index].content.clone();
+        let edited = self
+            .config
+            .editor()
+            .edit(&current)
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+
+        match edited {
+            Some(content) if content != current => {
+                self.bank.prompts[index].update_content(content);
+                self.storage.save(self.bank)?;
+                self.status = format!("✓ Prompt '{}' updated", self.bank.prompts[index].name);
+            }
+            _ => self.status = "No changes made".to_string(),
+        }
+        self.rendered = None;
+        Ok(())
+    }
+
+    fn delete_selected(&mut self) -> Result<()> {
+        let Some(index) = self.selected_index() else {
+            return Ok(());
+        };
+        let prompt = self.bank.prompts.remove(index);
+        self.storage.save(self.bank)?;
+        self.status = format!("✓ Prompt '{}' deleted", prompt.name);
+        self.refilter();
+        Ok(())
+    }
+}
+
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut clipboard = Clipboard::new().map_err(|e| PromptBankError::Clipboard(e.to_string()))?;
+    clipboard
+        .set_text(text)
+        .map_err(|e| PromptBankError::Clipboard(e.to_string()))
+}
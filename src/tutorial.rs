@@ -0,0 +1,202 @@
+use colored::*;
+use dialoguer::{Confirm, Input};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use crate::claude::{ClaudeIntegration, InstallType};
+use crate::config::Config;
+use crate::error::{PromptBankError, Result};
+use crate::prompt::{Includes, Prompt, PromptBank, PromptCategory};
+use crate::storage::{Storage, StorageFormat};
+
+const TUTORIAL_BANK: &str = "tutorial";
+const SAMPLE_CONTENT: &str =
+    "You are reviewing {{language}} code.\nFocus on {{focus}} and explain each suggestion briefly.";
+
+/// Scratch directory removed when the tutorial ends, even on error
+struct Sandbox {
+    root: PathBuf,
+}
+
+impl Sandbox {
+    fn create() -> Result<Self> {
+        let root = std::env::temp_dir().join(format!("promptbank-tutorial-{}", std::process::id()));
+        fs::create_dir_all(root.join("project"))?;
+        Ok(Self { root })
+    }
+
+    fn bank_path(&self) -> PathBuf {
+        self.root.join("prompts.json")
+    }
+
+    fn project(&self) -> PathBuf {
+        self.root.join("project")
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Walk through the add → apply → edit → install workflow in a throwaway bank
+pub fn run() -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        return Err(PromptBankError::InvalidInput(
+            "The tutorial is interactive and needs a terminal".to_string(),
+        ));
+    }
+
+    println!("\n{}", "Welcome to promptbank!".bold().underline());
+    println!(
+        "This tutorial uses a temporary bank, so your real prompts are never touched.\n\
+         It is deleted when you finish."
+    );
+    if !confirm("Ready to start?")? {
+        return Ok(());
+    }
+
+    let sandbox = Sandbox::create()?;
+    let storage = Storage::at_path(TUTORIAL_BANK, sandbox.bank_path(), StorageFormat::Json);
+    let mut bank = PromptBank::new();
+
+    // 1. Add
+    step(1, "Add a prompt");
+    println!(
+        "Prompts have a name, a category, and content. Anything written as\n\
+         {} becomes a variable you fill in later.",
+        "{{variable}}".magenta()
+    );
+    let name: String = Input::new()
+        .with_prompt("Name for your prompt")
+        .default("code-review".to_string())
+        .interact_text()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+    let prompt = Prompt::new(
+        name.clone(),
+        PromptCategory::Task,
+        "Tutorial prompt".to_string(),
+        SAMPLE_CONTENT.to_string(),
+        vec!["tutorial".to_string()],
+    );
+    bank.add(prompt);
+    storage.save(&bank)?;
+    println!("\n{} Added '{}' with this content:\n", "✓".green(), name);
+    print_content(SAMPLE_CONTENT);
+    hint(&format!(
+        "promptbank add --name {} --category task --file review.md",
+        name
+    ));
+
+    // 2. Apply
+    step(2, "Render it with variables");
+    let prompt = bank.get(&name).expect("prompt was just added");
+    let mut values = Vec::new();
+    for variable in &prompt.variables {
+        let value: String = Input::new()
+            .with_prompt(format!("  {}", variable))
+            .interact_text()
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        values.push((variable.clone(), value));
+    }
+    println!();
+    print_content(&prompt.render(&values, Includes::default())?);
+    let vars: Vec<String> = values
+        .iter()
+        .map(|(k, v)| format!("--var \"{}={}\"", k, v))
+        .collect();
+    hint(&format!(
+        "promptbank apply {} {} --copy",
+        name,
+        vars.join(" ")
+    ));
+
+    // 3. Edit
+    step(3, "Edit it");
+    println!("Editing opens your $EDITOR. Variables are re-detected when you save.");
+    if confirm("Open the prompt in your editor now?")? {
+        let prompt = bank.get_mut(&name).expect("prompt was just added");
+        let edited = Config::load()?
+            .editor()
+            .edit(&prompt.content)
+            .map_err(|e| PromptBankError::InvalidInput(e.to_string()))?;
+        match edited {
+            Some(content) if content != prompt.content => {
+                prompt.update_content(content);
+                storage.save(&bank)?;
+                let prompt = bank.get(&name).expect("prompt was just added");
+                println!(
+                    "\n{} Saved. Variables now: {}",
+                    "✓".green(),
+                    if prompt.variables.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        prompt.variables.join(", ").magenta().to_string()
+                    }
+                );
+            }
+            _ => println!("{}", "No changes made.".yellow()),
+        }
+    }
+    hint(&format!("promptbank edit {}", name));
+
+    // 4. Install
+    step(4, "Install it for Claude Code");
+    println!(
+        "Prompts can be installed as skills or slash commands in a project's .claude directory."
+    );
+    let prompt = bank.get(&name).expect("prompt was just added");
+    let claude = ClaudeIntegration::for_project(&sandbox.project())?;
+    let path = claude.install(prompt, InstallType::Skill, TUTORIAL_BANK)?;
+    println!(
+        "\n{} Installed a skill into a scratch project:\n",
+        "✓".green()
+    );
+    print_content(&fs::read_to_string(&path)?);
+    hint(&format!(
+        "promptbank claude install {} --as-type skill --project .",
+        name
+    ));
+
+    println!("\n{}", "That's the whole loop!".bold());
+    println!(
+        "The temporary bank at {} is being removed.\n\
+         Next: try {} or browse everything with {}.",
+        sandbox.root.display(),
+        "promptbank add".cyan(),
+        "promptbank tui".cyan()
+    );
+    Ok(())
+}
+
+fn step(number: usize, title: &str) {
+    println!(
+        "\n{} {}",
+        format!("Step {}:", number).bold().cyan(),
+        title.bold()
+    );
+}
+
+fn hint(command: &str) {
+    println!(
+        "\n  {} {}",
+        "From the command line:".dimmed(),
+        command.cyan()
+    );
+}
+
+fn print_content(content: &str) {
+    println!("{}", "─".repeat(50).dimmed());
+    println!("{}", content.trim_end());
+    println!("{}", "─".repeat(50).dimmed());
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    Confirm::new()
+        .with_prompt(question)
+        .default(true)
+        .interact()
+        .map_err(|e| PromptBankError::InvalidInput(e.to_string()))
+}